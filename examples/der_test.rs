@@ -1,6 +1,5 @@
 #[macro_use]
 extern crate eagre_asn1 as asn1;
-extern crate byteorder;
 
 #[derive(Debug)]
 struct User {
@@ -19,7 +19,7 @@ impl DER for Foo {
         asn1::der::ContentType::Constructed
     }
 
-    fn der_encode_content(&self, w: &mut dyn ::std::io::Write) -> ::std::io::Result<()> {
+    fn der_encode_content(&self, w: &mut dyn asn1::io::Write) -> asn1::io::Result<()> {
         self.a
             .der_intermediate()?
             .encode_explicit(12, asn1::der::Class::Application, w)?;
@@ -29,7 +29,7 @@ impl DER for Foo {
         Ok(())
     }
 
-    fn der_decode_content(r: &mut dyn ::std::io::Read, _: usize) -> ::std::io::Result<Self> {
+    fn der_decode_content(r: &mut dyn asn1::io::Read, _: usize) -> asn1::io::Result<Self> {
         let a: i32 =
             asn1::der::DER::der_from_intermediate(asn1::der::Intermediate::decode_explicit(r)?.2)?;
         let b: bool =
@@ -1,8 +1,9 @@
 #[macro_use]
 extern crate eagre_asn1 as asn1;
 
-use asn1::xer::XEREncodeable;
+use asn1::xer::{XERDecodeable, XEREncodeable};
 
+#[derive(Debug, PartialEq)]
 struct User {
     pub name: String,
     pub id: i32,
@@ -15,7 +16,9 @@ fn main() {
         name: "Rahix".to_string(),
         id: 12,
     };
-    let mut stream = ::std::io::Cursor::new(Vec::<u8>::new());
+    let mut stream = asn1::io::Cursor::new(Vec::<u8>::new());
     foo.xer_encode(&mut stream).unwrap();
-    println!("{}", String::from_utf8(stream.into_inner()).unwrap());
+    let text = String::from_utf8(stream.into_inner()).unwrap();
+    println!("{}", text);
+    assert_eq!(foo, User::xer_from_str(&text).unwrap());
 }
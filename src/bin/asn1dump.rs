@@ -0,0 +1,33 @@
+//! Print a human readable tree of DER or BER encoded data
+//!
+//! Usage: `asn1dump [FILE]`, reads from stdin if no file (or `-`) is given.
+
+use std::io::{self, Read, Write};
+use std::{env, fs, process};
+
+use eagre_asn1::der::dump;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() > 1 || args.iter().any(|a| a == "-h" || a == "--help") {
+        eprintln!("Usage: asn1dump [FILE]");
+        process::exit(2);
+    }
+
+    let mut bytes = vec![];
+    let result = match args.first().map(String::as_str) {
+        None | Some("-") => io::stdin().read_to_end(&mut bytes),
+        Some(path) => fs::File::open(path).and_then(|mut f| f.read_to_end(&mut bytes)),
+    };
+    if let Err(e) = result {
+        eprintln!("asn1dump: {}", e);
+        process::exit(1);
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    if let Err(e) = dump::dump(&bytes, &mut out).and_then(|_| out.flush()) {
+        eprintln!("asn1dump: {}", e);
+        process::exit(1);
+    }
+}
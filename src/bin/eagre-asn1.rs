@@ -0,0 +1,103 @@
+//! Inspect and convert ASN.1 encoded files
+//!
+//! Run `eagre-asn1 --help` for a list of commands. Every command reads from the given file or from
+//! stdin if no file (or `-`) is given.
+
+use std::io::{self, Read, Write};
+use std::{env, fs, process};
+
+use eagre_asn1::der::{dump, Node};
+use eagre_asn1::pem;
+
+const USAGE: &str = "Usage: eagre-asn1 <COMMAND> [ARGS]
+
+Commands:
+    dump [FILE]              Print the element tree of DER or BER data
+    pem2der [FILE]           Convert the first PEM block to DER
+    der2pem <LABEL> [FILE]   Wrap DER data in a PEM block with the given label
+    hex [FILE]               Print the data as hex
+    extract <PATH> [FILE]    Write the element at PATH (for example 0/2/1) as DER
+    check [FILE]             Check that the data is a single strict DER element";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("eagre-asn1: {}", e);
+        process::exit(1);
+    }
+}
+
+fn usage_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, USAGE)
+}
+
+fn read_input(path: Option<&String>) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    match path.map(String::as_str) {
+        None | Some("-") => io::stdin().read_to_end(&mut bytes)?,
+        Some(path) => fs::File::open(path)?.read_to_end(&mut bytes)?,
+    };
+    Ok(bytes)
+}
+
+fn run(args: &[String]) -> io::Result<()> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => return Err(usage_error()),
+    };
+    // Commands with a leading positional argument take the file as second argument
+    let (arg, file) = match command {
+        "der2pem" | "extract" => match args {
+            [arg] => (Some(arg), None),
+            [arg, file] => (Some(arg), Some(file)),
+            _ => return Err(usage_error()),
+        },
+        _ => match args {
+            [] => (None, None),
+            [file] => (None, Some(file)),
+            _ => return Err(usage_error()),
+        },
+    };
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match command {
+        "dump" => dump::dump(&read_input(file)?, &mut out)?,
+        "pem2der" => {
+            let text = String::from_utf8(read_input(file)?)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "PEM is not utf8"))?;
+            out.write_all(&pem::decode(&text)?.data)?;
+        }
+        "der2pem" => {
+            let label = arg.unwrap();
+            out.write_all(pem::encode(label, &read_input(file)?).as_bytes())?;
+        }
+        "hex" => {
+            for line in read_input(file)?.chunks(32) {
+                for byte in line {
+                    write!(out, "{:02X}", byte)?;
+                }
+                writeln!(out)?;
+            }
+        }
+        "extract" => {
+            let path = arg.unwrap();
+            let bytes = read_input(file)?;
+            let tree = Node::parse(&bytes)?;
+            let node = tree.get(path).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no element at path {}", path),
+                )
+            })?;
+            out.write_all(&bytes[node.offset..node.offset + node.encoded_length()])?;
+        }
+        "check" => {
+            Node::parse(&read_input(file)?)?.check_der()?;
+            writeln!(out, "OK")?;
+        }
+        "-h" | "--help" | "help" => writeln!(out, "{}", USAGE)?,
+        _ => return Err(usage_error()),
+    }
+    out.flush()
+}
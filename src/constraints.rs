@@ -0,0 +1,458 @@
+use crate::der::Component;
+use crate::io;
+use crate::prelude::*;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::any::type_name;
+use core::error::Error;
+use core::fmt;
+
+/// Subtype constraints of a value
+///
+/// Constraints are only visible in the packed encodings, where e.g. an `INTEGER (0..7)` is encoded
+/// in three bits. Every bound is optional, `Constraints::NONE` does not constrain anything.
+///
+/// Components of `der_sequence!` and `der_choice!` can be annotated with constraints, which are
+/// then checked by every encoding, see `der_sequence!`.
+///
+/// # Example
+/// ```
+/// # use eagre_asn1::constraints::Constraints;
+/// // INTEGER (0..255)
+/// let byte = Constraints::value(0, 255);
+/// assert!(byte.check_value(256).is_err());
+///
+/// // OCTET STRING (SIZE(16))
+/// let key = Constraints::size(16, 16);
+/// assert!(key.check_size(16).is_ok());
+/// assert_eq!(key.to_string(), "SIZE(16)");
+///
+/// // PrintableString (SIZE(1..64)) (FROM("0123456789"))
+/// let digits = Constraints::size(1, 64).with_alphabet("0123456789");
+/// assert!(digits.check_str("12a").is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Constraints {
+    /// Lower bound of the value
+    pub min: Option<i64>,
+    /// Upper bound of the value
+    pub max: Option<i64>,
+    /// Lower bound of the size, in items of the type
+    pub size_min: Option<usize>,
+    /// Upper bound of the size, in items of the type
+    pub size_max: Option<usize>,
+    /// Permitted alphabet of a character string
+    pub alphabet: Option<&'static str>,
+}
+
+impl Constraints {
+    /// No constraints at all
+    pub const NONE: Constraints = Constraints {
+        min: None,
+        max: None,
+        size_min: None,
+        size_max: None,
+        alphabet: None,
+    };
+
+    /// Value range constraint `(min..max)`
+    pub const fn value(min: i64, max: i64) -> Constraints {
+        Constraints::NONE.with_value(min, max)
+    }
+
+    /// Size constraint `SIZE(min..max)`
+    pub const fn size(min: usize, max: usize) -> Constraints {
+        Constraints::NONE.with_size(min, max)
+    }
+
+    /// Add a value range constraint
+    pub const fn with_value(self, min: i64, max: i64) -> Constraints {
+        Constraints {
+            min: Some(min),
+            max: Some(max),
+            ..self
+        }
+    }
+
+    /// Add a size constraint
+    pub const fn with_size(self, min: usize, max: usize) -> Constraints {
+        Constraints {
+            size_min: Some(min),
+            size_max: Some(max),
+            ..self
+        }
+    }
+
+    /// Add a permitted alphabet constraint `FROM("...")`
+    pub const fn with_alphabet(self, chars: &'static str) -> Constraints {
+        Constraints {
+            alphabet: Some(chars),
+            ..self
+        }
+    }
+
+    fn violation(&self, what: String) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            ConstraintError {
+                violation: format!("{} violates constraint {}", what, self),
+                field: None,
+            },
+        )
+    }
+
+    /// Check that `value` satisfies the value range
+    pub fn check_value(&self, value: i64) -> io::Result<()> {
+        if self.min.is_some_and(|min| value < min) || self.max.is_some_and(|max| value > max) {
+            return Err(self.violation(format!("value {}", value)));
+        }
+        Ok(())
+    }
+
+    /// Check that `size` satisfies the size constraint
+    pub fn check_size(&self, size: usize) -> io::Result<()> {
+        if self.size_min.is_some_and(|min| size < min)
+            || self.size_max.is_some_and(|max| size > max)
+        {
+            return Err(self.violation(format!("size {}", size)));
+        }
+        Ok(())
+    }
+
+    /// Check that only constraints of the permitted kinds are set, the others do not apply to `T`
+    pub fn check_applicable<T: ?Sized>(
+        &self,
+        value: bool,
+        size: bool,
+        alphabet: bool,
+    ) -> io::Result<()> {
+        let (kind, inapplicable) = if !value && (self.min.is_some() || self.max.is_some()) {
+            let c = Constraints {
+                min: self.min,
+                max: self.max,
+                ..Constraints::NONE
+            };
+            ("value", c)
+        } else if !size && (self.size_min.is_some() || self.size_max.is_some()) {
+            let c = Constraints {
+                size_min: self.size_min,
+                size_max: self.size_max,
+                ..Constraints::NONE
+            };
+            ("size", c)
+        } else if !alphabet && self.alphabet.is_some() {
+            let c = Constraints {
+                alphabet: self.alphabet,
+                ..Constraints::NONE
+            };
+            ("permitted alphabet", c)
+        } else {
+            return Ok(());
+        };
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            ConstraintError {
+                violation: format!(
+                    "{} constraint {} does not apply to {}",
+                    kind,
+                    inapplicable,
+                    type_name::<T>()
+                ),
+                field: None,
+            },
+        ))
+    }
+
+    /// Check the size in characters and the permitted alphabet of a character string
+    pub fn check_str(&self, s: &str) -> io::Result<()> {
+        self.check_size(s.chars().count())?;
+        if let Some(bad) = self
+            .alphabet
+            .and_then(|alphabet| s.chars().find(|c| !alphabet.contains(*c)))
+        {
+            return Err(self.violation(format!("character {:?}", bad)));
+        }
+        Ok(())
+    }
+}
+
+fn write_bound<T: fmt::Display>(
+    f: &mut fmt::Formatter,
+    bound: Option<T>,
+    name: &str,
+) -> fmt::Result {
+    match bound {
+        Some(bound) => write!(f, "{}", bound),
+        None => write!(f, "{}", name),
+    }
+}
+
+/// Writes the constraints in ASN.1 notation, like `(0..7) SIZE(1..4)`
+impl fmt::Display for Constraints {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = self.min.is_some() || self.max.is_some();
+        if value {
+            write!(f, "(")?;
+            write_bound(f, self.min, "MIN")?;
+            write!(f, "..")?;
+            write_bound(f, self.max, "MAX")?;
+            write!(f, ")")?;
+        }
+        if self.size_min.is_some() || self.size_max.is_some() {
+            if value {
+                write!(f, " ")?;
+            }
+            write!(f, "SIZE(")?;
+            if self.size_min.is_none() || self.size_min != self.size_max {
+                write_bound(f, self.size_min, "0")?;
+                write!(f, "..")?;
+            }
+            write_bound(f, self.size_max, "MAX")?;
+            write!(f, ")")?;
+        }
+        if let Some(alphabet) = self.alphabet {
+            if value || self.size_min.is_some() || self.size_max.is_some() {
+                write!(f, " ")?;
+            }
+            write!(f, "FROM({:?})", alphabet)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error of a value violating its constraints
+///
+/// The error is wrapped in an `io::Error`. If the value is a component of a sequence or choice,
+/// the error names the component.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintError {
+    violation: String,
+    field: Option<String>,
+}
+
+impl ConstraintError {
+    /// Return the violated constraint, like `value 8 violates constraint (0..7)`
+    pub fn violation(&self) -> &str {
+        &self.violation
+    }
+
+    /// Return the component with the invalid value, like `Message.id`
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+}
+
+impl fmt::Display for ConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.field {
+            Some(ref field) => write!(f, "{}: {}", field, self.violation),
+            None => write!(f, "{}", self.violation),
+        }
+    }
+}
+
+impl Error for ConstraintError {}
+
+/// Name `field` of `owner` in constraint errors of `result` that do not name a component yet
+pub fn in_field<T>(result: io::Result<T>, field: &str, owner: &str) -> io::Result<T> {
+    result.map_err(|e| {
+        let error = match e
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<ConstraintError>())
+        {
+            Some(error) if error.field.is_none() => ConstraintError {
+                violation: error.violation.clone(),
+                field: Some(format!("{}.{}", owner, field)),
+            },
+            _ => return e,
+        };
+        io::Error::new(e.kind(), error)
+    })
+}
+
+/// Check `value` of component `field` of `owner` against constraints `c`
+pub fn check_field<T: Constrained>(
+    value: &T,
+    c: &Constraints,
+    field: &str,
+    owner: &str,
+) -> io::Result<()> {
+    in_field(value.check_constraints(c), field, owner)
+}
+
+/// Types that can be checked against constraints
+///
+/// Constraints of a kind that does not apply to the type are an error, like a size constraint on
+/// an integer. The default implementation accepts no constraints at all. Types implementing
+/// `DER` through `der_sequence!`, `der_choice!` or `der_enumerated!` implement this trait
+/// automatically.
+pub trait Constrained {
+    /// Check this value against constraints `c`
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(false, false, false)
+    }
+}
+
+impl Constrained for bool {}
+
+impl Constrained for i32 {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(true, false, false)?;
+        c.check_value(i64::from(*self))
+    }
+}
+
+impl Constrained for String {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(false, true, true)?;
+        c.check_str(self)
+    }
+}
+
+impl Constrained for Vec<u8> {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(false, true, false)?;
+        c.check_size(self.len())
+    }
+}
+
+/// Items of a `SEQUENCE OF` are not constrained
+impl<T: Constrained> Constrained for Vec<T> {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(false, true, false)?;
+        c.check_size(self.len())
+    }
+}
+
+impl<const N: usize> Constrained for [u8; N] {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(false, true, false)?;
+        c.check_size(N)
+    }
+}
+
+impl<T: Constrained, const N: usize> Constrained for [T; N] {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(false, true, false)?;
+        c.check_size(N)
+    }
+}
+
+/// Components of tuples are not constrained
+macro_rules! constrained_tuple {
+    () => {};
+    ($first:ident $($ty:ident)*) => {
+        impl<$first, $($ty),*> Constrained for ($first, $($ty,)*) {}
+
+        constrained_tuple!($($ty)*);
+    };
+}
+
+constrained_tuple!(A B C D E F G H I J K L);
+
+/// Smart pointers are checked like the value they point to
+macro_rules! constrained_pointer {
+    ($($pointer:ident $(<$lt:lifetime>)?),+) => {$(
+        impl<T: Constrained> Constrained for $pointer<$($lt,)? T> {
+            fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+                (**self).check_constraints(c)
+            }
+        }
+    )+};
+}
+
+constrained_pointer!(Box, Rc, Arc, Component<'_>);
+
+/// Absent values satisfy every constraint
+impl<T: Constrained> Constrained for Option<T> {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        match self {
+            Some(value) => value.check_constraints(c),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Build `Constraints` from the annotations of `der_sequence!` and `der_choice!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! der_constraints {
+    (@bound MAX) => {
+        None
+    };
+    (@bound $($bound:tt)+) => {
+        Some($($bound)+)
+    };
+    // Check a component against its annotations, a component without any needs no `Constrained`
+    (@check $value:expr, $field:expr, $owner:expr;) => {};
+    (@check $value:expr, $field:expr, $owner:expr; $($c:tt)+) => {
+        $crate::constraints::check_field($value, &$crate::der_constraints!($($c)+), $field, $owner)?;
+    };
+    ($(VALUE ($min:literal .. $($max:tt)+))? $(SIZE ($size_min:literal $(.. $($size_max:tt)+)?))? $(FROM ($alphabet:literal))?) => {{
+        #[allow(unused_mut)]
+        let mut c = $crate::constraints::Constraints::NONE;
+        $(
+            c.min = Some($min);
+            c.max = $crate::der_constraints!(@bound $($max)+);
+        )?
+        $(
+            c.size_min = Some($size_min);
+            c.size_max = Some($size_min);
+            $(c.size_max = $crate::der_constraints!(@bound $($size_max)+);)?
+        )?
+        $(c.alphabet = Some($alphabet);)?
+        c
+    }};
+}
+
+#[test]
+fn constraint_checks() {
+    let c = Constraints::value(-1, 7).with_size(1, 4);
+    assert!(c.check_value(-1).is_ok());
+    assert!(c.check_value(8).is_err());
+    assert!(c.check_size(0).is_err());
+    assert_eq!(c.to_string(), "(-1..7) SIZE(1..4)");
+    let semi = Constraints {
+        min: Some(0),
+        ..Constraints::NONE
+    };
+    assert_eq!(semi.to_string(), "(0..MAX)");
+    assert_eq!(
+        semi.check_value(-5).unwrap_err().to_string(),
+        "value -5 violates constraint (0..MAX)"
+    );
+    assert!(Constraints::NONE.check_size(usize::MAX).is_ok());
+    let alphabet = Constraints::NONE.with_alphabet("ab");
+    assert_eq!(alphabet.to_string(), "FROM(\"ab\")");
+    assert_eq!(
+        alphabet.check_str("abc").unwrap_err().to_string(),
+        "character 'c' violates constraint FROM(\"ab\")"
+    );
+}
+
+#[test]
+fn constraint_annotations() {
+    assert_eq!(der_constraints!(), Constraints::NONE);
+    assert_eq!(der_constraints!(VALUE(-1..7)), Constraints::value(-1, 7));
+    assert_eq!(der_constraints!(VALUE(0..MAX)).to_string(), "(0..MAX)");
+    assert_eq!(der_constraints!(SIZE(16)), Constraints::size(16, 16));
+    assert_eq!(
+        der_constraints!(SIZE(1..MAX) FROM("01")).to_string(),
+        "SIZE(1..MAX) FROM(\"01\")"
+    );
+
+    let error = in_field(Constraints::value(0, 7).check_value(8), "id", "Message").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Message.id: value 8 violates constraint (0..7)"
+    );
+    // The innermost component is named
+    let error = in_field(Err::<(), _>(error), "message", "Envelope").unwrap_err();
+    let error = error
+        .get_ref()
+        .unwrap()
+        .downcast_ref::<ConstraintError>()
+        .unwrap();
+    assert_eq!(error.field(), Some("Message.id"));
+    assert_eq!(error.violation(), "value 8 violates constraint (0..7)");
+}
@@ -0,0 +1,188 @@
+use super::*;
+use bytes::BytesMut;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Frames bigger than this are rejected unless another maximum is set
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 1 << 20;
+
+/// `tokio-util` codec for streams of DER values, one top-level TLV per frame
+///
+/// Frame boundaries are found from the tag and length octets, so frames can arrive in any number
+/// of pieces. Every frame is decoded as a `T`. Frames longer than the maximum frame size are an
+/// error when encoding and decoding, before any of the content is buffered.
+///
+/// # Example
+/// ```
+/// # use bytes::BytesMut;
+/// # use eagre_asn1::der::codec::DerCodec;
+/// # use tokio_util::codec::{Decoder, Encoder};
+/// let mut codec = DerCodec::<i32>::new();
+/// let mut buf = BytesMut::new();
+/// codec.encode(300, &mut buf).unwrap();
+/// assert_eq!(&buf[..], &[0x02, 0x02, 0x01, 0x2C]);
+///
+/// let mut partial = buf.split_to(3);
+/// assert_eq!(codec.decode(&mut partial).unwrap(), None);
+/// partial.unsplit(buf);
+/// assert_eq!(codec.decode(&mut partial).unwrap(), Some(300));
+/// assert!(partial.is_empty());
+/// ```
+pub struct DerCodec<T> {
+    max_frame_size: usize,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<T> DerCodec<T> {
+    /// Create a DerCodec with the `DEFAULT_MAX_FRAME_SIZE`
+    pub fn new() -> DerCodec<T> {
+        DerCodec::with_max_frame_size(DEFAULT_MAX_FRAME_SIZE)
+    }
+
+    /// Create a DerCodec accepting frames of at most `max_frame_size` bytes, header included
+    pub fn with_max_frame_size(max_frame_size: usize) -> DerCodec<T> {
+        DerCodec {
+            max_frame_size,
+            item: PhantomData,
+        }
+    }
+
+    /// Return the maximum frame size in bytes
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    fn too_big(&self, size: usize) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "frame of {} bytes exceeds the maximum frame size of {} bytes",
+                size, self.max_frame_size
+            ),
+        )
+    }
+}
+
+impl<T> Default for DerCodec<T> {
+    fn default() -> DerCodec<T> {
+        DerCodec::new()
+    }
+}
+
+impl<T> Clone for DerCodec<T> {
+    fn clone(&self) -> DerCodec<T> {
+        DerCodec::with_max_frame_size(self.max_frame_size)
+    }
+}
+
+impl<T> fmt::Debug for DerCodec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DerCodec")
+            .field("max_frame_size", &self.max_frame_size)
+            .finish()
+    }
+}
+
+impl<T: DER> Decoder for DerCodec<T> {
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<T>> {
+        let length = match partial::der_element_length(src)? {
+            Partial::Done(_, length) => length,
+            // Only very long tags can keep the header incomplete for that long
+            Partial::NeedMore(_) if src.len() > self.max_frame_size => {
+                return Err(self.too_big(src.len()))
+            }
+            Partial::NeedMore(_) => return Ok(None),
+        };
+        if length > self.max_frame_size {
+            return Err(self.too_big(length));
+        }
+        if src.len() < length {
+            src.reserve(length - src.len());
+            return Ok(None);
+        }
+        let frame = src.split_to(length);
+        T::der_decode(&mut &frame[..]).map(Some)
+    }
+}
+
+impl<T: DER> Encoder<T> for DerCodec<T> {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> io::Result<()> {
+        let bytes = item.der_bytes()?;
+        if bytes.len() > self.max_frame_size {
+            return Err(self.too_big(bytes.len()));
+        }
+        dst.extend_from_slice(&bytes);
+        Ok(())
+    }
+}
+
+#[test]
+fn split_frames() {
+    let mut codec = DerCodec::<String>::new();
+    let mut buf = BytesMut::new();
+    codec.encode("first".to_string(), &mut buf).unwrap();
+    codec.encode("x".repeat(300), &mut buf).unwrap();
+    let encoded = buf.split();
+
+    // Feed the encoding byte by byte, both frames come out exactly once
+    let mut decoded = vec![];
+    for byte in encoded.iter() {
+        buf.extend_from_slice(&[*byte]);
+        if let Some(item) = codec.decode(&mut buf).unwrap() {
+            decoded.push(item);
+        }
+    }
+    assert_eq!(decoded, vec!["first".to_string(), "x".repeat(300)]);
+    assert!(buf.is_empty());
+
+    // A header announcing a too big frame is rejected before the content arrives
+    let mut codec = DerCodec::<String>::with_max_frame_size(16);
+    let mut header = BytesMut::from(&[0x0C, 0x82, 0x01, 0x00][..]);
+    assert_eq!(
+        codec.decode(&mut header).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+    let mut tag = BytesMut::from(&[0x1F; 20][..]);
+    assert!(codec.decode(&mut tag).is_err());
+    assert!(codec.encode("x".repeat(20), &mut buf).is_err());
+}
+
+#[test]
+fn framed_duplex() {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        // A tiny buffer makes the frames arrive in pieces
+        let (client, server) = tokio::io::duplex(3);
+        let mut writer = FramedWrite::new(client, DerCodec::<Vec<i32>>::new());
+        let mut reader = FramedRead::new(server, DerCodec::<Vec<i32>>::new());
+        let messages = vec![vec![1, 2, 3], vec![], vec![-70000; 50]];
+
+        let send = async {
+            for message in messages.clone() {
+                writer.send(message).await.unwrap();
+            }
+            writer.close().await.unwrap();
+        };
+        let receive = async {
+            let mut received = vec![];
+            while let Some(message) = reader.next().await {
+                received.push(message.unwrap());
+            }
+            received
+        };
+        let ((), received) = futures_util::future::join(send, receive).await;
+        assert_eq!(received, messages);
+    });
+}
@@ -1,5 +1,8 @@
-use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use std::io::{self, Read, Write};
+use crate::io::{self, Read, Write};
+use crate::io::{ReadOctet, WriteOctet};
+use crate::prelude::*;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
 
 use super::*;
 
@@ -14,7 +17,7 @@ use super::*;
 /// # Example Implementation #
 /// ```
 /// # use eagre_asn1::der::*;
-/// # use std::io::{self, Read, Write};
+/// # use eagre_asn1::io::{self, Read, Write};
 ///
 /// # struct Null;
 /// impl DER for Null {
@@ -39,6 +42,12 @@ use super::*;
 /// }
 /// ```
 pub trait DER: Sized {
+    /// Whether a tag on this type has to be `EXPLICIT`, `true` for `CHOICE` and `ANY`
+    ///
+    /// `AUTOMATIC` and `IMPLICIT` default tagging fall back to `EXPLICIT` tags for these types,
+    /// `IMPLICIT` tags are rejected at compile time by the macros.
+    const DER_EXPLICIT_ONLY: bool = false;
+
     /// Return universal tag of this type
     fn der_universal_tag() -> UniversalTag;
     /// Return content type of this type
@@ -85,6 +94,89 @@ pub trait DER: Sized {
         let mut stream = io::Cursor::new(bytes);
         Self::der_decode(&mut stream)
     }
+    /// Create object from a buffer that may hold only the beginning of its encoding
+    ///
+    /// Returns `Partial::NeedMore` instead of failing if the tag, length or content bytes are
+    /// incomplete. The returned length tells how many bytes of `buf` the object took.
+    fn der_decode_partial(buf: &[u8]) -> io::Result<Partial<Self>> {
+        match Intermediate::decode_partial(buf)? {
+            Partial::Done(i, length) => Ok(Partial::Done(Self::der_from_intermediate(i)?, length)),
+            Partial::NeedMore(needed) => Ok(Partial::NeedMore(needed)),
+        }
+    }
+    /// Return whether an untagged element with `class` and `tag` is an encoding of this type
+    ///
+    /// Used to find out whether `OPTIONAL` components of a sequence are present.
+    fn der_matches(class: Class, tag: u32) -> bool {
+        class == Class::Universal && tag == Self::der_universal_tag() as u32
+    }
+    /// Value to use if this type is missing from a sequence, `Some` only for optional types
+    fn der_absent() -> Option<Self> {
+        None
+    }
+    /// Return whether this value is encoded at all, `false` only for absent optional values
+    fn der_present(&self) -> bool {
+        true
+    }
+    /// Return the tags an encoding of this type can have, empty if it can have any tag
+    ///
+    /// Types have a single universal tag, except for `CHOICE` types, which have the tags of all
+    /// their alternatives.
+    fn der_tags() -> Vec<(Class, u32)> {
+        vec![(Class::Universal, Self::der_universal_tag() as u32)]
+    }
+}
+
+/// Object-safe companion of `DER` for encoding values of different types
+///
+/// `DER` needs `Sized` and has functions without `self`, so there is no `dyn DER`. This trait is
+/// implemented for every `DER` type and can be used as `Box<dyn DynEncode>` instead.
+///
+/// # Example
+/// ```
+/// # use eagre_asn1::der::*;
+/// let values: Vec<Box<dyn DynEncode>> = vec![Box::new(7), Box::new(true)];
+/// let mut content = vec![];
+/// for value in values.iter() {
+///     value.dyn_encode(&mut content).unwrap();
+/// }
+/// assert_eq!(content, vec![0x02, 0x01, 0x07, 0x01, 0x01, 0xFF]);
+/// assert_eq!(values[1].dyn_tag().unwrap(), (Class::Universal, 1));
+/// ```
+pub trait DynEncode {
+    /// Create Intermediate from this object
+    fn dyn_intermediate(&self) -> io::Result<Intermediate>;
+    /// Fully encode into stream ( tag bytes + length bytes + content bytes )
+    fn dyn_encode(&self, w: &mut dyn Write) -> io::Result<()>;
+    /// Return the class and tag this value is encoded with
+    fn dyn_tag(&self) -> io::Result<(Class, u32)>;
+    /// Return fully encoded bytes
+    fn dyn_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut stream = Vec::new();
+        self.dyn_encode(&mut stream)?;
+        Ok(stream)
+    }
+}
+
+impl<T: DER> DynEncode for T {
+    fn dyn_intermediate(&self) -> io::Result<Intermediate> {
+        self.der_intermediate()
+    }
+
+    fn dyn_encode(&self, w: &mut dyn Write) -> io::Result<()> {
+        self.der_encode(w)
+    }
+
+    fn dyn_tag(&self) -> io::Result<(Class, u32)> {
+        // Only choices and Any have more or less than one tag and need to look at the value
+        match T::der_tags()[..] {
+            [tag] => Ok(tag),
+            _ => {
+                let i = self.der_intermediate()?;
+                Ok((i.class, i.tag))
+            }
+        }
+    }
 }
 
 /// FooBar Cool
@@ -129,25 +221,15 @@ impl DER for i32 {
     }
 
     fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
-        let mut bytes = Vec::new();
-        bytes.write_i32::<BigEndian>(self.clone())?;
-        let i = 0;
-        loop {
-            if bytes[i] == 0
-                && i != (bytes.len() - 1)
-                && (bytes[i + 1] == 0 || bytes[i + 1] & 0x80 == 0)
-            {
-                bytes.remove(i);
-            } else if bytes[i] == 0xff
-                && i != (bytes.len() - 1)
-                && (bytes[i + 1] == 0xff || bytes[i + 1] & 0x80 == 0x80)
-            {
-                bytes.remove(i);
-            } else {
-                break;
-            }
+        let bytes = self.to_be_bytes();
+        let mut start = 0;
+        while start < bytes.len() - 1
+            && ((bytes[start] == 0 && bytes[start + 1] & 0x80 == 0)
+                || (bytes[start] == 0xff && bytes[start + 1] & 0x80 == 0x80))
+        {
+            start += 1;
         }
-        w.write(&bytes)?;
+        w.write_all(&bytes[start..])?;
         Ok(())
     }
 
@@ -203,7 +285,7 @@ impl DER for String {
     }
 
     fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
-        w.write(self.as_bytes())?;
+        w.write_all(self.as_bytes())?;
         Ok(())
     }
 
@@ -225,7 +307,7 @@ impl<'a> DER for &'a str {
     }
 
     fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
-        w.write(self.as_bytes())?;
+        w.write_all(self.as_bytes())?;
         Ok(())
     }
 
@@ -237,6 +319,28 @@ impl<'a> DER for &'a str {
     }
 }
 
+/// Borrowed octets can only be encoded, decode into `Vec<u8>` or `types::OctetString` instead
+impl DER for &[u8] {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::OctetString
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Primitive
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(self)
+    }
+
+    fn der_decode_content(_: &mut dyn Read, _: usize) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "borrowed octets can not be decoded",
+        ))
+    }
+}
+
 impl<T: DER> DER for Vec<T> {
     fn der_universal_tag() -> UniversalTag {
         UniversalTag::Sequence
@@ -273,7 +377,7 @@ impl DER for Vec<u8> {
     }
 
     fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
-        w.write(self)?;
+        w.write_all(self)?;
         Ok(())
     }
 
@@ -283,3 +387,272 @@ impl DER for Vec<u8> {
         Ok(buffer)
     }
 }
+
+/// `OPTIONAL` components, absent values are left out of the enclosing sequence
+impl<T: DER> DER for Option<T> {
+    const DER_EXPLICIT_ONLY: bool = T::DER_EXPLICIT_ONLY;
+
+    fn der_universal_tag() -> UniversalTag {
+        T::der_universal_tag()
+    }
+
+    fn der_content() -> ContentType {
+        T::der_content()
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        match self {
+            Some(value) => value.der_encode_content(w),
+            None => Err(absent_error()),
+        }
+    }
+
+    fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+        T::der_decode_content(r, length).map(Some)
+    }
+
+    fn der_intermediate(&self) -> io::Result<Intermediate> {
+        match self {
+            Some(value) => value.der_intermediate(),
+            None => Err(absent_error()),
+        }
+    }
+
+    fn der_from_intermediate(i: Intermediate) -> io::Result<Self> {
+        T::der_from_intermediate(i).map(Some)
+    }
+
+    fn der_matches(class: Class, tag: u32) -> bool {
+        T::der_matches(class, tag)
+    }
+
+    fn der_absent() -> Option<Self> {
+        Some(None)
+    }
+
+    fn der_tags() -> Vec<(Class, u32)> {
+        T::der_tags()
+    }
+
+    fn der_present(&self) -> bool {
+        self.is_some()
+    }
+}
+
+/// Implement `DER` for smart pointers, which are encoded like the value they point to
+macro_rules! der_pointer {
+    ($($pointer:ident $(<$lt:lifetime>)?),+) => {$(
+        impl<T: DER> DER for $pointer<$($lt,)? T> {
+            const DER_EXPLICIT_ONLY: bool = T::DER_EXPLICIT_ONLY;
+
+            fn der_universal_tag() -> UniversalTag {
+                T::der_universal_tag()
+            }
+
+            fn der_content() -> ContentType {
+                T::der_content()
+            }
+
+            fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+                (**self).der_encode_content(w)
+            }
+
+            fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+                T::der_decode_content(r, length).map($pointer::new)
+            }
+
+            fn der_intermediate(&self) -> io::Result<Intermediate> {
+                (**self).der_intermediate()
+            }
+
+            fn der_from_intermediate(i: Intermediate) -> io::Result<Self> {
+                T::der_from_intermediate(i).map($pointer::new)
+            }
+
+            fn der_matches(class: Class, tag: u32) -> bool {
+                T::der_matches(class, tag)
+            }
+
+            fn der_absent() -> Option<Self> {
+                T::der_absent().map($pointer::new)
+            }
+
+            fn der_tags() -> Vec<(Class, u32)> {
+                T::der_tags()
+            }
+
+            fn der_present(&self) -> bool {
+                (**self).der_present()
+            }
+        }
+    )+};
+}
+
+der_pointer!(Box, Rc, Arc, Component<'_>);
+
+/// A field of a struct variant in `der_choice!`, encoded like the value it holds
+///
+/// The variant is encoded through a private struct of these, which borrows the fields of the
+/// variant instead of cloning them. Decoding always produces owned values.
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum Component<'a, T> {
+    Borrowed(&'a T),
+    Owned(T),
+}
+
+impl<'a, T> Component<'a, T> {
+    /// Wrap a decoded value
+    pub fn new(value: T) -> Component<'a, T> {
+        Component::Owned(value)
+    }
+
+    /// Return the decoded value
+    pub fn into_owned(self) -> T {
+        match self {
+            Component::Owned(value) => value,
+            // Only values being encoded are borrowed
+            Component::Borrowed(_) => unreachable!(),
+        }
+    }
+}
+
+impl<'a, T> ::core::ops::Deref for Component<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match *self {
+            Component::Borrowed(value) => value,
+            Component::Owned(ref value) => value,
+        }
+    }
+}
+
+/// Values of any type, so that `Vec<Box<dyn DynEncode>>` is a `SEQUENCE` of them
+///
+/// Like `types::Any`, this type has no tag of its own. It can only be encoded, decoding fails.
+impl DER for Box<dyn DynEncode> {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::EOC
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Constructed
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(&self.dyn_intermediate()?.content)
+    }
+
+    fn der_decode_content(_: &mut dyn Read, _: usize) -> io::Result<Self> {
+        Err(dyn_decode_error())
+    }
+
+    fn der_intermediate(&self) -> io::Result<Intermediate> {
+        (**self).dyn_intermediate()
+    }
+
+    fn der_from_intermediate(_: Intermediate) -> io::Result<Self> {
+        Err(dyn_decode_error())
+    }
+
+    fn der_matches(_: Class, _: u32) -> bool {
+        true
+    }
+
+    fn der_tags() -> Vec<(Class, u32)> {
+        vec![]
+    }
+}
+
+fn dyn_decode_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "values of unknown type can not be decoded",
+    )
+}
+
+/// `SEQUENCE SIZE(N) OF`, decoding fails unless there are exactly `N` items
+impl<T: DER, const N: usize> DER for [T; N] {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::Sequence
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Constructed
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        for item in self.iter() {
+            item.der_encode(w)?;
+        }
+        Ok(())
+    }
+
+    fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+        into_array(Vec::<T>::der_decode_content(r, length)?)
+    }
+}
+
+/// `OCTET STRING SIZE(N)`
+impl<const N: usize> DER for [u8; N] {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::OctetString
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Primitive
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(self)
+    }
+
+    fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+        into_array(Vec::<u8>::der_decode_content(r, length)?)
+    }
+}
+
+/// Implement `DER` for tuples, which are encoded as a `SEQUENCE` of all components in order
+macro_rules! der_tuple {
+    () => {};
+    (($first:ident $first_var:ident) $(($ty:ident $var:ident))*) => {
+        impl<$first: DER, $($ty: DER),*> DER for ($first, $($ty,)*) {
+            fn der_universal_tag() -> UniversalTag {
+                UniversalTag::Sequence
+            }
+
+            fn der_content() -> ContentType {
+                ContentType::Constructed
+            }
+
+            fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+                let ($first_var, $($var,)*) = self;
+                $first_var.der_encode(w)?;
+                $($var.der_encode(w)?;)*
+                Ok(())
+            }
+
+            fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+                let mut encoded = r.take(length as u64);
+                let $first_var = $first::der_decode(&mut encoded)?;
+                $(let $var = $ty::der_decode(&mut encoded)?;)*
+                if encoded.limit() > 0 {
+                    return Err(tuple_mismatch());
+                }
+                Ok(($first_var, $($var,)*))
+            }
+        }
+
+        der_tuple!($(($ty $var))*);
+    };
+}
+
+der_tuple!((A a) (B b) (C c) (D d) (E e) (F f) (G g) (H h) (I i) (J j) (K k) (L l));
+
+fn absent_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "absent optional value can not be encoded on its own",
+    )
+}
@@ -0,0 +1,400 @@
+use crate::io::{self, Write};
+use crate::prelude::*;
+use core::fmt::Write as FmtWrite;
+
+use super::tree::{decode_header, Header};
+use super::*;
+
+/// Number of content bytes shown in hex previews
+const HEX_PREVIEW_BYTES: usize = 16;
+/// Number of characters shown in string previews
+const STRING_PREVIEW_CHARS: usize = 64;
+
+/// Write a human readable tree of all elements contained in `bytes`
+///
+/// Every element is printed on its own line, showing its offset, the number of tag and length
+/// bytes, the content length, class, tag and a preview of the decoded value. Children of
+/// constructed elements are indented below their parent. BER indefinite length encoding is
+/// supported, in which case `NDEF` is shown instead of the length.
+///
+/// Malformed input does not abort the dump, the offending bytes are flagged as `<unparseable: ..>`
+/// and values which do not decode are flagged as `<invalid: ..>`. Only errors of the underlying
+/// writer are returned.
+///
+/// # Example
+/// ```
+/// # use eagre_asn1::der::*;
+/// let bytes = vec!["Hello".to_string()].der_bytes().unwrap();
+/// assert_eq!(
+///     dump::dump_string(&bytes),
+///     "    0  2      7: UNIVERSAL Sequence\n    2  2      5:   UNIVERSAL UTF8String \"Hello\"\n"
+/// );
+/// ```
+pub fn dump(bytes: &[u8], w: &mut dyn Write) -> io::Result<()> {
+    dump_elements(bytes, 0, 0, false, w)?;
+    Ok(())
+}
+
+/// Return the dump of `bytes` as a string, see [`dump`]
+pub fn dump_string(bytes: &[u8]) -> String {
+    let mut buf = vec![];
+    dump(bytes, &mut buf).expect("writing into a vector can not fail");
+    String::from_utf8(buf).expect("dump output is always valid utf8")
+}
+
+/// Dump a list of elements and return the number of bytes consumed
+fn dump_elements(
+    bytes: &[u8],
+    offset: usize,
+    depth: usize,
+    until_eoc: bool,
+    w: &mut dyn Write,
+) -> io::Result<usize> {
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let rest = &bytes[pos..];
+        if until_eoc && rest.starts_with(&[0x00, 0x00]) {
+            return Ok(pos + 2);
+        }
+        let header = match decode_header(rest) {
+            Ok(header) => header,
+            Err(e) => {
+                flag_unparseable(offset + pos, depth, rest, &e.to_string(), w)?;
+                return Ok(bytes.len());
+            }
+        };
+        let content = &rest[header.length..];
+        if header.content_type == ContentType::Constructed && depth >= MAX_NESTING_DEPTH {
+            flag_unparseable(
+                offset + pos,
+                depth,
+                rest,
+                "elements are nested too deeply",
+                w,
+            )?;
+            return Ok(bytes.len());
+        }
+        match header.content_length {
+            None if header.content_type == ContentType::Constructed => {
+                write_line(offset + pos, &header, "NDEF", depth, w)?;
+                writeln!(w)?;
+                let used =
+                    dump_elements(content, offset + pos + header.length, depth + 1, true, w)?;
+                pos += header.length + used;
+            }
+            None => {
+                flag_unparseable(
+                    offset + pos,
+                    depth,
+                    rest,
+                    "primitive element with indefinite length",
+                    w,
+                )?;
+                return Ok(bytes.len());
+            }
+            Some(length) if length > content.len() => {
+                flag_unparseable(
+                    offset + pos,
+                    depth,
+                    rest,
+                    "element content exceeds available data",
+                    w,
+                )?;
+                return Ok(bytes.len());
+            }
+            Some(length) => {
+                let content = &content[..length];
+                write_line(offset + pos, &header, &length.to_string(), depth, w)?;
+                if header.content_type == ContentType::Constructed {
+                    writeln!(w)?;
+                    dump_elements(content, offset + pos + header.length, depth + 1, false, w)?;
+                } else {
+                    match preview(&header, content) {
+                        Ok(ref p) if p.is_empty() => writeln!(w)?,
+                        Ok(p) => writeln!(w, " {}", p)?,
+                        Err(e) => writeln!(w, " <invalid: {}> {}", e, hex_preview(content))?,
+                    }
+                }
+                pos += header.length + length;
+            }
+        }
+    }
+    if until_eoc {
+        flag_unparseable(offset + pos, depth, &[], "missing end-of-contents", w)?;
+    }
+    Ok(pos)
+}
+
+fn write_line(
+    offset: usize,
+    header: &Header,
+    length: &str,
+    depth: usize,
+    w: &mut dyn Write,
+) -> io::Result<()> {
+    let class = match header.class {
+        Class::Universal => "UNIVERSAL",
+        Class::Application => "APPLICATION",
+        Class::ContextSpecific => "CONTEXT",
+        Class::Private => "PRIVATE",
+    };
+    let tag = match UniversalTag::from_tag(header.tag) {
+        Some(tag) if header.class == Class::Universal => format!("{:?}", tag),
+        _ => header.tag.to_string(),
+    };
+    write!(
+        w,
+        "{:>5} {:>2} {:>6}: {:indent$}{} {}",
+        offset,
+        header.length,
+        length,
+        "",
+        class,
+        tag,
+        indent = depth * 2
+    )
+}
+
+fn flag_unparseable(
+    offset: usize,
+    depth: usize,
+    bytes: &[u8],
+    reason: &str,
+    w: &mut dyn Write,
+) -> io::Result<()> {
+    writeln!(
+        w,
+        "{:>5} {:>2} {:>6}: {:indent$}<unparseable: {}> {}",
+        offset,
+        "",
+        bytes.len(),
+        "",
+        reason,
+        hex_preview(bytes),
+        indent = depth * 2
+    )
+}
+
+fn hex_preview(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for byte in bytes.iter().take(HEX_PREVIEW_BYTES) {
+        if !s.is_empty() {
+            s.push(' ');
+        }
+        write!(s, "{:02X}", byte).unwrap();
+    }
+    if bytes.len() > HEX_PREVIEW_BYTES {
+        s.push_str(" ...");
+    }
+    s
+}
+
+fn string_preview(s: &str) -> String {
+    if s.chars().count() > STRING_PREVIEW_CHARS {
+        let short: String = s.chars().take(STRING_PREVIEW_CHARS).collect();
+        format!("{:?} ...", short)
+    } else {
+        format!("{:?}", s)
+    }
+}
+
+fn preview(header: &Header, content: &[u8]) -> Result<String, &'static str> {
+    let tag = match UniversalTag::from_tag(header.tag) {
+        Some(tag) if header.class == Class::Universal => tag,
+        _ => return Ok(hex_preview(content)),
+    };
+    Ok(match tag {
+        UniversalTag::Boolean => match content {
+            [0x00] => "FALSE".to_string(),
+            [_] => "TRUE".to_string(),
+            _ => return Err("boolean must be exactly one byte"),
+        },
+        UniversalTag::Integer | UniversalTag::Enumerated => integer_preview(content)?,
+        UniversalTag::Null => {
+            if !content.is_empty() {
+                return Err("null with content");
+            }
+            String::new()
+        }
+        UniversalTag::ObjectIdentifier => oid_preview(content, true)?,
+        UniversalTag::RelativeOID => oid_preview(content, false)?,
+        UniversalTag::BitString => match content.split_first() {
+            Some((&unused, _)) if unused > 7 => return Err("more than 7 unused bits"),
+            Some((&unused, bits)) => format!("unused {}: {}", unused, hex_preview(bits)),
+            None => return Err("bit string without unused bits byte"),
+        },
+        UniversalTag::UTF8String => {
+            string_preview(::core::str::from_utf8(content).map_err(|_| "invalid utf8")?)
+        }
+        UniversalTag::UTCTime | UniversalTag::GeneralizedTime => {
+            let s = ::core::str::from_utf8(content).map_err(|_| "time is not ascii")?;
+            match time_preview(s, tag) {
+                Some(time) => format!("{} ({})", string_preview(s), time),
+                None => string_preview(s),
+            }
+        }
+        UniversalTag::BMPString => {
+            let chunks = content.chunks_exact(2);
+            if !chunks.remainder().is_empty() {
+                return Err("odd number of bytes in BMPString");
+            }
+            let units: Vec<u16> = chunks.map(|c| (c[0] as u16) << 8 | c[1] as u16).collect();
+            string_preview(&String::from_utf16(&units).map_err(|_| "invalid utf16")?)
+        }
+        UniversalTag::UniversalString => {
+            let chunks = content.chunks_exact(4);
+            if !chunks.remainder().is_empty() {
+                return Err("number of bytes in UniversalString not divisible by 4");
+            }
+            let s = chunks
+                .map(|c| {
+                    ::core::char::from_u32(
+                        (c[0] as u32) << 24
+                            | (c[1] as u32) << 16
+                            | (c[2] as u32) << 8
+                            | c[3] as u32,
+                    )
+                })
+                .collect::<Option<String>>()
+                .ok_or("invalid character in UniversalString")?;
+            string_preview(&s)
+        }
+        UniversalTag::NumericString
+        | UniversalTag::PrintableString
+        | UniversalTag::T61String
+        | UniversalTag::VideotexString
+        | UniversalTag::IA5String
+        | UniversalTag::GraphicString
+        | UniversalTag::VisibleString
+        | UniversalTag::GeneralString
+        | UniversalTag::CharacterString
+        | UniversalTag::ObjectDescriptor => match ::core::str::from_utf8(content) {
+            Ok(s) => string_preview(s),
+            Err(_) => hex_preview(content),
+        },
+        _ => hex_preview(content),
+    })
+}
+
+fn integer_preview(content: &[u8]) -> Result<String, &'static str> {
+    if content.is_empty() {
+        return Err("integer without content");
+    }
+    if content.len() > 8 {
+        return Ok(hex_preview(content));
+    }
+    let mut value: i64 = if content[0] & 0x80 != 0 { -1 } else { 0 };
+    for byte in content {
+        value = value << 8 | *byte as i64;
+    }
+    Ok(value.to_string())
+}
+
+fn oid_preview(content: &[u8], absolute: bool) -> Result<String, &'static str> {
+    let mut arcs = vec![];
+    let mut arc: u64 = 0;
+    for (i, byte) in content.iter().enumerate() {
+        if arc > (u64::MAX >> 7) {
+            return Err("object identifier arc too big");
+        }
+        arc = arc << 7 | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        } else if i == content.len() - 1 {
+            return Err("truncated object identifier arc");
+        }
+    }
+    if arcs.is_empty() {
+        return Err("empty object identifier");
+    }
+    if absolute {
+        let first = arcs[0];
+        let (root, second) = if first < 80 {
+            (first / 40, first % 40)
+        } else {
+            (2, first - 80)
+        };
+        arcs[0] = second;
+        arcs.insert(0, root);
+    }
+    Ok(arcs
+        .iter()
+        .map(|a| a.to_string())
+        .collect::<Vec<_>>()
+        .join("."))
+}
+
+fn time_preview(s: &str, tag: UniversalTag) -> Option<String> {
+    let digits = s.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        UniversalTag::UTCTime => {
+            let year: u32 = digits.get(0..2)?.parse().ok()?;
+            (
+                if year >= 50 { 1900 + year } else { 2000 + year },
+                &digits[2..],
+            )
+        }
+        _ => (digits.get(0..4)?.parse().ok()?, &digits[4..]),
+    };
+    let (rest, fraction) = match rest.find('.') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, ""),
+    };
+    if rest.len() < 6 || rest.len() > 10 || rest.len() % 2 != 0 {
+        return None;
+    }
+    if !rest.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let part = |i: usize| rest.get(i..i + 2).unwrap_or("00");
+    Some(format!(
+        "{:04}-{}-{} {}:{}:{}{} UTC",
+        year,
+        part(0),
+        part(2),
+        part(4),
+        part(6),
+        part(8),
+        fraction
+    ))
+}
+
+#[test]
+fn dump_values() {
+    let bytes = vec![
+        0x30, 0x17, 0x02, 0x02, 0xFF, 0x7F, 0x06, 0x03, 0x2A, 0x86, 0x48, 0xA0, 0x80, 0x01, 0x01,
+        0xFF, 0x00, 0x00, 0x17, 0x05, 0x39, 0x39, 0x31, 0x32, 0x33,
+    ];
+    assert_eq!(
+        dump_string(&bytes),
+        "    0  2     23: UNIVERSAL Sequence
+    2  2      2:   UNIVERSAL Integer -129
+    6  2      3:   UNIVERSAL ObjectIdentifier 1.2.840
+   11  2   NDEF:   CONTEXT 0
+   13  2      1:     UNIVERSAL Boolean TRUE
+   18  2      5:   UNIVERSAL UTCTime \"99123\"
+"
+    );
+}
+
+#[test]
+fn dump_flags_garbage() {
+    let output = dump_string(&[0x30, 0x03, 0x05, 0x01, 0x00, 0x04, 0x02, 0x01]);
+    assert_eq!(
+        output,
+        "    0  2      3: UNIVERSAL Sequence
+    2  2      1:   UNIVERSAL Null <invalid: null with content> 00
+    5         3: <unparseable: element content exceeds available data> 04 02 01
+"
+    );
+}
+
+#[test]
+fn dump_flags_deep_nesting() {
+    let output = dump_string(&[0x30, 0x80].repeat(100_000));
+    let lines: Vec<&str> = output.lines().collect();
+    // All open elements but the innermost one also miss their end-of-contents
+    assert_eq!(lines.len(), 2 * MAX_NESTING_DEPTH);
+    assert!(lines[MAX_NESTING_DEPTH].contains("<unparseable: elements are nested too deeply>"));
+}
@@ -0,0 +1,103 @@
+use super::*;
+use crate::io;
+
+/// Encoder writing DER into a buffer of `N` bytes, without allocating
+///
+/// Values are appended one after another. The content octets are written straight into the
+/// buffer, so encoding does not allocate as long as the content encoder of the type does not,
+/// which is true for `bool`, `i32`, `Null` and `&[u8]`. Choices and `Any` are tagged by their
+/// value and are encoded through their `Intermediate`, which allocates.
+///
+/// # Example
+/// ```
+/// # use eagre_asn1::der::FixedEncoder;
+/// let mut encoder = FixedEncoder::<8>::new();
+/// encoder.encode(true).unwrap();
+/// encoder.encode(-2).unwrap();
+/// assert_eq!(encoder.as_bytes(), &[0x01, 0x01, 0xFF, 0x02, 0x01, 0xFE]);
+/// assert!(encoder.encode(1000).is_err());
+/// assert_eq!(encoder.len(), 6);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct FixedEncoder<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FixedEncoder<N> {
+    /// Create an empty FixedEncoder
+    pub const fn new() -> FixedEncoder<N> {
+        FixedEncoder {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Append the encoding of `value`, leaving the buffer as it was if it does not fit
+    pub fn encode<T: DER + Copy>(&mut self, value: T) -> io::Result<()> {
+        let full = || io::Error::new(io::ErrorKind::WriteZero, "value does not fit into buffer");
+        let fit = |e: io::Error| match e.kind() {
+            io::ErrorKind::WriteZero => full(),
+            _ => e,
+        };
+        // The content goes first, the header is moved in front of it once its length is known
+        let free = &mut self.buf[self.len..];
+        let available = free.len();
+        let mut w: &mut [u8] = free;
+        let (class, tag, content_type) = if T::DER_EXPLICIT_ONLY {
+            // Choices and Any take their tag from the value, only their Intermediate carries it
+            let i = value.der_intermediate()?;
+            io::Write::write_all(&mut w, &i.content).map_err(fit)?;
+            (i.class, i.tag, i.content_type)
+        } else {
+            value.der_encode_content(&mut w).map_err(fit)?;
+            (
+                Class::Universal,
+                T::der_universal_tag() as u32,
+                T::der_content(),
+            )
+        };
+        let content = available - w.len();
+
+        // At most 6 bytes of tag and 1 + 8 bytes of length
+        let mut header = [0; 15];
+        let mut w: &mut [u8] = &mut header;
+        der_encode_tag_bytes(tag, class, content_type, &mut w)?;
+        der_encode_length_bytes(content, &mut w)?;
+        let header_len = 15 - w.len();
+        if header_len + content > available {
+            return Err(full());
+        }
+        self.buf
+            .copy_within(self.len..self.len + content, self.len + header_len);
+        self.buf[self.len..self.len + header_len].copy_from_slice(&header[..header_len]);
+        self.len += header_len + content;
+        Ok(())
+    }
+
+    /// Return the encoded bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Number of encoded bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether nothing was encoded yet
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all encoded bytes
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for FixedEncoder<N> {
+    fn default() -> FixedEncoder<N> {
+        FixedEncoder::new()
+    }
+}
@@ -1,11 +1,12 @@
-use std::io::{self, Read, Write};
+use crate::io::{self, Read, Write};
+use crate::prelude::*;
 
 use super::*;
 
 /// Intermediate Type
 ///
 /// Intermediate type necessary for tagging, etc.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Intermediate {
     /// Class of this encoded object
     pub class: Class,
@@ -50,7 +51,7 @@ impl Intermediate {
     pub fn encode(&self, w: &mut dyn Write) -> io::Result<()> {
         der_encode_tag_bytes(self.tag, self.class, self.content_type, w)?;
         der_encode_length_bytes(self.content.len(), w)?;
-        w.write(&self.content)?;
+        w.write_all(&self.content)?;
         Ok(())
     }
 
@@ -61,7 +62,7 @@ impl Intermediate {
         self.encode(&mut stream)?;
         let data = stream.into_inner();
         der_encode_length_bytes(data.len(), w)?;
-        w.write(&data)?;
+        w.write_all(&data)?;
         Ok(())
     }
 
@@ -69,10 +70,24 @@ impl Intermediate {
     pub fn encode_implicit(&self, tag: u32, class: Class, w: &mut dyn Write) -> io::Result<()> {
         der_encode_tag_bytes(tag, class, self.content_type, w)?;
         der_encode_length_bytes(self.content.len(), w)?;
-        w.write(&self.content)?;
+        w.write_all(&self.content)?;
         Ok(())
     }
 
+    /// Wrap this Intermediate into a constructed one with an explicit tag
+    pub fn into_explicit(self, tag: u32, class: Class) -> io::Result<Intermediate> {
+        let mut content = vec![];
+        self.encode(&mut content)?;
+        Ok(Intermediate::new(class, ContentType::Constructed, tag).with_content(content))
+    }
+
+    /// Replace the tag of this Intermediate for implicit tagging
+    pub fn into_implicit(mut self, tag: u32, class: Class) -> Intermediate {
+        self.tag = tag;
+        self.class = class;
+        self
+    }
+
     /// Decode an Intermediate
     pub fn decode(r: &mut dyn Read) -> io::Result<Intermediate> {
         let (_, tag, class, content_type) = der_decode_tag_bytes(r)?;
@@ -88,6 +103,23 @@ impl Intermediate {
         })
     }
 
+    /// Decode an Intermediate from a buffer that may hold only its beginning
+    ///
+    /// Nothing is read from `buf`, the returned length tells how many bytes the Intermediate took.
+    pub fn decode_partial(buf: &[u8]) -> io::Result<Partial<Intermediate>> {
+        let length = match partial::der_element_length(buf)? {
+            Partial::Done(_, length) => length,
+            Partial::NeedMore(needed) => return Ok(Partial::NeedMore(needed)),
+        };
+        if buf.len() < length {
+            return Ok(Partial::NeedMore(length - buf.len()));
+        }
+        Ok(Partial::Done(
+            Intermediate::decode(&mut &buf[..length])?,
+            length,
+        ))
+    }
+
     /// Decode an Intermediate using explicit tagging
     pub fn decode_explicit(r: &mut dyn Read) -> io::Result<(u32, Class, Intermediate)> {
         let (_, tag, class, _) = der_decode_tag_bytes(r)?;
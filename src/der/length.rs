@@ -1,5 +1,5 @@
-use byteorder::{ReadBytesExt, WriteBytesExt};
-use std::io::{self, Read, Write};
+use crate::io::{self, Read, Write};
+use crate::io::{ReadOctet, WriteOctet};
 
 /// Encode DER length bytes
 pub fn der_encode_length_bytes(length: usize, w: &mut dyn Write) -> io::Result<()> {
@@ -30,7 +30,7 @@ pub fn der_decode_length_bytes(r: &mut dyn Read) -> io::Result<(usize, usize)> {
     if (first_byte & 0x80) != 0 {
         // Long form
         let length_length = first_byte & 0x7F;
-        if (length_length as u64 * 8) > (usize::max_value() as f64).log2() as u64 {
+        if (length_length as u64 * 8) > u64::from(usize::BITS) {
             // Afl found
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "too big size"));
         }
@@ -7,6 +7,19 @@
 /// * `IMPLICIT TAG <CLASS> <TAG>;` is used for implicit tagging
 /// * `NOTAG` is used if no tagging is required
 ///
+/// Like an ASN.1 module, the macro accepts a tagging environment after the name, as in
+/// `SomeStruct AUTOMATIC TAGS:`, `SomeStruct IMPLICIT TAGS:` or `SomeStruct EXPLICIT TAGS:`. Fields
+/// may then leave out `NOTAG` and give only `TAG <CLASS> <TAG>;` to use the default tag type.
+/// `AUTOMATIC TAGS` numbers all fields by position with `CONTEXT` tags, unless any field has a
+/// tag. Then, as in X.680, no field is numbered and the environment works like `IMPLICIT TAGS`.
+/// `IMPLICIT` and `AUTOMATIC` defaults use `EXPLICIT` tags for `CHOICE` and `ANY` types, as X.680
+/// requires.
+/// `der_choice!` accepts the same environments.
+///
+/// Fields of type `Option<T>` are `OPTIONAL` components, they are left out if `None`. When
+/// decoding, an optional component is only taken if the next element carries its tag, so
+/// consecutive optional components need distinct tags.
+///
 /// `<CLASS>` is replaced by one of
 ///
 /// * `UNIVERSAL` for 00
@@ -14,6 +27,30 @@
 /// * `CONTEXT` for 10
 /// * `PRIVATE` for 11
 ///
+/// Between the tag and the type, a field can be annotated with constraints, in this order:
+///
+/// * `VALUE(<MIN>..<MAX>);` for a value range like `INTEGER (0..255)`, `<MAX>` may be `MAX`
+/// * `SIZE(<MIN>..<MAX>);` or `SIZE(<N>);` for a size constraint like `OCTET STRING (SIZE(16))`
+/// * `FROM("<CHARACTERS>");` for the permitted alphabet of a character string
+///
+/// Constraints are checked when encoding and decoding in every encoding rule, the error names the
+/// field like `SomeStruct.bar: value 300 violates constraint (0..255)`. PER and OER also use them
+/// for the encoding itself.
+///
+/// After the constraints, `DEFINED BY <FIELD>;` marks a field of an open type, like
+/// `ANY DEFINED BY`. Its type is chosen by the `ObjectIdentifier` in the earlier field `<FIELD>`,
+/// see `types::OpenType` and `der_open_type!`.
+///
+/// A trailing `...` marks the sequence as extensible. Components of a newer version that follow
+/// the last field are skipped when decoding, `... KEEP <FIELD>` keeps them in a field of type
+/// `Vec<Intermediate>` instead. Kept components are written back by DER only. PER and OER add the
+/// extension bit to the encoding.
+///
+/// Only DER is implemented by default, so fields may be of any type implementing `DER`. The other
+/// encoding rules are listed after the name, as in `SomeStruct WITH(XER, JER, PER, OER):` or
+/// `SomeStruct WITH(PER) AUTOMATIC TAGS:`, and then have to be implemented by every field type.
+/// `der_choice!` and `der_enumerated!` accept the same list.
+///
 /// # Example
 ///
 /// ```
@@ -29,8 +66,8 @@
 ///
 /// der_sequence! {
 ///     SomeStruct:
-///         foo: EXPLICIT TAG APPLICATION 42; TYPE String,
-///         bar: NOTAG TYPE i32,
+///         foo: EXPLICIT TAG APPLICATION 42; SIZE(1..64); TYPE String,
+///         bar: NOTAG VALUE(0..255); TYPE i32,
 /// }
 ///
 /// # fn main() {
@@ -43,6 +80,9 @@
 /// // Send to far away planet
 /// let decoded = SomeStruct::der_from_bytes(encoded).unwrap();
 /// assert_eq!(data, decoded);
+///
+/// let invalid = SomeStruct { bar: 300, ..data };
+/// assert!(invalid.der_bytes().is_err());
 /// # }
 /// ```
 ///
@@ -52,8 +92,30 @@
 /// welcome :)
 #[macro_export]
 macro_rules! der_sequence {
-    ($struct_name:ident : $($field_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $field_type:ty),+) => {
-        impl $crate::der::DER for $struct_name {
+    ($struct_name:ident WITH ($($codec:ident),* $(,)?) $($rest:tt)+) => {
+        $crate::der_sequence!(@with [$($codec)*] $struct_name $($rest)+);
+    };
+    ($struct_name:ident $($rest:tt)+) => {
+        $crate::der_sequence!(@with [] $struct_name $($rest)+);
+    };
+    (@with $codecs:tt $struct_name:ident $env:ident TAGS : $($fields:tt)+) => {
+        $crate::der_tagging!($env der_sequence $struct_name $codecs: $($fields)+);
+    };
+    (@with $codecs:tt $struct_name:ident : $($field_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? TYPE $field_type:ty),+) => {
+        $crate::der_sequence!(@sequence $codecs $struct_name [false]: $($field_name: $tagtype $(TAG $tagclass $tagval;)* $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? TYPE $field_type),+);
+    };
+    (@with $codecs:tt $struct_name:ident : $($field_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? TYPE $field_type:ty),+,) => {
+        $crate::der_sequence!(@sequence $codecs $struct_name [false]: $($field_name: $tagtype $(TAG $tagclass $tagval;)* $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? TYPE $field_type),+);
+    };
+    (@with $codecs:tt $struct_name:ident : $($field_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? TYPE $field_type:ty),+, ... $(KEEP $unknown:ident)? $(,)?) => {
+        $crate::der_sequence!(@sequence $codecs $struct_name [true $(, $unknown)?]: $($field_name: $tagtype $(TAG $tagclass $tagval;)* $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? TYPE $field_type),+);
+    };
+    (@sequence [$($codec:ident)*] $struct_name:ident $(<$lt:lifetime>)? [$extensible:tt $(, $unknown:ident)?] : $($field_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? TYPE $field_type:ty),+) => {
+        // X.680 forbids IMPLICIT tags on CHOICE and ANY types, their own tag would be lost
+        $(const _: () = assert!(!($crate::der::is_implicit(stringify!($tagtype)) && <$field_type as $crate::der::DER>::DER_EXPLICIT_ONLY),
+                                "IMPLICIT tag on a CHOICE or ANY type");)+
+
+        impl $crate::der::DER for $struct_name $(<$lt>)? {
             fn der_universal_tag() -> $crate::der::UniversalTag {
                 $crate::der::UniversalTag::Sequence
             }
@@ -62,11 +124,12 @@ macro_rules! der_sequence {
                 $crate::der::ContentType::Constructed
             }
 
-            fn der_encode_content(&self, w: &mut dyn ::std::io::Write) -> ::std::io::Result<()> {
+            fn der_encode_content(&self, w: &mut dyn $crate::io::Write) -> $crate::io::Result<()> {
                 //use $crate::der::DER;
-                $({
+                $(if $crate::der::DER::der_present(&self.$field_name) {
+                    $crate::der_constraints!(@check &self.$field_name, stringify!($field_name), stringify!($struct_name); $(VALUE $value)? $(SIZE $size)? $(FROM $from)?);
                     let i = self.$field_name.der_intermediate()?;
-                    match stringify!($tagtype) {
+                    match $crate::der::tagging(stringify!($tagtype), <$field_type as $crate::der::DER>::DER_EXPLICIT_ONLY) {
                         "NOTAG" => i.encode(w)?,
                         $("EXPLICIT" => i.encode_explicit($tagval, match stringify!($tagclass) {
                                                                               "UNIVERSAL" => $crate::der::Class::Universal,
@@ -86,29 +149,102 @@ macro_rules! der_sequence {
                         _ => unreachable!(),
                     }
                 })+
+                $(for i in &self.$unknown {
+                    i.encode(w)?;
+                })?
                 Ok(())
             }
 
-            fn der_decode_content(r: &mut dyn ::std::io::Read, _: usize) -> ::std::io::Result<Self> {
+            fn der_decode_content(r: &mut dyn $crate::io::Read, length: usize) -> $crate::io::Result<Self> {
+                let mut content = $crate::prelude::vec![0; length];
+                $crate::io::Read::read_exact(r, &mut content)?;
+                let mut items = $crate::prelude::vec![];
+                let mut stream = &content[..];
+                while !stream.is_empty() {
+                    items.push($crate::der::Intermediate::decode(&mut stream)?);
+                }
+                let mut items = items.into_iter().peekable();
                 $(
-                    let i = match stringify!($tagtype) {
-                        "NOTAG" => $crate::der::Intermediate::decode(r)?,
-                        "EXPLICIT" => $crate::der::Intermediate::decode_explicit(r)?.2,
-                        "IMPLICIT" => $crate::der::Intermediate::decode_implicit(<$field_type>::der_universal_tag() as u32, $crate::der::Class::Universal, r)?.2,
-                        _ => unreachable!(),
+                    // Optional components are only taken if the next element has their tag
+                    let present = match items.peek() {
+                        Some(i) => match $crate::der::tagging(stringify!($tagtype), <$field_type as $crate::der::DER>::DER_EXPLICIT_ONLY) {
+                            "NOTAG" => <$field_type as $crate::der::DER>::der_matches(i.class, i.tag),
+                            $("EXPLICIT" | "IMPLICIT" => i.tag == $tagval && i.class == match stringify!($tagclass) {
+                                                                              "UNIVERSAL" => $crate::der::Class::Universal,
+                                                                              "APPLICATION" => $crate::der::Class::Application,
+                                                                              "CONTEXT" => $crate::der::Class::ContextSpecific,
+                                                                              "PRIVATE" => $crate::der::Class::Private,
+                                                                              _ => unreachable!(),
+                                                                          },)*
+                            _ => unreachable!(),
+                        },
+                        None => false,
                     };
-                    let $field_name : $field_type = $crate::der::DER::der_from_intermediate(i)?;
+                    let $field_name : $field_type = match <$field_type as $crate::der::DER>::der_absent() {
+                        Some(absent) if !present => absent,
+                        _ => {
+                            let i = items.next().ok_or_else(|| $crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                                $crate::prelude::format!("missing component {} in {}", stringify!($field_name), stringify!($struct_name))))?;
+                            let i = match $crate::der::tagging(stringify!($tagtype), <$field_type as $crate::der::DER>::DER_EXPLICIT_ONLY) {
+                                "NOTAG" => i,
+                                "EXPLICIT" => $crate::der::Intermediate::decode(&mut &i.content[..])?,
+                                "IMPLICIT" => {
+                                    let mut i = i;
+                                    i.tag = <$field_type as $crate::der::DER>::der_universal_tag() as u32;
+                                    i.class = $crate::der::Class::Universal;
+                                    i
+                                },
+                                _ => unreachable!(),
+                            };
+                            $crate::der::DER::der_from_intermediate(i)?
+                        },
+                    };
+                    $crate::der_constraints!(@check &$field_name, stringify!($field_name), stringify!($struct_name); $(VALUE $value)? $(SIZE $size)? $(FROM $from)?);
+                    $(let $field_name = $crate::types::OpenType::open_resolve($field_name, &$id)?;)?
                 )+
+                // Components after the last field are extension additions of a newer version
+                let extensible: bool = $extensible;
+                let rest: $crate::prelude::Vec<$crate::der::Intermediate> = items.collect();
+                if !extensible && !rest.is_empty() {
+                    return Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                        $crate::prelude::format!("unexpected component after the last field of {}", stringify!($struct_name))));
+                }
                 Ok($struct_name {
                     $(
                         $field_name: $field_name,
                     )+
+                    $(
+                        $unknown: rest,
+                    )?
                 })
             }
         }
+
+        impl $crate::constraints::Constrained for $struct_name $(<$lt>)? {}
+
+        $crate::der_sequence!(@codecs [$($codec)*] $struct_name $(<$lt>)? [$extensible $(, $unknown)?]
+                              { $($field_name $(BY $id)?: $field_type = [$(VALUE $value)? $(SIZE $size)? $(FROM $from)?]),+ });
+    };
+    // Implement the encoding rules listed in `WITH(...)` one by one
+    (@codecs [] $($rest:tt)*) => {};
+    (@codecs [$codec:ident $($more:ident)*] $struct_name:ident $(<$lt:lifetime>)? [$($extensible:tt)*] $fields:tt) => {
+        $crate::der_sequence!(@codec $codec $struct_name $(<$lt>)? [$($extensible)*] $fields);
+        $crate::der_sequence!(@codecs [$($more)*] $struct_name $(<$lt>)? [$($extensible)*] $fields);
+    };
+    (@codec XER $struct_name:ident $(<$lt:lifetime>)? [$($extensible:tt)*] { $($field_name:ident $(BY $id:ident)? : $field_type:ty = [$($c:tt)*]),+ }) => {
+        $crate::xer_sequence!($struct_name $(<$lt>)? [$($extensible)*]: $($field_name $(BY $id)? = [$($c)*]),+);
+    };
+    (@codec JER $struct_name:ident $(<$lt:lifetime>)? [$($extensible:tt)*] { $($field_name:ident $(BY $id:ident)? : $field_type:ty = [$($c:tt)*]),+ }) => {
+        $crate::jer_sequence!($struct_name $(<$lt>)? [$($extensible)*]: $($field_name $(BY $id)? = [$($c)*]),+);
+    };
+    (@codec PER $struct_name:ident $(<$lt:lifetime>)? [$($extensible:tt)*] { $($field_name:ident $(BY $id:ident)? : $field_type:ty = [$($c:tt)*]),+ }) => {
+        $crate::per_sequence!($struct_name $(<$lt>)? [$($extensible)*]: $($field_name $(BY $id)?: $field_type = [$($c)*]),+);
     };
-    ($struct_name:ident : $($field_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $field_type:ty),+,) => {
-        der_sequence!($struct_name: $($field_name: $tagtype $(TAG $tagclass $tagval;)* TYPE $field_type),+);
+    (@codec OER $struct_name:ident $(<$lt:lifetime>)? [$($extensible:tt)*] { $($field_name:ident $(BY $id:ident)? : $field_type:ty = [$($c:tt)*]),+ }) => {
+        $crate::oer_sequence!($struct_name $(<$lt>)? [$($extensible)*]: $($field_name $(BY $id)?: $field_type = [$($c)*]),+);
+    };
+    (@codec $codec:ident $($rest:tt)*) => {
+        compile_error!(concat!("unknown encoding rules ", stringify!($codec), ", expected XER, JER, PER or OER"));
     };
 }
 
@@ -116,32 +252,72 @@ macro_rules! der_sequence {
 ///
 /// Used like `der_sequence!` except that every variant has to have it's unique tag
 ///
+/// A choice has no tag of its own, it is encoded as the chosen alternative. Untagged, it can be used
+/// as field of a sequence, alternative of another choice or element of a `Vec`, as long as the
+/// tags of all its alternatives differ from their neighbours. `IMPLICIT` tags would replace the tag
+/// that identifies the alternative, so X.680 forbids them and the macros reject them:
+///
+/// ```compile_fail
+/// # #[macro_use]
+/// # extern crate eagre_asn1;
+/// enum Value {
+///     Number(i32),
+/// }
+///
+/// der_choice! {Value:
+///     Number: NOTAG TYPE i32,
+/// }
+///
+/// struct Holder {
+///     pub value: Value,
+/// }
+///
+/// // error: IMPLICIT tag on a CHOICE or ANY type
+/// der_sequence! {Holder:
+///     value: IMPLICIT TAG CONTEXT 0; TYPE Value,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// A trailing `... <VARIANT>` marks the choice as extensible, alternatives of a newer version are
+/// decoded into `<VARIANT>(Intermediate)` by DER and encoded back unchanged. The other encoding
+/// rules do not carry the tag of an unknown alternative, they fail to decode and encode it.
+///
+/// A variant without `TYPE` is a unit variant encoded as `NULL`. A variant followed by fields in
+/// braces, written like the fields of `der_sequence!`, is a struct variant encoded as a `SEQUENCE`
+/// of those fields. The sequence is a private struct named like the variant that borrows the
+/// fields while encoding, it hides any other type of that name in the macro.
+///
 /// # Example
 /// ```
 /// # #[macro_use]
 /// # extern crate eagre_asn1;
 /// # use eagre_asn1::der::DER;
-/// # use eagre_asn1::types::Null;
 ///
 /// # #[derive(Debug, PartialEq)]
 /// enum Command {
 ///     Forward(i32),
 ///     Rotate(i32),
-///     Start(Null),
-///     Stop(Null),
+///     Start,
+///     Stop,
+///     Goto { x: i32, y: i32 },
 /// }
 ///
 /// der_choice!{
 ///     Command: // Don't forget to make every variant have it's own tag
 ///         Forward: IMPLICIT TAG CONTEXT 1; TYPE i32,
 ///         Rotate:  IMPLICIT TAG CONTEXT 2; TYPE i32,
-///         Start:   IMPLICIT TAG CONTEXT 3; TYPE Null,
-///         Stop:    IMPLICIT TAG CONTEXT 4; TYPE Null,
+///         Start:   IMPLICIT TAG CONTEXT 3;,
+///         Stop:    IMPLICIT TAG CONTEXT 4;,
+///         Goto:    IMPLICIT TAG CONTEXT 5; {
+///             x: NOTAG TYPE i32,
+///             y: NOTAG TYPE i32,
+///         },
 /// }
 ///
 /// # fn main() {
-/// for cmd in vec!(Command::Start(Null), Command::Forward(12),
-///                 Command::Rotate(2), Command::Stop(Null)) {
+/// for cmd in vec!(Command::Start, Command::Forward(12), Command::Rotate(2),
+///                 Command::Goto { x: 3, y: 4 }, Command::Stop) {
 ///     let encoded = cmd.der_bytes().unwrap();
 ///     // Send to far away planet
 ///     let decoded = Command::der_from_bytes(encoded).unwrap();
@@ -151,8 +327,78 @@ macro_rules! der_sequence {
 /// ```
 #[macro_export]
 macro_rules! der_choice {
-    ($choice_name:ident : $($variant_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $variant_type:ty),+) => {
+    ($choice_name:ident WITH ($($codec:ident),* $(,)?) $($rest:tt)+) => {
+        $crate::der_choice!(@with [$($codec)*] $choice_name $($rest)+);
+    };
+    ($choice_name:ident $($rest:tt)+) => {
+        $crate::der_choice!(@with [] $choice_name $($rest)+);
+    };
+    (@with $codecs:tt $choice_name:ident $env:ident TAGS : $($variants:tt)+) => {
+        $crate::der_tagging!($env der_choice $choice_name $codecs: $($variants)+);
+    };
+    (@with $codecs:tt $choice_name:ident : $($variant_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(TYPE $variant_type:ty)? $({ $($fields:tt)* })?),+) => {
+        $crate::der_choice!(@choice $codecs $choice_name [false]: $($variant_name [$(TUPLE $variant_type)? $(STRUCT { $($fields)* })?]: $tagtype $(TAG $tagclass $tagval;)* $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)?
+                                                           TYPE $crate::der_choice!(@type $variant_name [$(TUPLE $variant_type)? $(STRUCT { $($fields)* })?])),+);
+    };
+    (@with $codecs:tt $choice_name:ident : $($variant_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(TYPE $variant_type:ty)? $({ $($fields:tt)* })?),+,) => {
+        $crate::der_choice!(@choice $codecs $choice_name [false]: $($variant_name [$(TUPLE $variant_type)? $(STRUCT { $($fields)* })?]: $tagtype $(TAG $tagclass $tagval;)* $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)?
+                                                           TYPE $crate::der_choice!(@type $variant_name [$(TUPLE $variant_type)? $(STRUCT { $($fields)* })?])),+);
+    };
+    (@with $codecs:tt $choice_name:ident : $($variant_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(TYPE $variant_type:ty)? $({ $($fields:tt)* })?),+, ... $($unknown:ident)? $(,)?) => {
+        $crate::der_choice!(@choice $codecs $choice_name [true $(, $unknown)?]: $($variant_name [$(TUPLE $variant_type)? $(STRUCT { $($fields)* })?]: $tagtype $(TAG $tagclass $tagval;)* $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)?
+                                                                     TYPE $crate::der_choice!(@type $variant_name [$(TUPLE $variant_type)? $(STRUCT { $($fields)* })?])),+);
+    };
+    // The type every alternative is encoded as, NULL for unit variants and a SEQUENCE of the same
+    // name for struct variants
+    (@type $variant_name:ident []) => { $crate::types::Null };
+    (@type $variant_name:ident [TUPLE $variant_type:ty]) => { $variant_type };
+    (@type $variant_name:ident [STRUCT $($fields:tt)*]) => { $variant_name<'_> };
+    (@struct $codecs:tt $variant_name:ident [STRUCT { $($field_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? TYPE $field_type:ty),+ $(,)? }]) => {
+        struct $variant_name<'a> {
+            $($field_name: $crate::der::Component<'a, $field_type>,)+
+        }
+
+        $crate::der_sequence!(@sequence $codecs $variant_name<'_> [false]: $($field_name: $tagtype $(TAG $tagclass $tagval;)* $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? TYPE $crate::der::Component<'_, $field_type>),+);
+    };
+    (@struct $codecs:tt $variant_name:ident [$($shape:tt)*]) => {};
+    // Match a variant, binding its payload to `$payload`
+    (@pattern $choice_name:ident $variant_name:ident [] $payload:ident) => { &$choice_name::$variant_name };
+    (@pattern $choice_name:ident $variant_name:ident [TUPLE $variant_type:ty] $payload:ident) => { &$choice_name::$variant_name(ref $payload) };
+    (@pattern $choice_name:ident $variant_name:ident [STRUCT { $($field_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? TYPE $field_type:ty),+ $(,)? }] $payload:ident) => {
+        &$choice_name::$variant_name { $(ref $field_name),+ }
+    };
+    (@ignore $choice_name:ident $variant_name:ident []) => { &$choice_name::$variant_name };
+    (@ignore $choice_name:ident $variant_name:ident [TUPLE $variant_type:ty]) => { &$choice_name::$variant_name(_) };
+    (@ignore $choice_name:ident $variant_name:ident [STRUCT $($fields:tt)*]) => { &$choice_name::$variant_name { .. } };
+    // A reference to the value the payload bound by `@pattern` is encoded as
+    (@payload $variant_name:ident [] $payload:ident) => { &$crate::types::Null };
+    (@payload $variant_name:ident [TUPLE $variant_type:ty] $payload:ident) => { $payload };
+    (@payload $variant_name:ident [STRUCT { $($field_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? TYPE $field_type:ty),+ $(,)? }] $payload:ident) => {
+        &$variant_name { $($field_name: $crate::der::Component::Borrowed($field_name)),+ }
+    };
+    // Build the variant from the decoded value
+    (@construct $choice_name:ident $variant_name:ident [] $value:ident) => {{
+        let $crate::types::Null = $value;
+        $choice_name::$variant_name
+    }};
+    (@construct $choice_name:ident $variant_name:ident [TUPLE $variant_type:ty] $value:ident) => { $choice_name::$variant_name($value) };
+    (@construct $choice_name:ident $variant_name:ident [STRUCT { $($field_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? TYPE $field_type:ty),+ $(,)? }] $payload:ident) => {{
+        let $variant_name { $($field_name),+ } = $payload;
+        $choice_name::$variant_name { $($field_name: $field_name.into_owned()),+ }
+    }};
+    (@choice $codecs:tt $choice_name:ident [$extensible:tt $(, $unknown:ident)?] : $($variant_name:ident [$($shape:tt)*] : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? TYPE $variant_type:ty),+) => {
+        // Struct variants are encoded through a struct of the same name, private to this block
+        const _: () = {
+        $($crate::der_choice!(@struct $codecs $variant_name [$($shape)*]);)+
+
+        // X.680 forbids IMPLICIT tags on CHOICE and ANY types, their own tag would be lost
+        $(const _: () = assert!(!($crate::der::is_implicit(stringify!($tagtype)) && <$variant_type as $crate::der::DER>::DER_EXPLICIT_ONLY),
+                                "IMPLICIT tag on a CHOICE or ANY type");)+
+
         impl $crate::der::DER for $choice_name {
+            // A CHOICE has no tag of its own, it is encoded as the chosen alternative
+            const DER_EXPLICIT_ONLY: bool = true;
+
             fn der_universal_tag() -> $crate::der::UniversalTag {
                 $crate::der::UniversalTag::EOC
             }
@@ -161,41 +407,53 @@ macro_rules! der_choice {
                 $crate::der::ContentType::Constructed
             }
 
-            fn der_encode_content(&self, w: &mut dyn ::std::io::Write) -> ::std::io::Result<()> {
-                //use $crate::der::DER;
+            fn der_encode_content(&self, w: &mut dyn $crate::io::Write) -> $crate::io::Result<()> {
+                w.write_all(&self.der_intermediate()?.content)
+            }
+
+            fn der_decode_content(_: &mut dyn $crate::io::Read, _: usize) -> $crate::io::Result<Self> {
+                Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                    $crate::prelude::format!("{} is a CHOICE and can only be decoded with its tag", stringify!($choice_name))))
+            }
+
+            fn der_intermediate(&self) -> $crate::io::Result<$crate::der::Intermediate> {
                 match self {
-                    $(&$choice_name::$variant_name(ref val) => {
+                    $($crate::der_choice!(@pattern $choice_name $variant_name [$($shape)*] payload) => {
+                        let val = $crate::der_choice!(@payload $variant_name [$($shape)*] payload);
+                        $crate::der_constraints!(@check val, stringify!($variant_name), stringify!($choice_name); $(VALUE $value)? $(SIZE $size)? $(FROM $from)?);
                         let i = val.der_intermediate()?;
-                        match stringify!($tagtype) {
-                            "NOTAG" => i.encode(w)?,
-                            $("EXPLICIT" => i.encode_explicit($tagval, match stringify!($tagclass) {
+                        Ok(match $crate::der::tagging(stringify!($tagtype), <$variant_type as $crate::der::DER>::DER_EXPLICIT_ONLY) {
+                            "NOTAG" => i,
+                            $("EXPLICIT" => i.into_explicit($tagval, match stringify!($tagclass) {
                                                                               "UNIVERSAL" => $crate::der::Class::Universal,
                                                                               "APPLICATION" => $crate::der::Class::Application,
                                                                               "CONTEXT" => $crate::der::Class::ContextSpecific,
                                                                               "PRIVATE" => $crate::der::Class::Private,
                                                                               _ => unreachable!(),
-                                }, w)?,
-                            "IMPLICIT" => i.encode_implicit($tagval, match stringify!($tagclass) {
+                                                                          })?,
+                            "IMPLICIT" => i.into_implicit($tagval, match stringify!($tagclass) {
                                                                               "UNIVERSAL" => $crate::der::Class::Universal,
                                                                               "APPLICATION" => $crate::der::Class::Application,
                                                                               "CONTEXT" => $crate::der::Class::ContextSpecific,
                                                                               "PRIVATE" => $crate::der::Class::Private,
                                                                               _ => unreachable!(),
-                                }, w)?,)*
+                                                                          }),)*
                             _ => unreachable!(),
-                    }
+                        })
                     },)+
+                    $(&$choice_name::$unknown(ref i) => Ok(i.clone()),)?
                 }
-                Ok(())
             }
 
-            fn der_decode_content(r: &mut dyn ::std::io::Read, _: usize) -> ::std::io::Result<Self> {
-                //use $crate::der::DER;
-                let i = $crate::der::Intermediate::decode(r)?;
+            fn der_from_intermediate(i: $crate::der::Intermediate) -> $crate::io::Result<Self> {
                 $(
-                    match stringify!($tagtype) {
-                        "NOTAG" => if i.tag == <$variant_type>::der_universal_tag() as u32 && i.class == $crate::der::Class::Universal {
-                            return Ok($choice_name::$variant_name(<$variant_type>::der_from_intermediate(i)?));
+                    let checked = |value: $variant_type| -> $crate::io::Result<Self> {
+                        $crate::der_constraints!(@check &value, stringify!($variant_name), stringify!($choice_name); $(VALUE $value)? $(SIZE $size)? $(FROM $from)?);
+                        Ok($crate::der_choice!(@construct $choice_name $variant_name [$($shape)*] value))
+                    };
+                    match $crate::der::tagging(stringify!($tagtype), <$variant_type as $crate::der::DER>::DER_EXPLICIT_ONLY) {
+                        "NOTAG" => if <$variant_type as $crate::der::DER>::der_matches(i.class, i.tag) {
+                            return checked(<$variant_type>::der_from_intermediate(i)?);
                         },
                         $("EXPLICIT" => if i.tag == $tagval && i.class == match stringify!($tagclass) {
                                                                               "UNIVERSAL" => $crate::der::Class::Universal,
@@ -204,7 +462,7 @@ macro_rules! der_choice {
                                                                               "PRIVATE" => $crate::der::Class::Private,
                                                                               _ => unreachable!(),
                                                                           } {
-                            return Ok($choice_name::$variant_name(<$variant_type>::der_from_bytes(i.content)?));
+                            return checked(<$variant_type>::der_from_bytes(i.content)?);
                         },
                         "IMPLICIT" => if i.tag == $tagval && i.class == match stringify!($tagclass) {
                                                                               "UNIVERSAL" => $crate::der::Class::Universal,
@@ -216,22 +474,104 @@ macro_rules! der_choice {
                             let mut i = i;
                             i.tag = <$variant_type>::der_universal_tag() as u32;
                             i.class = $crate::der::Class::Universal;
-                            return Ok($choice_name::$variant_name(<$variant_type>::der_from_intermediate(i)?));
+                            return checked(<$variant_type>::der_from_intermediate(i)?);
                         },)*
                         _ => unreachable!(),
                     }
                 )+
-                Err(::std::io::Error::new(::std::io::ErrorKind::InvalidInput, "Was not able to decode choice option"))
+                // Alternatives added by a newer version are kept as they are
+                let unknown: Option<fn($crate::der::Intermediate) -> Self> = None $(.or(Some($choice_name::$unknown)))?;
+                match unknown {
+                    Some(unknown) => Ok(unknown(i)),
+                    None => Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput, "Was not able to decode choice option")),
+                }
+            }
+
+            fn der_matches(class: $crate::der::Class, tag: u32) -> bool {
+                Self::der_tags().contains(&(class, tag))
+            }
+
+            fn der_tags() -> $crate::prelude::Vec<($crate::der::Class, u32)> {
+                let mut tags = $crate::prelude::vec![];
+                $(
+                    match stringify!($tagtype) {
+                        "NOTAG" => tags.extend(<$variant_type as $crate::der::DER>::der_tags()),
+                        _ => {$(
+                            tags.push((match stringify!($tagclass) {
+                                           "UNIVERSAL" => $crate::der::Class::Universal,
+                                           "APPLICATION" => $crate::der::Class::Application,
+                                           "CONTEXT" => $crate::der::Class::ContextSpecific,
+                                           "PRIVATE" => $crate::der::Class::Private,
+                                           _ => unreachable!(),
+                                       }, $tagval));
+                        )*},
+                    }
+                )+
+                tags
             }
         }
+
+        impl $crate::constraints::Constrained for $choice_name {}
+
+        $crate::der_choice!(@codecs $codecs $choice_name [$extensible $(, $unknown)?]
+                            { $($variant_name [$($shape)*]: $tagtype $(TAG $tagclass $tagval;)* TYPE $variant_type = [$(VALUE $value)? $(SIZE $size)? $(FROM $from)?]),+ });
+        };
     };
-    ($choice_name:ident : $($variant_name:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $variant_type:ty),+,) => {
-        der_choice!($choice_name: $($variant_name: $tagtype $(TAG $tagclass $tagval;)* TYPE $variant_type),+);
+    // Implement the encoding rules listed in `WITH(...)` one by one
+    (@codecs [] $($rest:tt)*) => {};
+    (@codecs [$codec:ident $($more:ident)*] $choice_name:ident $extensible:tt $variants:tt) => {
+        $crate::der_choice!(@codec $codec $choice_name $extensible $variants);
+        $crate::der_choice!(@codecs [$($more)*] $choice_name $extensible $variants);
+    };
+    (@codec XER $choice_name:ident [$($extensible:tt)*] { $($variant_name:ident [$($shape:tt)*] : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $variant_type:ty = [$($c:tt)*]),+ }) => {
+        $crate::xer_choice!($choice_name [$($extensible)*]: $($variant_name [$($shape)*] = [$($c)*]),+);
+    };
+    (@codec JER $choice_name:ident [$($extensible:tt)*] { $($variant_name:ident [$($shape:tt)*] : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $variant_type:ty = [$($c:tt)*]),+ }) => {
+        $crate::jer_choice!($choice_name [$($extensible)*]: $($variant_name [$($shape)*] = [$($c)*]),+);
+    };
+    (@codec PER $choice_name:ident [$($extensible:tt)*] { $($variant_name:ident [$($shape:tt)*] : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $variant_type:ty = [$($c:tt)*]),+ }) => {
+        $crate::per_choice!($choice_name [$($extensible)*]: $($variant_name [$($shape)*]: $tagtype $(TAG $tagclass $tagval;)* TYPE $variant_type = [$($c)*]),+);
+    };
+    (@codec OER $choice_name:ident [$($extensible:tt)*] { $($variant_name:ident [$($shape:tt)*] : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $variant_type:ty = [$($c:tt)*]),+ }) => {
+        $crate::oer_choice!($choice_name [$($extensible)*]: $($variant_name [$($shape)*]: $tagtype $(TAG $tagclass $tagval;)* TYPE $variant_type = [$($c)*]),+);
+    };
+    (@codec $codec:ident $($rest:tt)*) => {
+        compile_error!(concat!("unknown encoding rules ", stringify!($codec), ", expected XER, JER, PER or OER"));
     };
 }
 
 /// Macro to create enumeration implementation for enum
 ///
+/// Like `der_sequence!`, only DER is implemented unless other encoding rules are listed after the
+/// name, as in `der_enumerated!(Kind WITH(XER, PER), Low, High)`.
+///
+/// A trailing `... <VARIANT>` marks the enumeration as extensible, values of a newer version are
+/// decoded into the unit variant `<VARIANT>`, which is not listed itself and can not be encoded.
+/// With a trailing `... <VARIANT>(i64)` instead, they are decoded into `<VARIANT>(value)` and
+/// encoded back unchanged by DER and OER, the other encoding rules do not carry the value.
+///
+/// Variants may be given their value as `<VARIANT> = <VALUE>`, which is needed for an enum with
+/// such a catch-all variant because it can not be cast to an integer:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate eagre_asn1;
+/// # use eagre_asn1::der::DER;
+/// # #[derive(Debug, PartialEq)]
+/// enum Level {
+///     Low,
+///     High,
+///     Other(i64),
+/// }
+///
+/// der_enumerated!(Level, Low = 1, High = 5, ... Other(i64));
+///
+/// # fn main() {
+/// let level = Level::der_from_bytes(Level::Other(3).der_bytes().unwrap()).unwrap();
+/// assert_eq!(level, Level::Other(3));
+/// # }
+/// ```
+///
 /// # Example
 /// ```
 /// # #[macro_use]
@@ -258,7 +598,28 @@ macro_rules! der_choice {
 /// ```
 #[macro_export]
 macro_rules! der_enumerated {
-    ($enum_name:ident, $($enum_variant:ident),+) => {
+    ($enum_name:ident WITH ($($codec:ident),* $(,)?), $($rest:tt)+) => {
+        $crate::der_enumerated!(@with [$($codec)*] $enum_name, $($rest)+);
+    };
+    ($enum_name:ident, $($rest:tt)+) => {
+        $crate::der_enumerated!(@with [] $enum_name, $($rest)+);
+    };
+    (@with $codecs:tt $enum_name:ident, $($enum_variant:ident $(= $value:expr)?),+) => {
+        $crate::der_enumerated!(@enumerated $codecs $enum_name [false], $($enum_variant $(= $value)?),+);
+    };
+    (@with $codecs:tt $enum_name:ident, $($enum_variant:ident $(= $value:expr)?),+,) => {
+        $crate::der_enumerated!(@enumerated $codecs $enum_name [false], $($enum_variant $(= $value)?),+);
+    };
+    (@with $codecs:tt $enum_name:ident, $($enum_variant:ident $(= $value:expr)?),+, ... $other:ident (i64) $(,)?) => {
+        $crate::der_enumerated!(@enumerated $codecs $enum_name [true; $other], $($enum_variant $(= $value)?),+);
+    };
+    (@with $codecs:tt $enum_name:ident, $($enum_variant:ident $(= $value:expr)?),+, ... $($unknown:ident)? $(,)?) => {
+        $crate::der_enumerated!(@enumerated $codecs $enum_name [true $(, $unknown)?], $($enum_variant $(= $value)?),+);
+    };
+    // Without an explicit value the discriminant of the variant is used
+    (@value $enum_name:ident $enum_variant:ident) => { $enum_name::$enum_variant as i64 };
+    (@value $enum_name:ident $enum_variant:ident = $value:expr) => { $value };
+    (@enumerated $codecs:tt $enum_name:ident [$extensible:tt $(, $unknown:ident)? $(; $other:ident)?], $($enum_variant:ident $(= $value:expr)?),+) => {
         impl $crate::der::DER for $enum_name {
             fn der_universal_tag() -> $crate::der::UniversalTag {
                 $crate::der::UniversalTag::Enumerated
@@ -268,29 +629,357 @@ macro_rules! der_enumerated {
                 $crate::der::ContentType::Primitive
             }
 
-            fn der_encode_content(&self, w: &mut dyn ::std::io::Write) -> ::std::io::Result<()> {
+            fn der_encode_content(&self, w: &mut dyn $crate::io::Write) -> $crate::io::Result<()> {
                 //use $crate::der::DER;
-                match self {
-                    $(&$enum_name::$enum_variant => $enum_name::$enum_variant as i32,)+
-                }.der_encode(w)?;
+                use ::core::convert::TryFrom;
+                let value: i64 = match self {
+                    $(&$enum_name::$enum_variant => $crate::der_enumerated!(@value $enum_name $enum_variant $(= $value)?),)+
+                    $(&$enum_name::$unknown => return Err($crate::der::unknown_value(stringify!($enum_name))),)?
+                    $(&$enum_name::$other(value) => value,)?
+                };
+                i32::try_from(value).map_err(|_| $crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                    $crate::prelude::format!("enumeration value {} of {} is out of range", value, stringify!($enum_name))))?.der_encode(w)?;
                 Ok(())
             }
 
-            fn der_decode_content(r: &mut dyn ::std::io::Read, _: usize) -> ::std::io::Result<Self> {
+            fn der_decode_content(r: &mut dyn $crate::io::Read, _: usize) -> $crate::io::Result<Self> {
                 //use $crate::der::DER;
-                use std::io;
-                let val = i32::der_decode(r)?;
-                let mut result = Err(io::Error::new(io::ErrorKind::InvalidInput, "Unknown enum variant"));
+                use $crate::io;
+                let val = i64::from(i32::der_decode(r)?);
+                $(
+                    if val == $crate::der_enumerated!(@value $enum_name $enum_variant $(= $value)?) {
+                        return Ok($enum_name::$enum_variant);
+                    }
+                )+
+                // Values added by a newer version map to the unknown variant or are kept as they are
+                let unknown: Option<Self> = None $(.or(Some($enum_name::$unknown)))? $(.or(Some($enum_name::$other(val))))?;
+                unknown.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Unknown enum variant"))
+            }
+        }
+
+        impl $crate::constraints::Constrained for $enum_name {}
+
+        $crate::der_enumerated!(@codecs $codecs $enum_name [$extensible $(, $unknown)? $(; $other)?] { $($enum_variant $(= $value)?),+ });
+    };
+    // Implement the encoding rules listed in `WITH(...)` one by one
+    (@codecs [] $($rest:tt)*) => {};
+    (@codecs [$codec:ident $($more:ident)*] $enum_name:ident $extensible:tt $variants:tt) => {
+        $crate::der_enumerated!(@codec $codec $enum_name $extensible $variants);
+        $crate::der_enumerated!(@codecs [$($more)*] $enum_name $extensible $variants);
+    };
+    (@codec XER $enum_name:ident [$($extensible:tt)*] { $($enum_variant:ident $(= $value:expr)?),+ }) => {
+        $crate::xer_enumerated!($enum_name [$($extensible)*], $($enum_variant = $crate::der_enumerated!(@value $enum_name $enum_variant $(= $value)?)),+);
+    };
+    (@codec JER $enum_name:ident [$($extensible:tt)*] { $($enum_variant:ident $(= $value:expr)?),+ }) => {
+        $crate::jer_enumerated!($enum_name [$($extensible)*], $($enum_variant = $crate::der_enumerated!(@value $enum_name $enum_variant $(= $value)?)),+);
+    };
+    (@codec PER $enum_name:ident [$($extensible:tt)*] { $($enum_variant:ident $(= $value:expr)?),+ }) => {
+        $crate::per_enumerated!($enum_name [$($extensible)*], $($enum_variant = $crate::der_enumerated!(@value $enum_name $enum_variant $(= $value)?)),+);
+    };
+    (@codec OER $enum_name:ident [$($extensible:tt)*] { $($enum_variant:ident $(= $value:expr)?),+ }) => {
+        $crate::oer_enumerated!($enum_name [$($extensible)*], $($enum_variant = $crate::der_enumerated!(@value $enum_name $enum_variant $(= $value)?)),+);
+    };
+    (@codec $codec:ident $($rest:tt)*) => {
+        compile_error!(concat!("unknown encoding rules ", stringify!($codec), ", expected XER, JER, PER or OER"));
+    };
+}
+
+/// Macro to create an open type from an enum, choosing the variant by an OBJECT IDENTIFIER
+///
+/// Every variant is listed with the dotted OID it is identified by and its type. The trailing
+/// `... <VARIANT>` is required, values with any other OID are kept in `<VARIANT>(Any)`. Fields of
+/// the enum are annotated with `DEFINED BY <FIELD>;` in `der_sequence!`, see `types::OpenType`.
+///
+/// # Example
+/// ```
+/// # #[macro_use]
+/// # extern crate eagre_asn1;
+/// # use eagre_asn1::der::DER;
+/// # use eagre_asn1::types::{Any, Null, ObjectIdentifier};
+///
+/// # #[derive(Debug)]
+/// enum Parameters {
+///     Rsa(Null),
+///     Ec(ObjectIdentifier),
+///     Unknown(Any),
+/// }
+///
+/// der_open_type! {Parameters:
+///     Rsa: "1.2.840.113549.1.1.1" TYPE Null,
+///     Ec: "1.2.840.10045.2.1" TYPE ObjectIdentifier,
+///     ... Unknown
+/// }
+///
+/// # #[derive(Debug)]
+/// struct AlgorithmIdentifier {
+///     pub algorithm: ObjectIdentifier,
+///     pub parameters: Option<Parameters>,
+/// }
+///
+/// der_sequence! {AlgorithmIdentifier:
+///     algorithm: NOTAG TYPE ObjectIdentifier,
+///     parameters: NOTAG DEFINED BY algorithm; TYPE Option<Parameters>,
+/// }
+///
+/// # fn main() {
+/// let ec = AlgorithmIdentifier {
+///     algorithm: "1.2.840.10045.2.1".parse().unwrap(),
+///     parameters: Some(Parameters::Ec("1.2.840.10045.3.1.7".parse().unwrap())),
+/// };
+/// let decoded = AlgorithmIdentifier::der_from_bytes(ec.der_bytes().unwrap()).unwrap();
+/// match decoded.parameters {
+///     Some(Parameters::Ec(curve)) => assert_eq!(curve.to_string(), "1.2.840.10045.3.1.7"),
+///     _ => panic!("not resolved"),
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! der_open_type {
+    ($open_name:ident : $($variant_name:ident : $id:literal TYPE $variant_type:ty),+, ... $unknown:ident $(,)?) => {
+        impl $crate::types::OpenType for $open_name {
+            fn open_decode(id: &$crate::types::ObjectIdentifier, any: $crate::types::Any) -> $crate::io::Result<Self> {
                 $(
-                    if val == $enum_name::$enum_variant as i32 {
-                        result = Ok($enum_name::$enum_variant);
+                    if *id == $id.parse::<$crate::types::ObjectIdentifier>()? {
+                        return Ok($open_name::$variant_name(any.resolve::<$variant_type>()?));
                     }
                 )+
-                result
+                Ok($open_name::$unknown(any))
+            }
+
+            fn open_raw(any: $crate::types::Any) -> Self {
+                $open_name::$unknown(any)
+            }
+
+            fn open_encode(&self) -> $crate::io::Result<$crate::types::Any> {
+                match self {
+                    $(&$open_name::$variant_name(ref val) => {
+                        $crate::der::DER::der_from_intermediate($crate::der::DER::der_intermediate(val)?)
+                    },)+
+                    &$open_name::$unknown(ref any) => $crate::types::OpenType::open_encode(any),
+                }
+            }
+        }
+
+        $crate::der_open_codecs!($open_name);
+    };
+}
+
+/// Implementations of all encoding rules for an open type, which are those of `Any`
+///
+/// Decoding keeps the raw value, it is resolved by the sequence that holds the identifier.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! der_open_codecs {
+    ($open_type:ty) => {
+        impl $crate::der::DER for $open_type {
+            // Like ANY, an open type has no tag of its own
+            const DER_EXPLICIT_ONLY: bool = true;
+
+            fn der_universal_tag() -> $crate::der::UniversalTag {
+                $crate::der::UniversalTag::EOC
+            }
+
+            fn der_content() -> $crate::der::ContentType {
+                $crate::der::ContentType::Constructed
+            }
+
+            fn der_encode_content(&self, w: &mut dyn $crate::io::Write) -> $crate::io::Result<()> {
+                w.write_all(&$crate::der::DER::der_intermediate(self)?.content)
+            }
+
+            fn der_decode_content(
+                _: &mut dyn $crate::io::Read,
+                _: usize,
+            ) -> $crate::io::Result<Self> {
+                Err($crate::io::Error::new(
+                    $crate::io::ErrorKind::InvalidInput,
+                    "an open type can only be decoded with its tag",
+                ))
+            }
+
+            fn der_intermediate(&self) -> $crate::io::Result<$crate::der::Intermediate> {
+                $crate::der::DER::der_intermediate(&$crate::types::OpenType::open_encode(self)?)
+            }
+
+            fn der_from_intermediate(i: $crate::der::Intermediate) -> $crate::io::Result<Self> {
+                Ok($crate::types::OpenType::open_raw(
+                    $crate::der::DER::der_from_intermediate(i)?,
+                ))
+            }
+
+            fn der_matches(_: $crate::der::Class, _: u32) -> bool {
+                true
+            }
+
+            fn der_tags() -> $crate::prelude::Vec<($crate::der::Class, u32)> {
+                $crate::prelude::vec![]
             }
         }
+
+        impl $crate::xer::XEREncodeable for $open_type {
+            fn xer_name(&self) -> $crate::prelude::String {
+                $crate::prelude::String::from("ANY")
+            }
+
+            fn xer_encode_content(&self, e: &mut $crate::xer::Element) -> $crate::io::Result<()> {
+                $crate::xer::XEREncodeable::xer_encode_content(
+                    &$crate::types::OpenType::open_encode(self)?,
+                    e,
+                )
+            }
+        }
+
+        impl $crate::xer::XERDecodeable for $open_type {
+            fn xer_decode_content(e: &$crate::xer::Element) -> $crate::io::Result<Self> {
+                Ok($crate::types::OpenType::open_raw(
+                    <$crate::types::Any as $crate::xer::XERDecodeable>::xer_decode_content(e)?,
+                ))
+            }
+        }
+
+        impl $crate::jer::JER for $open_type {
+            fn jer_value(&self) -> $crate::io::Result<$crate::jer::Value> {
+                $crate::jer::JER::jer_value(&$crate::types::OpenType::open_encode(self)?)
+            }
+
+            fn jer_from_value(v: &$crate::jer::Value) -> $crate::io::Result<Self> {
+                Ok($crate::types::OpenType::open_raw(
+                    <$crate::types::Any as $crate::jer::JER>::jer_from_value(v)?,
+                ))
+            }
+        }
+
+        impl $crate::per::PER for $open_type {
+            fn per_encode(
+                &self,
+                w: &mut $crate::per::BitWriter,
+                c: &$crate::constraints::Constraints,
+            ) -> $crate::io::Result<()> {
+                $crate::per::PER::per_encode(&$crate::types::OpenType::open_encode(self)?, w, c)
+            }
+
+            fn per_decode(
+                r: &mut $crate::per::BitReader,
+                c: &$crate::constraints::Constraints,
+            ) -> $crate::io::Result<Self> {
+                Ok($crate::types::OpenType::open_raw(
+                    <$crate::types::Any as $crate::per::PER>::per_decode(r, c)?,
+                ))
+            }
+        }
+
+        impl $crate::oer::OER for $open_type {
+            fn oer_encode(
+                &self,
+                w: &mut $crate::prelude::Vec<u8>,
+                c: &$crate::constraints::Constraints,
+            ) -> $crate::io::Result<()> {
+                $crate::oer::OER::oer_encode(&$crate::types::OpenType::open_encode(self)?, w, c)
+            }
+
+            fn oer_decode(
+                r: &mut $crate::oer::Reader,
+                c: &$crate::constraints::Constraints,
+            ) -> $crate::io::Result<Self> {
+                Ok($crate::types::OpenType::open_raw(
+                    <$crate::types::Any as $crate::oer::OER>::oer_decode(r, c)?,
+                ))
+            }
+        }
+
+        impl $crate::constraints::Constrained for $open_type {}
+    };
+}
+
+/// Rewrite the fields of `der_sequence!` or `der_choice!` in a tagging environment
+///
+/// Works through the fields one by one, giving each one an explicit tag type. `[$index]` is the
+/// position of the current field, used as its tag number in an `AUTOMATIC TAGS` environment.
+/// `$codecs` is the list of encoding rules from `WITH(...)`, handed back unchanged.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! der_tagging {
+    (AUTOMATIC $mac:ident $name:ident $codecs:tt : $($fields:tt)*) => {
+        $crate::der_tagging!(@auto $mac $name $codecs [] [] [] [0] $($fields)*);
+    };
+    (IMPLICIT $mac:ident $name:ident $codecs:tt : $($fields:tt)*) => {
+        $crate::der_tagging!(@field $mac $name $codecs IMPLICIT [] [0] $($fields)*);
+    };
+    (EXPLICIT $mac:ident $name:ident $codecs:tt : $($fields:tt)*) => {
+        $crate::der_tagging!(@field $mac $name $codecs EXPLICIT [] [0] $($fields)*);
+    };
+    // Only `TAG <CLASS> <TAG>;` is given, the tag type is the default of the environment
+    (@field $mac:ident $name:ident $codecs:tt EXPLICIT [$($done:tt)*] [$($index:tt)+]
+     $field:ident : TAG $tagclass:ident $tagval:expr ; $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? $(TYPE $ty:ty)? $({ $($inner:tt)* })? $(, $($rest:tt)*)?) => {
+        $crate::der_tagging!(@field $mac $name $codecs EXPLICIT
+                             [$($done)* $field: EXPLICIT TAG $tagclass $tagval; $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($index)+ + 1] $($($rest)*)?);
+    };
+    (@field $mac:ident $name:ident $codecs:tt $env:ident [$($done:tt)*] [$($index:tt)+]
+     $field:ident : TAG $tagclass:ident $tagval:expr ; $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? $(TYPE $ty:ty)? $({ $($inner:tt)* })? $(, $($rest:tt)*)?) => {
+        $crate::der_tagging!(@field $mac $name $codecs $env
+                             [$($done)* $field: AUTOMATIC TAG $tagclass $tagval; $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($index)+ + 1] $($($rest)*)?);
+    };
+    (@field $mac:ident $name:ident $codecs:tt $env:ident [$($done:tt)*] [$($index:tt)+]
+     $field:ident : $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? $(TYPE $ty:ty)? $({ $($inner:tt)* })? $(, $($rest:tt)*)?) => {
+        $crate::der_tagging!(@field $mac $name $codecs $env
+                             [$($done)* $field: NOTAG $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($index)+ + 1] $($($rest)*)?);
+    };
+    // The tag type is given
+    (@field $mac:ident $name:ident $codecs:tt $env:ident [$($done:tt)*] [$($index:tt)+]
+     $field:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? $(TYPE $ty:ty)? $({ $($inner:tt)* })? $(, $($rest:tt)*)?) => {
+        $crate::der_tagging!(@field $mac $name $codecs $env
+                             [$($done)* $field: $tagtype $(TAG $tagclass $tagval;)* $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($index)+ + 1] $($($rest)*)?);
+    };
+    // A unit or struct variant of a choice without any tag
+    (@field $mac:ident $name:ident $codecs:tt $env:ident [$($done:tt)*] [$($index:tt)+] $field:ident $({ $($inner:tt)* })? $(, $($rest:tt)*)?) => {
+        $crate::der_tagging!(@field $mac $name $codecs $env [$($done)*] [$($index)+] $field: $({ $($inner)* })? $(, $($rest)*)?);
+    };
+    // All fields are done, only the extension marker may be left
+    (@field $mac:ident $name:ident $codecs:tt $env:ident [$($done:tt)*] [$($index:tt)+] $($rest:tt)*) => {
+        $crate::$mac!(@with $codecs $name: $($done)* $($rest)*);
+    };
+    // `AUTOMATIC TAGS` builds the fields with numbered context tags in `[$auto]` and, for the case
+    // that any field has a tag, the fields like `IMPLICIT TAGS` would in `[$plain]`. X.680 31.2.7
+    // only applies automatic tagging if no field has a tag, `[$tagged]` records those fields.
+    (@auto $mac:ident $name:ident $codecs:tt [$($auto:tt)*] [$($plain:tt)*] [$($tagged:tt)*] [$($index:tt)+]
+     $field:ident : TAG $tagclass:ident $tagval:expr ; $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? $(TYPE $ty:ty)? $({ $($inner:tt)* })? $(, $($rest:tt)*)?) => {
+        $crate::der_tagging!(@auto $mac $name $codecs
+                             [$($auto)* $field: AUTOMATIC TAG $tagclass $tagval; $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($plain)* $field: AUTOMATIC TAG $tagclass $tagval; $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($tagged)* $field] [$($index)+ + 1] $($($rest)*)?);
+    };
+    (@auto $mac:ident $name:ident $codecs:tt [$($auto:tt)*] [$($plain:tt)*] [$($tagged:tt)*] [$($index:tt)+]
+     $field:ident : $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? $(TYPE $ty:ty)? $({ $($inner:tt)* })? $(, $($rest:tt)*)?) => {
+        $crate::der_tagging!(@auto $mac $name $codecs
+                             [$($auto)* $field: AUTOMATIC TAG CONTEXT ($($index)+); $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($plain)* $field: NOTAG $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($tagged)*] [$($index)+ + 1] $($($rest)*)?);
+    };
+    (@auto $mac:ident $name:ident $codecs:tt [$($auto:tt)*] [$($plain:tt)*] [$($tagged:tt)*] [$($index:tt)+]
+     $field:ident : NOTAG $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? $(TYPE $ty:ty)? $({ $($inner:tt)* })? $(, $($rest:tt)*)?) => {
+        $crate::der_tagging!(@auto $mac $name $codecs
+                             [$($auto)* $field: NOTAG $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($plain)* $field: NOTAG $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($tagged)*] [$($index)+ + 1] $($($rest)*)?);
+    };
+    (@auto $mac:ident $name:ident $codecs:tt [$($auto:tt)*] [$($plain:tt)*] [$($tagged:tt)*] [$($index:tt)+]
+     $field:ident : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* $(VALUE $value:tt ;)? $(SIZE $size:tt ;)? $(FROM $from:tt ;)? $(DEFINED BY $id:ident ;)? $(TYPE $ty:ty)? $({ $($inner:tt)* })? $(, $($rest:tt)*)?) => {
+        $crate::der_tagging!(@auto $mac $name $codecs
+                             [$($auto)* $field: $tagtype $(TAG $tagclass $tagval;)* $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($plain)* $field: $tagtype $(TAG $tagclass $tagval;)* $(VALUE $value;)? $(SIZE $size;)? $(FROM $from;)? $(DEFINED BY $id;)? $(TYPE $ty)? $({ $($inner)* })?,]
+                             [$($tagged)* $field] [$($index)+ + 1] $($($rest)*)?);
+    };
+    (@auto $mac:ident $name:ident $codecs:tt [$($auto:tt)*] [$($plain:tt)*] [$($tagged:tt)*] [$($index:tt)+] $field:ident $({ $($inner:tt)* })? $(, $($rest:tt)*)?) => {
+        $crate::der_tagging!(@auto $mac $name $codecs [$($auto)*] [$($plain)*] [$($tagged)*] [$($index)+] $field: $({ $($inner)* })? $(, $($rest)*)?);
+    };
+    (@auto $mac:ident $name:ident $codecs:tt [$($auto:tt)*] [$($plain:tt)*] [] [$($index:tt)+] $($rest:tt)*) => {
+        $crate::$mac!(@with $codecs $name: $($auto)* $($rest)*);
     };
-    ($enum_name:ident, $($enum_val:ident),+,) => {
-        der_enumerated!($enum_name, $($enum_val),+);
+    (@auto $mac:ident $name:ident $codecs:tt [$($auto:tt)*] [$($plain:tt)*] [$($tagged:tt)+] [$($index:tt)+] $($rest:tt)*) => {
+        $crate::$mac!(@with $codecs $name: $($plain)* $($rest)*);
     };
 }
@@ -4,20 +4,110 @@ pub mod intermediate;
 pub mod length;
 /// Tag encoding/decoding
 pub mod tag;
+/// Recursive element tree
+pub mod tree;
 
+/// tokio-util codec for streams of DER values
+#[cfg(feature = "codec")]
+pub mod codec;
 /// DER Trait
 pub mod der;
+/// Human readable dumps of encoded data
+pub mod dump;
+/// Heapless encoding into fixed size buffers
+pub mod fixed;
+/// Decoding from partially received buffers
+pub mod partial;
 #[doc(hidden)]
 #[macro_use]
 pub mod macros;
+/// serde Serializer and Deserializer
+#[cfg(feature = "serde")]
+pub mod serde;
 
 #[cfg(test)]
 mod test;
 
-pub use self::der::DER;
+use crate::io;
+use crate::prelude::*;
+use core::convert::TryFrom;
+
+pub use self::der::{Component, DynEncode, DER};
+pub use self::fixed::FixedEncoder;
 pub use self::intermediate::Intermediate;
 pub use self::length::*;
+pub use self::partial::{Partial, StreamDecoder};
 pub use self::tag::*;
+pub use self::tree::{Node, MAX_NESTING_DEPTH};
+
+/// Resolve the tag type of a macro field, `AUTOMATIC` is `IMPLICIT` unless `EXPLICIT` is required
+#[doc(hidden)]
+pub fn tagging(tagtype: &'static str, explicit_only: bool) -> &'static str {
+    match tagtype {
+        "AUTOMATIC" if explicit_only => "EXPLICIT",
+        "AUTOMATIC" => "IMPLICIT",
+        _ => tagtype,
+    }
+}
+
+/// Return whether the tag type of a macro field is `IMPLICIT`, usable in constants
+#[doc(hidden)]
+pub const fn is_implicit(tagtype: &str) -> bool {
+    let (tagtype, implicit) = (tagtype.as_bytes(), b"IMPLICIT");
+    if tagtype.len() != implicit.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < implicit.len() {
+        if tagtype[i] != implicit[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Error for encoding the unknown variant of an extensible enumeration
+#[doc(hidden)]
+pub fn unknown_value(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("the unknown value of {} can not be encoded", name),
+    )
+}
+
+/// Error for encoding an unknown alternative of an extensible choice in anything but DER
+#[doc(hidden)]
+pub fn unknown_alternative(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+            "unknown alternatives of {} can only be encoded in DER",
+            name
+        ),
+    )
+}
+
+/// Convert decoded items into a fixed size array, failing unless there are exactly `N` of them
+#[doc(hidden)]
+pub fn into_array<T, const N: usize>(items: Vec<T>) -> io::Result<[T; N]> {
+    let count = items.len();
+    <[T; N]>::try_from(items).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("expected exactly {} elements, found {}", N, count),
+        )
+    })
+}
+
+/// Error for decoding a tuple from the wrong number of components
+#[doc(hidden)]
+pub fn tuple_mismatch() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "number of components does not match the tuple",
+    )
+}
 
 /// DER Universal Tag Values
 #[derive(Debug, Copy, Clone)]
@@ -86,8 +176,48 @@ pub enum UniversalTag {
     BMPString = 30,
 }
 
+impl UniversalTag {
+    /// Look up the universal tag with the given tag number
+    pub fn from_tag(tag: u32) -> Option<UniversalTag> {
+        Some(match tag {
+            0 => UniversalTag::EOC,
+            1 => UniversalTag::Boolean,
+            2 => UniversalTag::Integer,
+            3 => UniversalTag::BitString,
+            4 => UniversalTag::OctetString,
+            5 => UniversalTag::Null,
+            6 => UniversalTag::ObjectIdentifier,
+            7 => UniversalTag::ObjectDescriptor,
+            8 => UniversalTag::External,
+            9 => UniversalTag::Real,
+            10 => UniversalTag::Enumerated,
+            11 => UniversalTag::EmbeddedPDV,
+            12 => UniversalTag::UTF8String,
+            13 => UniversalTag::RelativeOID,
+            14 => UniversalTag::Reserved01,
+            15 => UniversalTag::Reserved02,
+            16 => UniversalTag::Sequence,
+            17 => UniversalTag::Set,
+            18 => UniversalTag::NumericString,
+            19 => UniversalTag::PrintableString,
+            20 => UniversalTag::T61String,
+            21 => UniversalTag::VideotexString,
+            22 => UniversalTag::IA5String,
+            23 => UniversalTag::UTCTime,
+            24 => UniversalTag::GeneralizedTime,
+            25 => UniversalTag::GraphicString,
+            26 => UniversalTag::VisibleString,
+            27 => UniversalTag::GeneralString,
+            28 => UniversalTag::UniversalString,
+            29 => UniversalTag::CharacterString,
+            30 => UniversalTag::BMPString,
+            _ => return None,
+        })
+    }
+}
+
 /// DER Class Values
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub enum Class {
     /// Universal
     Universal = 0,
@@ -100,7 +230,7 @@ pub enum Class {
 }
 
 /// DER ContentType Values
-#[derive(Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub enum ContentType {
     /// Primitive
     Primitive = 0,
@@ -0,0 +1,117 @@
+use crate::io;
+use crate::prelude::*;
+
+use super::*;
+
+/// Result of decoding from a buffer that may hold only the beginning of a value
+///
+/// Returned by `DER::der_decode_partial()`, `Intermediate::decode_partial()` and
+/// `StreamDecoder::decode()`. Nothing is consumed on `NeedMore`, so the same call can be repeated
+/// once more bytes arrived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Partial<T> {
+    /// The decoded value and the number of bytes it took
+    Done(T, usize),
+    /// The value is incomplete, at least this many more bytes are needed
+    NeedMore(usize),
+}
+
+impl<T> Partial<T> {
+    /// Return the decoded value, `None` if more bytes are needed
+    pub fn done(self) -> Option<T> {
+        match self {
+            Partial::Done(value, _) => Some(value),
+            Partial::NeedMore(_) => None,
+        }
+    }
+
+    /// Apply `f` to the decoded value
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Partial<U> {
+        match self {
+            Partial::Done(value, length) => Partial::Done(f(value), length),
+            Partial::NeedMore(needed) => Partial::NeedMore(needed),
+        }
+    }
+}
+
+/// Find the end of the element at the start of `buf` from its tag and length bytes
+///
+/// Result is `Done(header_length, element_length)`, the element itself may still be incomplete.
+pub(crate) fn der_element_length(buf: &[u8]) -> io::Result<Partial<usize>> {
+    let mut r = buf;
+    let tag_length = match der_decode_tag_bytes(&mut r) {
+        Ok((tag_length, _, _, _)) => tag_length,
+        Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(Partial::NeedMore(1)),
+        Err(e) => return Err(e),
+    };
+    let length_length = match r.first() {
+        None => return Ok(Partial::NeedMore(1)),
+        Some(&first) if first & 0x80 != 0 => 1 + (first & 0x7F) as usize,
+        Some(_) => 1,
+    };
+    let available = r.len();
+    let length = match der_decode_length_bytes(&mut r) {
+        Ok((_, length)) => length,
+        Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            return Ok(Partial::NeedMore(length_length - available))
+        }
+        Err(e) => return Err(e),
+    };
+    let header_length = tag_length + length_length;
+    let element_length = header_length
+        .checked_add(length)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "too big size"))?;
+    Ok(Partial::Done(header_length, element_length))
+}
+
+/// Decoder collecting bytes until complete values can be decoded
+///
+/// Bytes are fed as they arrive, e.g. from a non-blocking socket, and decoded values are removed
+/// from the front of the buffer.
+///
+/// # Example
+/// ```
+/// # use eagre_asn1::der::*;
+/// let mut decoder = StreamDecoder::new();
+/// decoder.feed(&[0x02, 0x02, 0x01]);
+/// assert_eq!(decoder.decode::<i32>().unwrap(), Partial::NeedMore(1));
+/// decoder.feed(&[0x2C, 0x01, 0x01]);
+/// assert_eq!(decoder.decode::<i32>().unwrap(), Partial::Done(300, 4));
+/// assert_eq!(decoder.decode::<bool>().unwrap(), Partial::NeedMore(1));
+/// assert_eq!(decoder.buffered(), &[0x01, 0x01]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StreamDecoder {
+    buf: Vec<u8>,
+}
+
+impl StreamDecoder {
+    /// Create an empty StreamDecoder
+    pub fn new() -> StreamDecoder {
+        StreamDecoder { buf: vec![] }
+    }
+
+    /// Append received bytes
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Decode the next value, removing its bytes once it is complete
+    pub fn decode<T: DER>(&mut self) -> io::Result<Partial<T>> {
+        let result = T::der_decode_partial(&self.buf)?;
+        if let Partial::Done(_, length) = result {
+            self.buf.drain(..length);
+        }
+        Ok(result)
+    }
+
+    /// Return the bytes not decoded yet
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Remove all bytes not decoded yet
+    pub fn clear(&mut self) {
+        self.buf.clear();
+    }
+}
@@ -0,0 +1,1177 @@
+//! DER Serializer and Deserializer for serde, enabled by the `serde` feature
+//!
+//! Types deriving `Serialize` and `Deserialize` are mapped to ASN.1 like this:
+//!
+//! * structs, tuples and sequences become `SEQUENCE`s, maps a `SEQUENCE OF SEQUENCE { key, value }`
+//! * struct fields are tagged like with `AUTOMATIC TAGS`, the n-th field gets the tag `[n]`
+//! * `Option` fields are `OPTIONAL`, `None` is left out
+//! * enums become `CHOICE`s, the n-th variant gets the tag `[n]`
+//! * bytes (see the `serde_bytes` crate) become `OCTET STRING`s, strings and chars `UTF8String`s
+//! * integers become `INTEGER`s, `bool` a `BOOLEAN` and units `NULL`
+//!
+//! Like with `AUTOMATIC TAGS`, tags are `IMPLICIT`, except for enums, which are tagged
+//! `EXPLICIT`. Floats are not supported.
+//!
+//! Tags can be overridden by renaming fields and variants. A name can end in a tag like
+//! `[APPLICATION 3]`, `[PRIVATE 7] EXPLICIT` or `[2] IMPLICIT`, where the class defaults to
+//! `CONTEXT`, or in `NOTAG`. Renaming a struct to end in a tag replaces the `SEQUENCE` tag.
+//!
+//! # Example
+//! ```
+//! # use serde::{Deserialize, Serialize};
+//! use eagre_asn1::der::serde::{from_bytes, to_bytes};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct User {
+//!     #[serde(rename = "name NOTAG")]
+//!     name: String,
+//!     age: Option<i32>,
+//!     #[serde(rename = "admin [APPLICATION 1]")]
+//!     admin: bool,
+//! }
+//!
+//! let user = User { name: "Rahix".to_string(), age: None, admin: true };
+//! let encoded = to_bytes(&user).unwrap();
+//! assert_eq!(
+//!     encoded,
+//!     vec![0x30, 0x0A, 0x0C, 0x05, 0x52, 0x61, 0x68, 0x69, 0x78, 0x41, 0x01, 0xFF]
+//! );
+//! assert_eq!(user, from_bytes(&encoded).unwrap());
+//! ```
+
+use crate::der::*;
+use crate::io;
+use crate::types::Null;
+use ::serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use ::serde::ser::{self, Serialize};
+use core::convert::TryFrom;
+use core::error;
+use core::fmt;
+
+/// Encode `value` into DER bytes
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    encode(value)?.0.encode(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Decode a value from DER bytes
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> io::Result<T> {
+    let mut stream = io::Cursor::new(bytes);
+    let i = Intermediate::decode(&mut stream)?;
+    if stream.position() as usize != bytes.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "trailing bytes after the encoded value",
+        ));
+    }
+    Ok(T::deserialize(Deserializer::new(i))?)
+}
+
+/// Error of the Serializer and Deserializer, converts into `io::Error`
+#[derive(Debug)]
+pub struct Error(io::Error);
+
+impl Error {
+    fn new<T: fmt::Display>(msg: T) -> Error {
+        Error(io::Error::new(io::ErrorKind::InvalidInput, msg.to_string()))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error::new(msg)
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error::new(msg)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error(e)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> io::Error {
+        e.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Mode {
+    Automatic,
+    Implicit,
+    Explicit,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Tagging {
+    None,
+    Tag(Class, u32, Mode),
+}
+
+impl Tagging {
+    /// Parse the tag at the end of `name`, `default` is used if there is none
+    fn parse(name: &str, default: Tagging) -> Result<Tagging, Error> {
+        let invalid = || Error::new(format!("invalid tag in \"{}\"", name));
+        if name == "NOTAG" || name.ends_with(" NOTAG") {
+            return Ok(Tagging::None);
+        }
+        let start = match name.find('[') {
+            Some(start) => start,
+            None => return Ok(default),
+        };
+        let end = start + name[start..].find(']').ok_or_else(invalid)?;
+        let mut tokens = name[start + 1..end].split_whitespace();
+        let (class, number) = match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(number), None, None) => (Class::ContextSpecific, number),
+            (Some(class), Some(number), None) => match class {
+                "APPLICATION" => (Class::Application, number),
+                "CONTEXT" => (Class::ContextSpecific, number),
+                "PRIVATE" => (Class::Private, number),
+                _ => return Err(invalid()),
+            },
+            _ => return Err(invalid()),
+        };
+        let tag = number.parse().map_err(|_| invalid())?;
+        let mode = match name[end + 1..].trim() {
+            "" => Mode::Automatic,
+            "IMPLICIT" => Mode::Implicit,
+            "EXPLICIT" => Mode::Explicit,
+            _ => return Err(invalid()),
+        };
+        Ok(Tagging::Tag(class, tag, mode))
+    }
+
+    /// Tagging of the field or variant `name` at position `index`
+    fn automatic(name: &str, index: usize) -> Result<Tagging, Error> {
+        let tag = u32::try_from(index).map_err(|_| Error::new("too many fields"))?;
+        Tagging::parse(
+            name,
+            Tagging::Tag(Class::ContextSpecific, tag, Mode::Automatic),
+        )
+    }
+
+    fn matches(&self, i: &Intermediate) -> bool {
+        match *self {
+            Tagging::None => false,
+            Tagging::Tag(class, tag, _) => i.class == class && i.tag == tag,
+        }
+    }
+
+    /// Tag an encoded value, `choice` values can only be tagged `EXPLICIT`
+    fn apply(&self, i: Intermediate, choice: bool) -> Result<Intermediate, Error> {
+        match *self {
+            Tagging::None => Ok(i),
+            Tagging::Tag(_, _, Mode::Implicit) if choice => {
+                Err(Error::new("a CHOICE can not be tagged IMPLICIT"))
+            }
+            Tagging::Tag(class, tag, Mode::Explicit) => Ok(i.into_explicit(tag, class)?),
+            Tagging::Tag(class, tag, Mode::Automatic) if choice => Ok(i.into_explicit(tag, class)?),
+            Tagging::Tag(class, tag, _) => Ok(i.into_implicit(tag, class)),
+        }
+    }
+
+    /// Create the Deserializer for an element tagged like this
+    fn deserializer(&self, i: Intermediate) -> Result<Deserializer, Error> {
+        match *self {
+            Tagging::None => Ok(Deserializer::new(i)),
+            Tagging::Tag(_, _, Mode::Explicit) => Ok(Deserializer::new(unwrap_explicit(i)?)),
+            Tagging::Tag(_, _, mode) => Ok(Deserializer {
+                i,
+                tagged: Some(mode),
+            }),
+        }
+    }
+}
+
+fn unwrap_explicit(i: Intermediate) -> Result<Intermediate, Error> {
+    let mut children = i.children()?;
+    if children.len() != 1 {
+        return Err(Error::new("explicit tag does not wrap exactly one element"));
+    }
+    Ok(children.remove(0))
+}
+
+fn absent() -> Error {
+    Error::new("absent values can only be encoded as struct fields")
+}
+
+/// Encode `value`, also returning whether it is a `CHOICE`
+fn encode<T: Serialize + ?Sized>(value: &T) -> Result<(Intermediate, bool), Error> {
+    let mut serializer = Serializer::new();
+    let i = value.serialize(&mut serializer)?.ok_or_else(absent)?;
+    Ok((i, serializer.choice))
+}
+
+fn integer(value: i128) -> Intermediate {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+    while start < bytes.len() - 1
+        && ((bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0)
+            || (bytes[start] == 0xFF && bytes[start + 1] & 0x80 != 0))
+    {
+        start += 1;
+    }
+    Intermediate::new(
+        Class::Universal,
+        ContentType::Primitive,
+        UniversalTag::Integer as u32,
+    )
+    .with_content(bytes[start..].to_vec())
+}
+
+/// Serializer into an `Intermediate`, `None` if the value is absent
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Serializer {
+    choice: bool,
+}
+
+impl Serializer {
+    /// Create a Serializer
+    pub fn new() -> Serializer {
+        Serializer { choice: false }
+    }
+}
+
+type Encoded = Result<Option<Intermediate>, Error>;
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = Option<Intermediate>;
+    type Error = Error;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = MapCompound;
+    type SerializeStruct = StructCompound<'a>;
+    type SerializeStructVariant = StructCompound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Encoded {
+        Ok(Some(v.der_intermediate()?))
+    }
+
+    fn serialize_i8(self, v: i8) -> Encoded {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Encoded {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Encoded {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Encoded {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Encoded {
+        Ok(Some(integer(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Encoded {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Encoded {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Encoded {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Encoded {
+        self.serialize_i128(i128::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Encoded {
+        let v = i128::try_from(v).map_err(|_| Error::new("integer too big"))?;
+        self.serialize_i128(v)
+    }
+
+    fn serialize_f32(self, _: f32) -> Encoded {
+        Err(Error::new("REAL is not supported"))
+    }
+
+    fn serialize_f64(self, _: f64) -> Encoded {
+        Err(Error::new("REAL is not supported"))
+    }
+
+    fn serialize_char(self, v: char) -> Encoded {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Encoded {
+        Ok(Some(v.der_intermediate()?))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Encoded {
+        Ok(Some(v.der_intermediate()?))
+    }
+
+    fn serialize_none(self) -> Encoded {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Encoded {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Encoded {
+        Ok(Some(Null.der_intermediate()?))
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Encoded {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, index: u32, variant: &'static str) -> Encoded {
+        self.choice = true;
+        let tagging = Tagging::automatic(variant, index as usize)?;
+        Ok(Some(tagging.apply(Null.der_intermediate()?, false)?))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Encoded {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Encoded {
+        self.choice = true;
+        let tagging = Tagging::automatic(variant, index as usize)?;
+        let (i, choice) = encode(value)?;
+        Ok(Some(tagging.apply(i, choice)?))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Compound<'a>, Error> {
+        Ok(Compound::new(self, Tagging::None, None))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Compound<'a>, Error> {
+        Ok(Compound::new(self, Tagging::None, None))
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, _: usize) -> Result<Compound<'a>, Error> {
+        let own = Tagging::parse(name, Tagging::None)?;
+        Ok(Compound::new(self, own, None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        index: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a>, Error> {
+        let tagging = Tagging::automatic(variant, index as usize)?;
+        Ok(Compound::new(self, Tagging::None, Some(tagging)))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<MapCompound, Error> {
+        Ok(MapCompound {
+            content: vec![],
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, _: usize) -> Result<StructCompound<'a>, Error> {
+        let own = Tagging::parse(name, Tagging::None)?;
+        Ok(StructCompound {
+            inner: Compound::new(self, own, None),
+            index: 0,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        index: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<StructCompound<'a>, Error> {
+        let tagging = Tagging::automatic(variant, index as usize)?;
+        Ok(StructCompound {
+            inner: Compound::new(self, Tagging::None, Some(tagging)),
+            index: 0,
+        })
+    }
+}
+
+/// Serializer of the elements of a `SEQUENCE`
+#[derive(Debug)]
+pub struct Compound<'a> {
+    serializer: &'a mut Serializer,
+    content: Vec<u8>,
+    own: Tagging,
+    variant: Option<Tagging>,
+}
+
+impl<'a> Compound<'a> {
+    fn new(serializer: &'a mut Serializer, own: Tagging, variant: Option<Tagging>) -> Compound<'a> {
+        Compound {
+            serializer,
+            content: vec![],
+            own,
+            variant,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        encode(value)?.0.encode(&mut self.content)?;
+        Ok(())
+    }
+
+    fn finish(self) -> Encoded {
+        let i = Intermediate::new(
+            Class::Universal,
+            ContentType::Constructed,
+            UniversalTag::Sequence as u32,
+        )
+        .with_content(self.content);
+        let i = self.own.apply(i, false)?;
+        match self.variant {
+            Some(tagging) => {
+                self.serializer.choice = true;
+                Ok(Some(tagging.apply(i, false)?))
+            }
+            None => Ok(Some(i)),
+        }
+    }
+}
+
+impl<'a> ser::SerializeSeq for Compound<'a> {
+    type Ok = Option<Intermediate>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Encoded {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTuple for Compound<'a> {
+    type Ok = Option<Intermediate>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Encoded {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for Compound<'a> {
+    type Ok = Option<Intermediate>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Encoded {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for Compound<'a> {
+    type Ok = Option<Intermediate>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Encoded {
+        self.finish()
+    }
+}
+
+/// Serializer of the fields of a struct, tagged by their position
+#[derive(Debug)]
+pub struct StructCompound<'a> {
+    inner: Compound<'a>,
+    index: usize,
+}
+
+impl<'a> StructCompound<'a> {
+    fn field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        let tagging = Tagging::automatic(key, self.index)?;
+        self.index += 1;
+        let mut serializer = Serializer::new();
+        if let Some(i) = value.serialize(&mut serializer)? {
+            tagging
+                .apply(i, serializer.choice)?
+                .encode(&mut self.inner.content)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for StructCompound<'a> {
+    type Ok = Option<Intermediate>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn skip_field(&mut self, _: &'static str) -> Result<(), Error> {
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Encoded {
+        self.inner.finish()
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for StructCompound<'a> {
+    type Ok = Option<Intermediate>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn skip_field(&mut self, _: &'static str) -> Result<(), Error> {
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Encoded {
+        self.inner.finish()
+    }
+}
+
+/// Serializer of the entries of a map, each a `SEQUENCE { key, value }`
+#[derive(Debug)]
+pub struct MapCompound {
+    content: Vec<u8>,
+    key: Option<Intermediate>,
+}
+
+impl ser::SerializeMap for MapCompound {
+    type Ok = Option<Intermediate>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(encode(key)?.0);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::new("map value without key"))?;
+        let mut entry = vec![];
+        key.encode(&mut entry)?;
+        encode(value)?.0.encode(&mut entry)?;
+        Intermediate::new(
+            Class::Universal,
+            ContentType::Constructed,
+            UniversalTag::Sequence as u32,
+        )
+        .with_content(entry)
+        .encode(&mut self.content)?;
+        Ok(())
+    }
+
+    fn end(self) -> Encoded {
+        Ok(Some(
+            Intermediate::new(
+                Class::Universal,
+                ContentType::Constructed,
+                UniversalTag::Sequence as u32,
+            )
+            .with_content(self.content),
+        ))
+    }
+}
+
+/// Deserializer from an `Intermediate`
+#[derive(Debug)]
+pub struct Deserializer {
+    i: Intermediate,
+    // Mode of the context tag replacing the universal tag, if any
+    tagged: Option<Mode>,
+}
+
+impl Deserializer {
+    /// Create a Deserializer for an untagged element
+    pub fn new(i: Intermediate) -> Deserializer {
+        Deserializer { i, tagged: None }
+    }
+
+    /// Check the universal tag of untagged elements
+    fn expect(&self, tags: &[UniversalTag]) -> Result<(), Error> {
+        if self.tagged.is_some()
+            || (self.i.class == Class::Universal && tags.iter().any(|t| *t as u32 == self.i.tag))
+        {
+            return Ok(());
+        }
+        Err(Error::new(format!(
+            "expected {:?}, found tag {} of class {:?}",
+            tags[0], self.i.tag, self.i.class
+        )))
+    }
+
+    fn integer<T: TryFrom<i128>>(&self) -> Result<T, Error> {
+        self.expect(&[UniversalTag::Integer])?;
+        let content = &self.i.content;
+        if content.is_empty() || content.len() > 16 {
+            return Err(Error::new("invalid integer length"));
+        }
+        if content.len() > 1
+            && ((content[0] == 0x00 && content[1] & 0x80 == 0)
+                || (content[0] == 0xFF && content[1] & 0x80 != 0))
+        {
+            return Err(Error::new("integer is not minimally encoded"));
+        }
+        let mut bytes = if content[0] & 0x80 != 0 {
+            [0xFF; 16]
+        } else {
+            [0x00; 16]
+        };
+        bytes[16 - content.len()..].copy_from_slice(content);
+        T::try_from(i128::from_be_bytes(bytes)).map_err(|_| Error::new("integer out of range"))
+    }
+
+    fn string(self) -> Result<String, Error> {
+        self.expect(&[
+            UniversalTag::UTF8String,
+            UniversalTag::NumericString,
+            UniversalTag::PrintableString,
+            UniversalTag::IA5String,
+            UniversalTag::VisibleString,
+        ])?;
+        Ok(String::der_from_intermediate(self.i)?)
+    }
+
+    fn elements(self) -> Result<Elements, Error> {
+        self.expect(&[UniversalTag::Sequence])?;
+        Ok(Elements {
+            items: self.i.children()?.into_iter(),
+        })
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Deserializer {
+    type Deserializer = Deserializer;
+
+    fn into_deserializer(self) -> Deserializer {
+        self
+    }
+}
+
+macro_rules! deserialize_integer {
+    ($($method:ident $visit:ident),+) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.$visit(self.integer()?)
+        }
+    )+};
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.tagged.is_some() || self.i.class != Class::Universal {
+            return Err(Error::new("the type of tagged values is not known"));
+        }
+        match UniversalTag::from_tag(self.i.tag) {
+            Some(UniversalTag::Boolean) => self.deserialize_bool(visitor),
+            Some(UniversalTag::Integer) => match self.integer::<i64>() {
+                Ok(v) => visitor.visit_i64(v),
+                Err(_) => visitor.visit_i128(self.integer()?),
+            },
+            Some(UniversalTag::OctetString) => self.deserialize_byte_buf(visitor),
+            Some(UniversalTag::Null) => self.deserialize_unit(visitor),
+            Some(UniversalTag::Sequence) => self.deserialize_seq(visitor),
+            _ => self.deserialize_string(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.expect(&[UniversalTag::Boolean])?;
+        visitor.visit_bool(bool::der_from_intermediate(self.i)?)
+    }
+
+    deserialize_integer!(
+        deserialize_i8 visit_i8,
+        deserialize_i16 visit_i16,
+        deserialize_i32 visit_i32,
+        deserialize_i64 visit_i64,
+        deserialize_i128 visit_i128,
+        deserialize_u8 visit_u8,
+        deserialize_u16 visit_u16,
+        deserialize_u32 visit_u32,
+        deserialize_u64 visit_u64,
+        deserialize_u128 visit_u128
+    );
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::new("REAL is not supported"))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::new("REAL is not supported"))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let s = self.string()?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(Error::new("expected a single character")),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.string()?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.expect(&[UniversalTag::OctetString])?;
+        visitor.visit_byte_buf(self.i.content)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // Absent values are not passed to the Deserializer at all
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.expect(&[UniversalTag::Null])?;
+        if !self.i.content.is_empty() {
+            return Err(Error::new("NULL with content"));
+        }
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut elements = self.elements()?;
+        let value = visitor.visit_seq(&mut elements)?;
+        elements.finish()?;
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.own_tag(name)?.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let entries = self
+            .elements()?
+            .items
+            .map(|entry| {
+                let mut pair = Deserializer::new(entry).elements()?.items;
+                match (pair.next(), pair.next(), pair.next()) {
+                    (Some(key), Some(value), None) => Ok((key, value)),
+                    _ => Err(Error::new("map entry is not a key and a value")),
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        visitor.visit_map(de::value::MapDeserializer::new(
+            entries
+                .into_iter()
+                .map(|(key, value)| (Deserializer::new(key), Deserializer::new(value))),
+        ))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let mut elements = self.own_tag(name)?.elements()?.items.peekable();
+        let mut present = vec![];
+        for (index, field) in fields.iter().enumerate() {
+            let tagging = Tagging::automatic(field, index)?;
+            let later = fields[index + 1..]
+                .iter()
+                .enumerate()
+                .map(|(offset, later)| Tagging::automatic(later, index + 1 + offset))
+                .collect::<Result<Vec<Tagging>, Error>>()?;
+            let matching = |i: &Intermediate| match tagging {
+                Tagging::None => !later.iter().any(|later| later.matches(i)),
+                _ => tagging.matches(i),
+            };
+            if let Some(i) = elements.next_if(matching) {
+                present.push((*field, tagging.deserializer(i)?));
+            }
+        }
+        if elements.next().is_some() {
+            return Err(Error::new(format!("unexpected element in {}", name)));
+        }
+        visitor.visit_map(Fields {
+            fields: present.into_iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let alternative = match self.tagged {
+            None => self.i,
+            Some(Mode::Implicit) => return Err(Error::new("a CHOICE can not be tagged IMPLICIT")),
+            Some(_) => unwrap_explicit(self.i)?,
+        };
+        let taggings = variants
+            .iter()
+            .enumerate()
+            .map(|(index, variant)| Tagging::automatic(variant, index))
+            .collect::<Result<Vec<Tagging>, Error>>()?;
+        let index = taggings
+            .iter()
+            .position(|tagging| tagging.matches(&alternative))
+            .or_else(|| taggings.iter().position(|t| *t == Tagging::None))
+            .ok_or_else(|| {
+                Error::new(format!(
+                    "no alternative of {} has tag {} of class {:?}",
+                    name, alternative.tag, alternative.class
+                ))
+            })?;
+        visitor.visit_enum(Alternative {
+            variant: variants[index],
+            deserializer: taggings[index].deserializer(alternative)?,
+        })
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+impl Deserializer {
+    /// Check the tag a struct is renamed to, if any, and return the Deserializer of the SEQUENCE
+    fn own_tag(self, name: &str) -> Result<Deserializer, Error> {
+        let own = Tagging::parse(name, Tagging::None)?;
+        if self.tagged.is_some() || own == Tagging::None {
+            return Ok(self);
+        }
+        if !own.matches(&self.i) {
+            return Err(Error::new(format!(
+                "expected tag of {}, found tag {} of class {:?}",
+                name, self.i.tag, self.i.class
+            )));
+        }
+        let mut inner = own.deserializer(self.i)?;
+        // The tag replaces the SEQUENCE tag
+        inner.tagged = inner.tagged.or(Some(Mode::Implicit));
+        Ok(inner)
+    }
+}
+
+/// Elements of a `SEQUENCE`
+struct Elements {
+    items: alloc::vec::IntoIter<Intermediate>,
+}
+
+impl Elements {
+    fn finish(&mut self) -> Result<(), Error> {
+        match self.items.next() {
+            Some(_) => Err(Error::new("unexpected element after the last one")),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for Elements {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.items.next() {
+            Some(i) => seed.deserialize(Deserializer::new(i)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+/// Present fields of a struct
+struct Fields {
+    fields: alloc::vec::IntoIter<(&'static str, Deserializer)>,
+    value: Option<Deserializer>,
+}
+
+impl<'de> de::MapAccess<'de> for Fields {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.fields.next() {
+            Some((field, value)) => {
+                self.value = Some(value);
+                seed.deserialize(field.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, Error> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(Error::new("struct field value without key")),
+        }
+    }
+}
+
+/// The alternative of an enum
+struct Alternative {
+    variant: &'static str,
+    deserializer: Deserializer,
+}
+
+impl<'de> de::EnumAccess<'de> for Alternative {
+    type Error = Error;
+    type Variant = Deserializer;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<(T::Value, Deserializer), Error> {
+        let variant =
+            seed.deserialize(IntoDeserializer::<Error>::into_deserializer(self.variant))?;
+        Ok((variant, self.deserializer))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Deserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{from_bytes, to_bytes};
+    use alloc::collections::BTreeMap;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Point,
+        Circle(u32),
+        #[serde(rename = "Line [APPLICATION 7] EXPLICIT")]
+        Line(i64, i64),
+        Rect {
+            width: u16,
+            height: Option<u16>,
+        },
+        #[serde(rename = "Label NOTAG")]
+        Label(String),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(rename = "Drawing [APPLICATION 2]")]
+    struct Drawing {
+        id: u8,
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+        shape: Shape,
+        comment: Option<String>,
+        #[serde(rename = "layers NOTAG")]
+        layers: Vec<i32>,
+        tags: BTreeMap<String, bool>,
+        #[serde(skip)]
+        cache: u8,
+        last: Option<()>,
+    }
+
+    #[test]
+    fn serialize_struct() {
+        let drawing = Drawing {
+            id: 200,
+            data: vec![0xAB],
+            shape: Shape::Circle(5),
+            comment: None,
+            layers: vec![-1],
+            tags: vec![("a".to_string(), true)].into_iter().collect(),
+            cache: 0,
+            last: Some(()),
+        };
+        let encoded = to_bytes(&drawing).unwrap();
+        assert_eq!(
+            encoded,
+            vec![
+                0x62, 0x1D, // [APPLICATION 2] IMPLICIT SEQUENCE
+                0x80, 0x02, 0x00, 0xC8, // [0] IMPLICIT INTEGER 200
+                0x81, 0x01, 0xAB, // [1] IMPLICIT OCTET STRING
+                0xA2, 0x03, 0x81, 0x01, 0x05, // [2] EXPLICIT CHOICE, [1] IMPLICIT INTEGER
+                0x30, 0x03, 0x02, 0x01, 0xFF, // SEQUENCE OF INTEGER
+                0xA5, 0x08, 0x30, 0x06, 0x0C, 0x01, 0x61, 0x01, 0x01, 0xFF, // [5] map
+                0x86, 0x00, // [6] IMPLICIT NULL, skipped fields are not counted
+            ]
+        );
+        assert_eq!(drawing, from_bytes(&encoded).unwrap());
+    }
+
+    #[test]
+    fn serialize_choice() {
+        let shapes = vec![
+            Shape::Point,
+            Shape::Line(-2, 3),
+            Shape::Rect {
+                width: 4,
+                height: None,
+            },
+            Shape::Label("x".to_string()),
+        ];
+        let encoded = to_bytes(&shapes).unwrap();
+        assert_eq!(
+            encoded,
+            vec![
+                0x30, 0x14, // SEQUENCE OF
+                0x80, 0x00, // [0] IMPLICIT NULL
+                0x67, 0x08, 0x30, 0x06, 0x02, 0x01, 0xFE, 0x02, 0x01, 0x03, // [APPLICATION 7]
+                0xA3, 0x03, 0x80, 0x01, 0x04, // [3] IMPLICIT SEQUENCE
+                0x0C, 0x01, 0x78, // UTF8String
+            ]
+        );
+        assert_eq!(shapes, from_bytes::<Vec<Shape>>(&encoded).unwrap());
+        assert_eq!(
+            (1u128 << 100, 'ä'),
+            from_bytes(&to_bytes(&(1u128 << 100, 'ä')).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn serialize_errors() {
+        assert!(to_bytes(&None::<u8>).is_err());
+        assert!(to_bytes(&1.5f64).is_err());
+        assert!(to_bytes(&vec![Some(1), None]).is_err());
+        assert!(from_bytes::<u8>(&[0x02, 0x02, 0x01, 0x00]).is_err());
+        assert!(from_bytes::<u8>(&[0x02, 0x02, 0x00, 0x01]).is_err());
+        assert!(from_bytes::<i8>(&[0x01, 0x01, 0xFF]).is_err());
+        assert!(from_bytes::<bool>(&[0x01, 0x01, 0xFF, 0x00]).is_err());
+        assert!(from_bytes::<Shape>(&[0x89, 0x00]).is_err());
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct Invalid {
+            #[serde(rename = "a [SOMETHING 1]")]
+            a: u8,
+        }
+        assert!(to_bytes(&Invalid { a: 1 }).is_err());
+
+        #[derive(Serialize, Deserialize, Debug)]
+        struct Implicit {
+            #[serde(rename = "shape [1] IMPLICIT")]
+            shape: Shape,
+        }
+        assert!(to_bytes(&Implicit {
+            shape: Shape::Point
+        })
+        .is_err());
+    }
+}
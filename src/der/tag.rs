@@ -1,6 +1,7 @@
 use super::*;
-use byteorder::{ReadBytesExt, WriteBytesExt};
-use std::io::{self, Read, Write};
+use crate::io::{self, Read, Write};
+use crate::io::{ReadOctet, WriteOctet};
+use crate::prelude::*;
 
 /// Encode DER tag bytes
 pub fn der_encode_tag_bytes(
@@ -58,7 +59,7 @@ pub fn der_decode_tag_bytes(r: &mut dyn Read) -> io::Result<(usize, u32, Class,
                 break;
             }
         }
-        if (7 * bytes.len()) > (u32::max_value() as f64).log2() as usize {
+        if (7 * bytes.len()) > u32::BITS as usize {
             // Afl found
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
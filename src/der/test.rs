@@ -1,9 +1,10 @@
 use super::*;
+use crate::types::PrintableString;
 
 #[test]
 fn decode_tag_bytes() {
     for i in 0..32000 {
-        let mut stream = ::std::io::Cursor::new(Vec::<u8>::new());
+        let mut stream = crate::io::Cursor::new(Vec::<u8>::new());
         der_encode_tag_bytes(i, Class::Private, ContentType::Constructed, &mut stream).unwrap();
         stream.set_position(0);
         let (_, tg, _, _) = der_decode_tag_bytes(&mut stream).unwrap();
@@ -13,7 +14,7 @@ fn decode_tag_bytes() {
 
 #[test]
 fn decode_invalid_tag_0xff() {
-    let mut stream = ::std::io::Cursor::new(vec![0xff]);
+    let mut stream = crate::io::Cursor::new(vec![0xff]);
     if let Ok(_) = der_decode_tag_bytes(&mut stream) {
         panic!("This is illegal!");
     }
@@ -47,7 +48,7 @@ fn encode_length_long_long_no_crash() {
 #[test]
 fn decode_length() {
     for i in 0..32000 {
-        let mut stream = ::std::io::Cursor::new(Vec::<u8>::new());
+        let mut stream = crate::io::Cursor::new(Vec::<u8>::new());
         der_encode_length_bytes(i as usize, &mut stream).unwrap();
         stream.set_position(0);
         let (_, res) = der_decode_length_bytes(&mut stream).unwrap();
@@ -148,6 +149,50 @@ fn serialize_sequence() {
     // f.write_all(&data.der_bytes().unwrap()).unwrap();
 }
 
+#[derive(Debug, PartialEq)]
+struct OptionalStruct {
+    pub alpha: Option<i32>,
+    pub beta: Option<bool>,
+    pub gamma: Option<TestChoice>,
+    pub delta: i32,
+}
+
+der_sequence! {OptionalStruct:
+    alpha: NOTAG TYPE Option<i32>,
+    beta: EXPLICIT TAG CONTEXT 0; TYPE Option<bool>,
+    gamma: NOTAG TYPE Option<TestChoice>,
+    delta: IMPLICIT TAG CONTEXT 1; TYPE i32,
+}
+
+#[test]
+fn serialize_optional() {
+    let data = OptionalStruct {
+        alpha: None,
+        beta: Some(true),
+        gamma: None,
+        delta: 7,
+    };
+    let encoded = data.der_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        vec![0x30, 0x08, 0xA0, 0x03, 0x01, 0x01, 0xFF, 0x81, 0x01, 0x07]
+    );
+    assert_eq!(data, OptionalStruct::der_from_bytes(encoded).unwrap());
+
+    let data = OptionalStruct {
+        alpha: Some(1),
+        beta: None,
+        gamma: Some(TestChoice::Gamma("a".to_string())),
+        delta: 7,
+    };
+    assert_eq!(
+        data,
+        OptionalStruct::der_from_bytes(data.der_bytes().unwrap()).unwrap()
+    );
+    // delta is not optional
+    assert!(OptionalStruct::der_from_bytes(vec![0x30, 0x03, 0x02, 0x01, 0x01]).is_err());
+}
+
 #[derive(Debug, PartialEq, Clone)]
 enum TestEnum {
     Alpha = 1,
@@ -193,3 +238,1134 @@ fn serialize_choice() {
         );
     }
 }
+
+#[derive(Debug)]
+struct Constrained {
+    pub port: i32,
+    pub key: Vec<u8>,
+    pub name: PrintableString,
+    pub hosts: Vec<String>,
+}
+
+der_sequence! {Constrained WITH(XER, JER, PER, OER):
+    port: NOTAG VALUE(0..65535); TYPE i32,
+    key: IMPLICIT TAG CONTEXT 0; SIZE(4); TYPE Vec<u8>,
+    name: NOTAG SIZE(1..8); FROM("abcdefghijklmnopqrstuvwxyz"); TYPE PrintableString,
+    hosts: NOTAG SIZE(0..2); TYPE Vec<String>,
+}
+
+/// The same sequence without constraints, to produce invalid encodings
+#[derive(Debug)]
+struct Unconstrained {
+    pub port: i32,
+    pub key: Vec<u8>,
+    pub name: PrintableString,
+    pub hosts: Vec<String>,
+}
+
+der_sequence! {Unconstrained WITH(XER, JER, PER, OER):
+    port: NOTAG TYPE i32,
+    key: IMPLICIT TAG CONTEXT 0; TYPE Vec<u8>,
+    name: NOTAG TYPE PrintableString,
+    hosts: NOTAG TYPE Vec<String>,
+}
+
+#[derive(Debug)]
+enum ConstrainedChoice {
+    Small(i32),
+}
+
+der_choice! {ConstrainedChoice:
+    Small: IMPLICIT TAG CONTEXT 0; VALUE(-8..7); TYPE i32,
+}
+
+#[test]
+fn enforce_constraints() {
+    use crate::jer::JER;
+    use crate::oer::OER;
+    use crate::per::PER;
+    use crate::xer::{XERDecodeable, XEREncodeable};
+
+    let valid = Constrained {
+        port: 443,
+        key: vec![1, 2, 3, 4],
+        name: PrintableString::from("host".to_string()),
+        hosts: vec!["a".to_string()],
+    };
+    assert!(Constrained::der_from_bytes(valid.der_bytes().unwrap()).is_ok());
+
+    let cases: Vec<(i32, Vec<u8>, &str, usize, &str)> = vec![
+        (
+            70000,
+            vec![1, 2, 3, 4],
+            "host",
+            0,
+            "Constrained.port: value 70000 violates constraint (0..65535)",
+        ),
+        (
+            443,
+            vec![1, 2, 3],
+            "host",
+            0,
+            "Constrained.key: size 3 violates constraint SIZE(4)",
+        ),
+        (
+            443,
+            vec![1, 2, 3, 4],
+            "Host",
+            0,
+            "Constrained.name: character 'H' violates constraint \
+             SIZE(1..8) FROM(\"abcdefghijklmnopqrstuvwxyz\")",
+        ),
+        (
+            443,
+            vec![1, 2, 3, 4],
+            "host",
+            3,
+            "Constrained.hosts: size 3 violates constraint SIZE(0..2)",
+        ),
+    ];
+    for (port, key, name, hosts, message) in cases {
+        let hosts = vec!["a".to_string(); hosts];
+        let constrained = Constrained {
+            port,
+            key: key.clone(),
+            name: PrintableString::from(name.to_string()),
+            hosts: hosts.clone(),
+        };
+        assert_eq!(constrained.der_bytes().unwrap_err().to_string(), message);
+        assert_eq!(constrained.xer_string().unwrap_err().to_string(), message);
+        assert_eq!(constrained.jer_string().unwrap_err().to_string(), message);
+        assert_eq!(constrained.uper_bytes().unwrap_err().to_string(), message);
+        assert_eq!(constrained.oer_bytes().unwrap_err().to_string(), message);
+
+        // Decoding checks the constraints as well. PER and OER are left out, because there the
+        // constraints change the encoding itself.
+        let invalid = Unconstrained {
+            port,
+            key,
+            name: PrintableString::from(name.to_string()),
+            hosts,
+        };
+        let error = Constrained::der_from_bytes(invalid.der_bytes().unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), message);
+        let error = Constrained::xer_from_str(&invalid.xer_string().unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), message);
+        let error = Constrained::jer_from_str(&invalid.jer_string().unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), message);
+    }
+
+    let error = ConstrainedChoice::Small(8).der_bytes().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "ConstrainedChoice.Small: value 8 violates constraint (-8..7)"
+    );
+    let mut encoded = ConstrainedChoice::Small(7).der_bytes().unwrap();
+    *encoded.last_mut().unwrap() = 8;
+    let error = ConstrainedChoice::der_from_bytes(encoded).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "ConstrainedChoice.Small: value 8 violates constraint (-8..7)"
+    );
+}
+
+#[derive(Debug)]
+struct Misconstrained {
+    pub name: String,
+    pub flag: bool,
+}
+
+der_sequence! {Misconstrained:
+    name: NOTAG VALUE(0..7); TYPE String,
+    flag: NOTAG SIZE(1); TYPE bool,
+}
+
+#[test]
+fn reject_inapplicable_constraints() {
+    let value = Misconstrained {
+        name: "a".to_string(),
+        flag: true,
+    };
+    assert_eq!(
+        value.der_bytes().unwrap_err().to_string(),
+        "Misconstrained.name: value constraint (0..7) does not apply to alloc::string::String"
+    );
+    let encoded = vec![0x30, 0x06, 0x0C, 0x01, 0x61, 0x01, 0x01, 0xFF];
+    assert!(Misconstrained::der_from_bytes(encoded).is_err());
+    assert_eq!(
+        crate::constraints::Constraints::size(1, 1)
+            .check_applicable::<bool>(true, false, false)
+            .unwrap_err()
+            .to_string(),
+        "size constraint SIZE(1) does not apply to bool"
+    );
+}
+
+/// A type with nothing but a hand-written DER implementation
+#[derive(Debug, PartialEq)]
+struct Celsius(i32);
+
+impl DER for Celsius {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::Integer
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Primitive
+    }
+
+    fn der_encode_content(&self, w: &mut dyn crate::io::Write) -> crate::io::Result<()> {
+        self.0.der_encode_content(w)
+    }
+
+    fn der_decode_content(r: &mut dyn crate::io::Read, length: usize) -> crate::io::Result<Self> {
+        i32::der_decode_content(r, length).map(Celsius)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Forecast {
+    pub high: Celsius,
+    pub low: Option<Celsius>,
+}
+
+der_sequence! {Forecast AUTOMATIC TAGS:
+    high: TYPE Celsius,
+    low: TYPE Option<Celsius>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Weather {
+    Sunny(Celsius),
+    Cloudy,
+}
+
+der_choice! {Weather AUTOMATIC TAGS:
+    Sunny: TYPE Celsius,
+    Cloudy,
+}
+
+#[test]
+fn der_only_components() {
+    let forecast = Forecast {
+        high: Celsius(21),
+        low: Some(Celsius(-3)),
+    };
+    let encoded = forecast.der_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        vec![0x30, 0x06, 0x80, 0x01, 0x15, 0x81, 0x01, 0xFD]
+    );
+    assert_eq!(Forecast::der_from_bytes(encoded).unwrap(), forecast);
+    for weather in [Weather::Sunny(Celsius(30)), Weather::Cloudy] {
+        let encoded = weather.der_bytes().unwrap();
+        assert_eq!(Weather::der_from_bytes(encoded).unwrap(), weather);
+    }
+}
+
+/// Version 1 of a message, which a newer version extends
+#[derive(Debug, PartialEq)]
+struct MessageV1 {
+    pub id: i32,
+    pub kind: KindV1,
+}
+
+der_sequence! {MessageV1:
+    id: NOTAG TYPE i32,
+    kind: NOTAG TYPE KindV1,
+    ...
+}
+
+/// Version 1 that keeps the extension additions for re-encoding
+#[derive(Debug, PartialEq)]
+struct RelayV1 {
+    pub id: i32,
+    pub kind: KindV1,
+    pub extensions: Vec<Intermediate>,
+}
+
+der_sequence! {RelayV1:
+    id: NOTAG TYPE i32,
+    kind: NOTAG TYPE KindV1,
+    ... KEEP extensions
+}
+
+/// Version 1 without the extension marker
+#[derive(Debug, PartialEq)]
+struct StrictV1 {
+    pub id: i32,
+    pub kind: KindV1,
+}
+
+der_sequence! {StrictV1:
+    id: NOTAG TYPE i32,
+    kind: NOTAG TYPE KindV1,
+}
+
+/// Version 2 of the message, which adds components, values and alternatives
+#[derive(Debug, PartialEq)]
+struct MessageV2 {
+    pub id: i32,
+    pub kind: KindV2,
+    pub name: String,
+    pub payload: PayloadV2,
+}
+
+der_sequence! {MessageV2:
+    id: NOTAG TYPE i32,
+    kind: NOTAG TYPE KindV2,
+    name: NOTAG TYPE String,
+    payload: EXPLICIT TAG CONTEXT 0; TYPE PayloadV2,
+}
+
+#[derive(Debug, PartialEq)]
+enum KindV1 {
+    Request = 0,
+    Response = 1,
+    Unknown,
+}
+
+der_enumerated!(KindV1, Request, Response, ... Unknown);
+
+#[derive(Debug, PartialEq)]
+enum KindV2 {
+    Request = 0,
+    Response = 1,
+    Notification = 2,
+}
+
+der_enumerated!(KindV2, Request, Response, Notification);
+
+#[derive(Debug, PartialEq)]
+enum PayloadV1 {
+    Text(String),
+    Unknown(Intermediate),
+}
+
+der_choice! {PayloadV1:
+    Text: IMPLICIT TAG CONTEXT 0; TYPE String,
+    ... Unknown
+}
+
+#[derive(Debug, PartialEq)]
+enum PayloadV2 {
+    Text(String),
+    Number(i32),
+}
+
+der_choice! {PayloadV2:
+    Text: IMPLICIT TAG CONTEXT 0; TYPE String,
+    Number: IMPLICIT TAG CONTEXT 1; TYPE i32,
+}
+
+#[test]
+fn extension_markers() {
+    let newer = MessageV2 {
+        id: 7,
+        kind: KindV2::Notification,
+        name: "update".to_string(),
+        payload: PayloadV2::Number(42),
+    };
+    let encoded = newer.der_bytes().unwrap();
+
+    let older = MessageV1::der_from_bytes(encoded.clone()).unwrap();
+    assert_eq!(
+        older,
+        MessageV1 {
+            id: 7,
+            kind: KindV1::Unknown,
+        }
+    );
+    assert!(StrictV1::der_from_bytes(encoded.clone()).is_err());
+
+    let relay = RelayV1::der_from_bytes(encoded.clone()).unwrap();
+    assert_eq!(relay.extensions.len(), 2);
+    assert_eq!(relay.extensions[0].tag, UniversalTag::UTF8String as u32);
+    // The unknown enumeration value itself is lost
+    assert!(relay.der_bytes().is_err());
+    let relay = RelayV1 {
+        kind: KindV1::Response,
+        ..relay
+    };
+    let reencoded = relay.der_bytes().unwrap();
+    assert_eq!(RelayV1::der_from_bytes(reencoded).unwrap(), relay);
+
+    let payload = PayloadV2::Number(42).der_bytes().unwrap();
+    let unknown = PayloadV1::der_from_bytes(payload.clone()).unwrap();
+    match unknown {
+        PayloadV1::Unknown(ref i) => assert_eq!(i.tag, 1),
+        _ => panic!("expected an unknown alternative"),
+    }
+    assert_eq!(unknown.der_bytes().unwrap(), payload);
+    let text = PayloadV1::Text("hello".to_string());
+    assert_eq!(
+        PayloadV1::der_from_bytes(text.der_bytes().unwrap()).unwrap(),
+        text
+    );
+}
+
+#[derive(Debug, PartialEq)]
+struct Automatic {
+    pub id: i32,
+    pub name: Option<String>,
+    pub pick: AutomaticChoice,
+    pub flag: bool,
+}
+
+der_sequence! {Automatic AUTOMATIC TAGS:
+    id: VALUE(0..255); TYPE i32,
+    name: TYPE Option<String>,
+    pick: TYPE AutomaticChoice,
+    flag: TYPE bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum AutomaticChoice {
+    Small(i32),
+    Text(String),
+}
+
+der_choice! {AutomaticChoice WITH(XER, JER, PER, OER) AUTOMATIC TAGS:
+    Small: TYPE i32,
+    Text: TYPE String,
+}
+
+#[derive(Debug, PartialEq)]
+struct ImplicitTags {
+    pub id: i32,
+    pub flag: bool,
+    pub pick: AutomaticChoice,
+}
+
+der_sequence! {ImplicitTags IMPLICIT TAGS:
+    id: TAG CONTEXT 0; TYPE i32,
+    flag: TYPE bool,
+    pick: TAG CONTEXT 1; TYPE AutomaticChoice,
+}
+
+#[derive(Debug, PartialEq)]
+struct ExplicitTags {
+    pub id: i32,
+    pub flag: bool,
+}
+
+der_sequence! {ExplicitTags EXPLICIT TAGS:
+    id: TAG CONTEXT 0; TYPE i32,
+    flag: IMPLICIT TAG CONTEXT 1; TYPE bool,
+    ...
+}
+
+/// Numbering by position would give `id` and `flag` the same tag
+#[derive(Debug, PartialEq)]
+struct MixedTags {
+    pub id: i32,
+    pub flag: bool,
+    pub name: Option<String>,
+}
+
+der_sequence! {MixedTags AUTOMATIC TAGS:
+    id: TYPE i32,
+    flag: TAG CONTEXT 0; TYPE bool,
+    name: TYPE Option<String>,
+}
+
+#[test]
+fn tagging_environments() {
+    use crate::oer::OER;
+    use crate::per::PER;
+
+    let value = Automatic {
+        id: 5,
+        name: Some("a".to_string()),
+        pick: AutomaticChoice::Text("b".to_string()),
+        flag: true,
+    };
+    let encoded = value.der_bytes().unwrap();
+    // Numbered context tags, EXPLICIT for the choice
+    assert_eq!(&encoded[2..8], &[0x80, 0x01, 0x05, 0x81, 0x01, 0x61][..]);
+    assert_eq!(encoded[8], 0xA2);
+    assert_eq!(&encoded[encoded.len() - 3..], &[0x83, 0x01, 0xFF][..]);
+    assert_eq!(Automatic::der_from_bytes(encoded).unwrap(), value);
+    let without_name = Automatic {
+        name: None,
+        ..value
+    };
+    assert_eq!(
+        Automatic::der_from_bytes(without_name.der_bytes().unwrap()).unwrap(),
+        without_name
+    );
+    assert!(Automatic {
+        id: 256,
+        ..without_name
+    }
+    .der_bytes()
+    .is_err());
+
+    let text = AutomaticChoice::Text("b".to_string());
+    assert_eq!(text.uper_bytes().unwrap(), vec![0x80, 0xB1, 0x00]);
+    assert_eq!(text.oer_bytes().unwrap(), vec![0x81, 0x01, 0x62]);
+
+    let value = ImplicitTags {
+        id: 5,
+        flag: true,
+        pick: AutomaticChoice::Small(1),
+    };
+    let encoded = value.der_bytes().unwrap();
+    assert_eq!(
+        &encoded[2..9],
+        &[0x80, 0x01, 0x05, 0x01, 0x01, 0xFF, 0xA1][..]
+    );
+    assert_eq!(ImplicitTags::der_from_bytes(encoded).unwrap(), value);
+
+    // A tagged field turns automatic tagging off, the other fields keep their universal tags
+    let value = MixedTags {
+        id: 5,
+        flag: true,
+        name: Some("a".to_string()),
+    };
+    let encoded = value.der_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        vec![0x30, 0x09, 0x02, 0x01, 0x05, 0x80, 0x01, 0xFF, 0x0C, 0x01, 0x61]
+    );
+    assert_eq!(MixedTags::der_from_bytes(encoded).unwrap(), value);
+
+    let value = ExplicitTags { id: 5, flag: true };
+    let encoded = value.der_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        vec![0x30, 0x08, 0xA0, 0x03, 0x02, 0x01, 0x05, 0x81, 0x01, 0xFF]
+    );
+    assert_eq!(ExplicitTags::der_from_bytes(encoded).unwrap(), value);
+}
+
+#[derive(Debug, PartialEq)]
+enum Inner {
+    Number(i32),
+    Text(String),
+}
+
+der_choice! {Inner WITH(XER, JER, PER, OER):
+    Number: NOTAG TYPE i32,
+    Text: NOTAG TYPE String,
+}
+
+#[derive(Debug, PartialEq)]
+enum Outer {
+    Inner(Inner),
+    Flag(bool),
+}
+
+der_choice! {Outer WITH(XER, JER, PER, OER):
+    Inner: NOTAG TYPE Inner,
+    Flag: IMPLICIT TAG CONTEXT 0; TYPE bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct Holder {
+    pub first: Inner,
+    pub second: Option<Inner>,
+    pub outers: Vec<Outer>,
+    pub last: bool,
+}
+
+der_sequence! {Holder WITH(XER, JER, PER, OER):
+    first: NOTAG TYPE Inner,
+    second: NOTAG TYPE Option<Inner>,
+    outers: NOTAG TYPE Vec<Outer>,
+    last: NOTAG TYPE bool,
+}
+
+#[test]
+fn untagged_choices() {
+    use crate::oer::OER;
+    use crate::per::PER;
+
+    assert_eq!(
+        Inner::Number(5).der_bytes().unwrap(),
+        vec![0x02, 0x01, 0x05]
+    );
+    assert_eq!(
+        Outer::Inner(Inner::Text("a".to_string()))
+            .der_bytes()
+            .unwrap(),
+        vec![0x0C, 0x01, 0x61]
+    );
+    assert_eq!(
+        Outer::Flag(true).der_bytes().unwrap(),
+        vec![0x80, 0x01, 0xFF]
+    );
+    assert_eq!(
+        Inner::der_tags(),
+        vec![(Class::Universal, 2), (Class::Universal, 12)]
+    );
+    assert!(Outer::der_matches(Class::Universal, 12));
+    assert!(!Outer::der_matches(Class::Universal, 1));
+
+    let mut value = Holder {
+        first: Inner::Number(1),
+        second: None,
+        outers: vec![Outer::Inner(Inner::Number(2)), Outer::Flag(false)],
+        last: true,
+    };
+    let encoded = value.der_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        vec![
+            0x30, 0x0E, 0x02, 0x01, 0x01, 0x30, 0x06, 0x02, 0x01, 0x02, 0x80, 0x01, 0x00, 0x01,
+            0x01, 0xFF
+        ]
+    );
+    assert_eq!(Holder::der_from_bytes(encoded).unwrap(), value);
+
+    value.second = Some(Inner::Text("x".to_string()));
+    assert_eq!(
+        Holder::der_from_bytes(value.der_bytes().unwrap()).unwrap(),
+        value
+    );
+    assert_eq!(
+        Holder::uper_from_bytes(&value.uper_bytes().unwrap()).unwrap(),
+        value
+    );
+    assert_eq!(
+        Holder::oer_from_bytes(&value.oer_bytes().unwrap()).unwrap(),
+        value
+    );
+
+    // The nested choice is ordered by its smallest tag in PER and supplies the tag in OER
+    assert_eq!(
+        Outer::Inner(Inner::Number(2)).uper_bytes().unwrap(),
+        vec![0x00, 0x40, 0x80]
+    );
+    assert_eq!(
+        Outer::Inner(Inner::Number(2)).oer_bytes().unwrap(),
+        vec![0x02, 0x01, 0x02]
+    );
+    assert_eq!(Outer::Flag(true).oer_bytes().unwrap(), vec![0x80, 0xFF]);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Command {
+    Start,
+    Move { x: i32, y: i32 },
+    Say(String),
+}
+
+der_choice! {Command WITH(XER, JER, PER, OER):
+    Start: IMPLICIT TAG CONTEXT 0;,
+    Move: EXPLICIT TAG CONTEXT 1; {
+        x: NOTAG TYPE i32,
+        y: NOTAG TYPE i32,
+    },
+    Say: IMPLICIT TAG CONTEXT 2; TYPE String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum AutomaticCommand {
+    Stop,
+    Jump { height: i32 },
+}
+
+der_choice! {AutomaticCommand AUTOMATIC TAGS:
+    Stop,
+    Jump { height: NOTAG TYPE i32 },
+}
+
+// `Level` is not `Clone`, the struct variant borrows it while encoding
+#[derive(Debug, PartialEq)]
+enum Setting {
+    Volume { level: Level },
+}
+
+der_choice! {Setting WITH(XER, JER, PER, OER):
+    Volume: EXPLICIT TAG CONTEXT 0; { level: NOTAG TYPE Level },
+}
+
+#[derive(Debug, PartialEq)]
+enum Level {
+    Low,
+    High,
+    Other(i64),
+}
+
+der_enumerated!(Level WITH(XER, JER, PER, OER), Low = 1, High = 5, ... Other(i64));
+
+#[test]
+fn unit_and_struct_variants() {
+    use crate::jer::JER;
+    use crate::oer::OER;
+    use crate::per::PER;
+    use crate::xer::{XERDecodeable, XEREncodeable};
+
+    assert_eq!(Command::Start.der_bytes().unwrap(), vec![0x80, 0x00]);
+    let moved = Command::Move { x: 1, y: 2 };
+    assert_eq!(
+        moved.der_bytes().unwrap(),
+        vec![0xA1, 0x08, 0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02]
+    );
+    assert_eq!(
+        Command::Say("hi".to_string()).der_bytes().unwrap(),
+        vec![0x82, 0x02, 0x68, 0x69]
+    );
+    assert_eq!(moved.jer_string().unwrap(), "{\"Move\":{\"x\":1,\"y\":2}}");
+
+    for value in [Command::Start, moved, Command::Say("hi".to_string())] {
+        assert_eq!(
+            Command::der_from_bytes(value.der_bytes().unwrap()).unwrap(),
+            value
+        );
+        assert_eq!(
+            Command::xer_from_str(&value.xer_string().unwrap()).unwrap(),
+            value
+        );
+        assert_eq!(
+            Command::jer_from_str(&value.jer_string().unwrap()).unwrap(),
+            value
+        );
+        assert_eq!(
+            Command::uper_from_bytes(&value.uper_bytes().unwrap()).unwrap(),
+            value
+        );
+        assert_eq!(
+            Command::oer_from_bytes(&value.oer_bytes().unwrap()).unwrap(),
+            value
+        );
+    }
+
+    assert_eq!(
+        AutomaticCommand::Stop.der_bytes().unwrap(),
+        vec![0x80, 0x00]
+    );
+    let jump = AutomaticCommand::Jump { height: 3 };
+    assert_eq!(
+        jump.der_bytes().unwrap(),
+        vec![0xA1, 0x03, 0x02, 0x01, 0x03]
+    );
+    assert_eq!(
+        AutomaticCommand::der_from_bytes(jump.der_bytes().unwrap()).unwrap(),
+        jump
+    );
+
+    let volume = Setting::Volume { level: Level::High };
+    assert_eq!(
+        volume.der_bytes().unwrap(),
+        vec![0xA0, 0x07, 0x30, 0x05, 0x0A, 0x03, 0x02, 0x01, 0x05]
+    );
+    assert_eq!(
+        Setting::der_from_bytes(volume.der_bytes().unwrap()).unwrap(),
+        volume
+    );
+    assert_eq!(
+        Setting::jer_from_str(&volume.jer_string().unwrap()).unwrap(),
+        volume
+    );
+    assert_eq!(
+        Setting::uper_from_bytes(&volume.uper_bytes().unwrap()).unwrap(),
+        volume
+    );
+}
+
+#[test]
+fn enumerated_other() {
+    use crate::jer::JER;
+    use crate::oer::OER;
+    use crate::per::PER;
+
+    assert_eq!(
+        Level::High.der_bytes().unwrap(),
+        vec![0x0A, 0x03, 0x02, 0x01, 0x05]
+    );
+    let encoded = vec![0x0A, 0x03, 0x02, 0x01, 0x07];
+    let decoded = Level::der_from_bytes(encoded.clone()).unwrap();
+    assert_eq!(decoded, Level::Other(7));
+    // The value is forwarded as it was received
+    assert_eq!(decoded.der_bytes().unwrap(), encoded);
+    assert!(Level::Other(1 << 40).der_bytes().is_err());
+
+    assert_eq!(Level::oer_from_bytes(&[0x07]).unwrap(), Level::Other(7));
+    assert_eq!(Level::Other(7).oer_bytes().unwrap(), vec![0x07]);
+    assert_eq!(
+        Level::uper_from_bytes(&Level::High.uper_bytes().unwrap()).unwrap(),
+        Level::High
+    );
+    assert!(Level::Other(7).uper_bytes().is_err());
+    assert!(Level::Other(7).jer_string().is_err());
+}
+
+#[derive(Debug)]
+enum Parameters {
+    Rsa(crate::types::Null),
+    Ec(crate::types::ObjectIdentifier),
+    Unknown(crate::types::Any),
+}
+
+der_open_type! {Parameters:
+    Rsa: "1.2.840.113549.1.1.1" TYPE crate::types::Null,
+    Ec: "1.2.840.10045.2.1" TYPE crate::types::ObjectIdentifier,
+    ... Unknown
+}
+
+#[derive(Debug)]
+struct AlgorithmIdentifier {
+    pub algorithm: crate::types::ObjectIdentifier,
+    pub parameters: Option<Parameters>,
+}
+
+der_sequence! {AlgorithmIdentifier WITH(XER, JER, PER, OER):
+    algorithm: NOTAG TYPE crate::types::ObjectIdentifier,
+    parameters: NOTAG DEFINED BY algorithm; TYPE Option<Parameters>,
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Extension {
+    pub id: crate::types::ObjectIdentifier,
+    pub value: Box<dyn crate::types::OpenValue>,
+}
+
+#[cfg(feature = "std")]
+der_sequence! {Extension WITH(XER, JER, PER, OER):
+    id: NOTAG TYPE crate::types::ObjectIdentifier,
+    value: EXPLICIT TAG CONTEXT 0; DEFINED BY id; TYPE Box<dyn crate::types::OpenValue>,
+}
+
+#[test]
+fn open_types() {
+    use crate::jer::JER;
+    use crate::oer::OER;
+    use crate::per::PER;
+    use crate::types::{Any, Null, ObjectIdentifier};
+    use crate::xer::{XERDecodeable, XEREncodeable};
+
+    let rsa = AlgorithmIdentifier {
+        algorithm: "1.2.840.113549.1.1.1".parse().unwrap(),
+        parameters: Some(Parameters::Rsa(Null)),
+    };
+    let encoded = rsa.der_bytes().unwrap();
+    assert_eq!(&encoded[encoded.len() - 2..], &[0x05, 0x00][..]);
+    let decoded = AlgorithmIdentifier::der_from_bytes(encoded).unwrap();
+    assert!(matches!(decoded.parameters, Some(Parameters::Rsa(Null))));
+
+    let curve: ObjectIdentifier = "1.2.840.10045.3.1.7".parse().unwrap();
+    let ec = AlgorithmIdentifier {
+        algorithm: "1.2.840.10045.2.1".parse().unwrap(),
+        parameters: Some(Parameters::Ec(curve.clone())),
+    };
+    for decoded in [
+        AlgorithmIdentifier::der_from_bytes(ec.der_bytes().unwrap()).unwrap(),
+        AlgorithmIdentifier::xer_from_str(&ec.xer_string().unwrap()).unwrap(),
+        AlgorithmIdentifier::jer_from_str(&ec.jer_string().unwrap()).unwrap(),
+        AlgorithmIdentifier::uper_from_bytes(&ec.uper_bytes().unwrap()).unwrap(),
+        AlgorithmIdentifier::oer_from_bytes(&ec.oer_bytes().unwrap()).unwrap(),
+    ] {
+        match decoded.parameters {
+            Some(Parameters::Ec(ref id)) => assert_eq!(id, &curve),
+            _ => panic!("parameters not resolved: {:?}", decoded),
+        }
+    }
+
+    // Unknown OIDs and absent parameters are kept as they are
+    let other = AlgorithmIdentifier {
+        algorithm: "1.3.101.112".parse().unwrap(),
+        parameters: Some(Parameters::Unknown(Any::new(7).unwrap())),
+    };
+    let decoded = AlgorithmIdentifier::der_from_bytes(other.der_bytes().unwrap()).unwrap();
+    match decoded.parameters {
+        Some(Parameters::Unknown(ref any)) => assert_eq!(any.resolve::<i32>().unwrap(), 7),
+        _ => panic!("unknown parameters resolved: {:?}", decoded),
+    }
+    let none = AlgorithmIdentifier {
+        parameters: None,
+        ..other
+    };
+    let decoded = AlgorithmIdentifier::der_from_bytes(none.der_bytes().unwrap()).unwrap();
+    assert!(decoded.parameters.is_none());
+
+    // A wrong type for a known OID is an error
+    let wrong = AlgorithmIdentifier {
+        algorithm: "1.2.840.113549.1.1.1".parse().unwrap(),
+        parameters: Some(Parameters::Unknown(Any::new(7).unwrap())),
+    };
+    assert!(AlgorithmIdentifier::der_from_bytes(wrong.der_bytes().unwrap()).is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn open_type_registry() {
+    use crate::oer::OER;
+    use crate::types::{Any, ObjectIdentifier, Registry};
+
+    let known: ObjectIdentifier = "1.3.6.1.4.1.99999.1".parse().unwrap();
+    Registry::global().register::<bool>(known.clone());
+    let extension = Extension {
+        id: known,
+        value: Box::new(true),
+    };
+    let decoded = Extension::der_from_bytes(extension.der_bytes().unwrap()).unwrap();
+    assert_eq!(decoded.value.downcast_ref::<bool>(), Some(&true));
+    let decoded = Extension::oer_from_bytes(&extension.oer_bytes().unwrap()).unwrap();
+    assert_eq!(decoded.value.downcast_ref::<bool>(), Some(&true));
+
+    let unknown = Extension {
+        id: "1.3.6.1.4.1.99999.2".parse().unwrap(),
+        value: Box::new(true),
+    };
+    let decoded = Extension::der_from_bytes(unknown.der_bytes().unwrap()).unwrap();
+    assert_eq!(decoded.value.downcast_ref::<bool>(), None);
+    let any = decoded.value.downcast_ref::<Any>().unwrap();
+    assert!(any.resolve::<bool>().unwrap());
+}
+
+#[derive(Debug, PartialEq)]
+struct Chain {
+    pub value: i32,
+    pub next: Option<Box<Chain>>,
+}
+
+der_sequence! {Chain WITH(XER, JER, PER, OER):
+    value: NOTAG TYPE i32,
+    next: IMPLICIT TAG CONTEXT 0; TYPE Option<Box<Chain>>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Fixed {
+    pub id: [u8; 4],
+    pub point: [i32; 2],
+    pub pair: (bool, i32),
+}
+
+der_sequence! {Fixed WITH(XER, JER, PER, OER):
+    id: NOTAG TYPE [u8; 4],
+    point: NOTAG TYPE [i32; 2],
+    pair: NOTAG TYPE (bool, i32),
+}
+
+#[test]
+fn generic_containers() {
+    use crate::jer::JER;
+    use crate::oer::OER;
+    use crate::per::PER;
+    use crate::xer::{XERDecodeable, XEREncodeable};
+    use alloc::rc::Rc;
+    use alloc::sync::Arc;
+
+    let chain = Chain {
+        value: 1,
+        next: Some(Box::new(Chain {
+            value: 2,
+            next: None,
+        })),
+    };
+    let encoded = chain.der_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        vec![0x30, 0x08, 0x02, 0x01, 0x01, 0xA0, 0x03, 0x02, 0x01, 0x02]
+    );
+    assert_eq!(chain, Chain::der_from_bytes(encoded).unwrap());
+    assert_eq!(
+        chain,
+        Chain::xer_from_str(&chain.xer_string().unwrap()).unwrap()
+    );
+    assert_eq!(
+        chain,
+        Chain::jer_from_str(&chain.jer_string().unwrap()).unwrap()
+    );
+    assert_eq!(
+        chain,
+        Chain::uper_from_bytes(&chain.uper_bytes().unwrap()).unwrap()
+    );
+    assert_eq!(
+        chain,
+        Chain::oer_from_bytes(&chain.oer_bytes().unwrap()).unwrap()
+    );
+
+    let fixed = Fixed {
+        id: [0xDE, 0xAD, 0xBE, 0xEF],
+        point: [1, 2],
+        pair: (true, 5),
+    };
+    assert_eq!(
+        fixed.der_bytes().unwrap(),
+        vec![
+            0x30, 0x16, 0x04, 0x04, 0xDE, 0xAD, 0xBE, 0xEF, 0x30, 0x06, 0x02, 0x01, 0x01, 0x02,
+            0x01, 0x02, 0x30, 0x06, 0x01, 0x01, 0xFF, 0x02, 0x01, 0x05,
+        ]
+    );
+    assert_eq!(
+        fixed,
+        Fixed::der_from_bytes(fixed.der_bytes().unwrap()).unwrap()
+    );
+    assert_eq!(
+        fixed,
+        Fixed::xer_from_str(&fixed.xer_string().unwrap()).unwrap()
+    );
+    assert_eq!(
+        fixed.jer_string().unwrap(),
+        "{\"id\":\"DEADBEEF\",\"point\":[1,2],\"pair\":[true,5]}"
+    );
+    assert_eq!(
+        fixed,
+        Fixed::jer_from_str(&fixed.jer_string().unwrap()).unwrap()
+    );
+    assert_eq!(
+        fixed,
+        Fixed::uper_from_bytes(&fixed.uper_bytes().unwrap()).unwrap()
+    );
+    assert_eq!(
+        fixed,
+        Fixed::aper_from_bytes(&fixed.aper_bytes().unwrap()).unwrap()
+    );
+    // The fixed size octets have no length
+    assert_eq!(&fixed.oer_bytes().unwrap()[..4], &[0xDE, 0xAD, 0xBE, 0xEF]);
+    assert_eq!(
+        fixed,
+        Fixed::oer_from_bytes(&fixed.oer_bytes().unwrap()).unwrap()
+    );
+
+    // Sizes are checked on decoding
+    assert!(<[u8; 4]>::der_from_bytes(vec![0x04, 0x03, 0x01, 0x02, 0x03]).is_err());
+    assert!(<[i32; 2]>::der_from_bytes(vec![0x30, 0x03, 0x02, 0x01, 0x01]).is_err());
+    assert!(<[i32; 2]>::jer_from_str("[1,2,3]").is_err());
+    assert!(<(bool, i32)>::der_from_bytes(vec![0x30, 0x03, 0x01, 0x01, 0xFF]).is_err());
+    assert!(<(bool, i32)>::jer_from_str("[true,5,6]").is_err());
+
+    assert_eq!(Rc::new(5).der_bytes().unwrap(), 5.der_bytes().unwrap());
+    assert_eq!(
+        *Arc::<i32>::der_from_bytes(vec![0x02, 0x01, 0x05]).unwrap(),
+        5
+    );
+}
+
+#[test]
+fn dynamic_encoding() {
+    use crate::types::{Any, Null};
+
+    let values: Vec<Box<dyn DynEncode>> = vec![
+        Box::new(5),
+        Box::new("text".to_string()),
+        Box::new(Null),
+        Box::new(TestChoice::Beta(true)),
+        Box::new(Any::new(true).unwrap()),
+    ];
+    let tags: Vec<(Class, u32)> = values.iter().map(|v| v.dyn_tag().unwrap()).collect();
+    assert_eq!(
+        tags,
+        vec![
+            (Class::Universal, 2),
+            (Class::Universal, 12),
+            (Class::Universal, 5),
+            (Class::ContextSpecific, 42),
+            (Class::Universal, 1),
+        ]
+    );
+    assert_eq!(values[0].dyn_bytes().unwrap(), 5.der_bytes().unwrap());
+
+    // A list of boxed values is a SEQUENCE of them
+    let encoded = values.der_bytes().unwrap();
+    let decoded = Vec::<Any>::der_from_bytes(encoded).unwrap();
+    assert_eq!(decoded.len(), 5);
+    assert_eq!(decoded[1].resolve::<String>().unwrap(), "text");
+    assert!(decoded[4].resolve::<bool>().unwrap());
+    assert!(Vec::<Box<dyn DynEncode>>::der_from_bytes(values.der_bytes().unwrap()).is_err());
+}
+
+/// A choice that is `Copy`, so it can be written by `FixedEncoder`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Switch {
+    Level(i32),
+    On(bool),
+}
+
+der_choice! {Switch:
+    Level: EXPLICIT TAG CONTEXT 0; TYPE i32,
+    On: IMPLICIT TAG CONTEXT 1; TYPE bool,
+}
+
+#[test]
+fn fixed_encoder() {
+    use crate::types::Null;
+
+    let mut encoder = FixedEncoder::<8>::new();
+    encoder.encode(Null).unwrap();
+    encoder.encode(&[0xAB, 0xCD][..]).unwrap();
+    assert_eq!(encoder.as_bytes(), &[0x05, 0x00, 0x04, 0x02, 0xAB, 0xCD]);
+    // Neither the content nor the header fits, the encoded values are kept
+    assert!(encoder.encode(&[1, 2, 3][..]).is_err());
+    assert!(encoder.encode(&[1, 2][..]).is_err());
+    assert_eq!(encoder.len(), 6);
+    assert!(encoder.encode(false).is_err());
+    encoder.clear();
+    assert!(encoder.is_empty());
+
+    let payload = [0x55; 200];
+    let mut encoder = FixedEncoder::<203>::new();
+    encoder.encode(&payload[..]).unwrap();
+    assert_eq!(&encoder.as_bytes()[..3], &[0x04, 0x81, 0xC8]);
+    assert_eq!(encoder.as_bytes(), &(&payload[..]).der_bytes().unwrap()[..]);
+    assert!(FixedEncoder::<202>::new().encode(&payload[..]).is_err());
+
+    // A choice is written with the tag of its alternative, not a universal tag
+    for switch in [Switch::Level(5), Switch::On(true)] {
+        let mut encoder = FixedEncoder::<16>::new();
+        encoder.encode(switch).unwrap();
+        assert_eq!(encoder.as_bytes(), &switch.der_bytes().unwrap()[..]);
+    }
+    assert!(FixedEncoder::<4>::new().encode(Switch::Level(5)).is_err());
+}
+
+#[test]
+fn partial_decoding() {
+    let bytes = "x".repeat(300).der_bytes().unwrap();
+    assert_eq!(&bytes[..4], &[0x0C, 0x82, 0x01, 0x2C]);
+    // Missing tag, length or content bytes
+    assert_eq!(
+        String::der_decode_partial(&[]).unwrap(),
+        Partial::NeedMore(1)
+    );
+    assert_eq!(
+        String::der_decode_partial(&bytes[..1]).unwrap(),
+        Partial::NeedMore(1)
+    );
+    assert_eq!(
+        String::der_decode_partial(&bytes[..2]).unwrap(),
+        Partial::NeedMore(2)
+    );
+    assert_eq!(
+        String::der_decode_partial(&bytes[..10]).unwrap(),
+        Partial::NeedMore(294)
+    );
+    assert_eq!(
+        String::der_decode_partial(&[0x1F, 0x81]).unwrap(),
+        Partial::NeedMore(1)
+    );
+    assert_eq!(
+        String::der_decode_partial(&bytes).unwrap(),
+        Partial::Done("x".repeat(300), 304)
+    );
+    // Invalid input is still an error
+    assert!(String::der_decode_partial(&[0x0C, 0x89]).is_err());
+    assert!(bool::der_decode_partial(&[0x0C, 0x00]).is_err());
+
+    let mut decoder = StreamDecoder::new();
+    let mut stream = vec![];
+    stream.extend_from_slice(&bytes);
+    stream.extend_from_slice(&vec![1, 2].der_bytes().unwrap());
+    let mut strings = vec![];
+    let mut lists = vec![];
+    for chunk in stream.chunks(7) {
+        decoder.feed(chunk);
+        if strings.is_empty() {
+            if let Some(s) = decoder.decode::<String>().unwrap().done() {
+                strings.push(s);
+            }
+        }
+        if !strings.is_empty() {
+            if let Some(l) = decoder.decode::<Vec<i32>>().unwrap().done() {
+                lists.push(l);
+            }
+        }
+    }
+    assert_eq!(strings, vec!["x".repeat(300)]);
+    assert_eq!(lists, vec![vec![1, 2]]);
+    assert!(decoder.buffered().is_empty());
+}
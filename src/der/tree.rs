@@ -0,0 +1,335 @@
+use crate::io;
+use crate::prelude::*;
+
+use super::*;
+
+/// Maximum nesting depth of constructed elements accepted when parsing untrusted input
+pub const MAX_NESTING_DEPTH: usize = 128;
+
+/// Tag and length of an element
+pub(crate) struct Header {
+    /// Number of tag and length bytes
+    pub length: usize,
+    pub tag: u32,
+    pub class: Class,
+    pub content_type: ContentType,
+    /// `None` for BER indefinite length
+    pub content_length: Option<usize>,
+}
+
+/// Decode the tag and length bytes at the start of `bytes`
+pub(crate) fn decode_header(bytes: &[u8]) -> io::Result<Header> {
+    let mut stream = io::Cursor::new(bytes);
+    let (tag_length, tag, class, content_type) = der_decode_tag_bytes(&mut stream)?;
+    let indefinite = bytes.get(tag_length) == Some(&0x80);
+    let (length_length, content_length) = der_decode_length_bytes(&mut stream)?;
+    Ok(Header {
+        length: tag_length + length_length,
+        tag,
+        class,
+        content_type,
+        content_length: if indefinite {
+            None
+        } else {
+            Some(content_length)
+        },
+    })
+}
+
+/// Node of a decoded element tree
+///
+/// A tree is created by recursively splitting the content octets of constructed elements into
+/// their child elements. This allows inspecting encoded data without knowing its schema.
+///
+/// # Example
+/// ```
+/// # use eagre_asn1::der::*;
+/// let bytes = vec![vec![1, 2], vec![3]].der_bytes().unwrap();
+/// let tree = Node::parse(&bytes).unwrap();
+///
+/// let node = tree.get("1/0").unwrap();
+/// assert_eq!(node.offset, 12);
+/// assert_eq!(3, i32::der_from_intermediate(node.intermediate.clone()).unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Node {
+    /// Offset of the first tag byte, counted from the start of the parsed input
+    pub offset: usize,
+    /// Number of tag and length bytes
+    pub header_length: usize,
+    /// The element itself
+    pub intermediate: Intermediate,
+    /// Child elements, always empty for primitive elements
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    /// Parse exactly one element and all of its children
+    pub fn parse(bytes: &[u8]) -> io::Result<Node> {
+        let node = Node::parse_one(bytes, 0, 0)?;
+        if node.encoded_length() != bytes.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "trailing data after element",
+            ));
+        }
+        Ok(node)
+    }
+
+    /// Parse a concatenation of elements
+    pub fn parse_all(bytes: &[u8]) -> io::Result<Vec<Node>> {
+        Node::parse_list(bytes, 0, 0)
+    }
+
+    fn parse_list(bytes: &[u8], offset: usize, depth: usize) -> io::Result<Vec<Node>> {
+        let mut nodes = vec![];
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let node = Node::parse_one(&bytes[pos..], offset + pos, depth)?;
+            pos += node.encoded_length();
+            nodes.push(node);
+        }
+        Ok(nodes)
+    }
+
+    fn parse_one(bytes: &[u8], offset: usize, depth: usize) -> io::Result<Node> {
+        let header = decode_header(bytes)?;
+        let length = match header.content_length {
+            Some(length) => length,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "indefinite length is not supported",
+                ))
+            }
+        };
+        let header_length = header.length;
+        if bytes.len() - header_length < length {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "element content exceeds available data",
+            ));
+        }
+        let content = &bytes[header_length..header_length + length];
+        let children = match header.content_type {
+            ContentType::Constructed if depth >= MAX_NESTING_DEPTH => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("offset {}: elements are nested too deeply", offset),
+                ))
+            }
+            ContentType::Constructed => {
+                Node::parse_list(content, offset + header_length, depth + 1)?
+            }
+            ContentType::Primitive => vec![],
+        };
+        Ok(Node {
+            offset,
+            header_length,
+            intermediate: Intermediate::new(header.class, header.content_type, header.tag)
+                .with_content(content.to_vec()),
+            children,
+        })
+    }
+
+    /// Total number of bytes this element occupies ( tag bytes + length bytes + content bytes )
+    pub fn encoded_length(&self) -> usize {
+        self.header_length + self.intermediate.content.len()
+    }
+
+    /// Look up a descendant of this node
+    ///
+    /// The path is a list of child indices separated by `/`, so `"0/2/1"` is the second child of
+    /// the third child of the first child. An empty path refers to the node itself.
+    pub fn get(&self, path: &str) -> Option<&Node> {
+        let mut node = self;
+        for index in path.split('/').filter(|s| !s.is_empty()) {
+            node = node.children.get(index.parse::<usize>().ok()?)?;
+        }
+        Some(node)
+    }
+
+    /// Check that this node and all of its children are strict DER
+    ///
+    /// This verifies minimal tag and length encoding, the primitive/constructed flag of universal
+    /// types and the content of booleans, integers, nulls, bit strings, object identifiers and
+    /// times. The ordering of SET components is not checked.
+    pub fn check_der(&self) -> io::Result<()> {
+        let i = &self.intermediate;
+        let error = |msg: &str| {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("offset {}: {}", self.offset, msg),
+            ))
+        };
+
+        let mut header = vec![];
+        der_encode_tag_bytes(i.tag, i.class, i.content_type, &mut header)?;
+        der_encode_length_bytes(i.content.len(), &mut header)?;
+        if header.len() != self.header_length {
+            return error("tag or length is not minimally encoded");
+        }
+
+        if i.class == Class::Universal {
+            let constructed = match UniversalTag::from_tag(i.tag) {
+                Some(UniversalTag::Sequence) | Some(UniversalTag::Set) => true,
+                Some(UniversalTag::External) | Some(UniversalTag::EmbeddedPDV) => true,
+                Some(UniversalTag::CharacterString) => true,
+                Some(UniversalTag::EOC) | None => return error("invalid universal tag"),
+                Some(_) => false,
+            };
+            if constructed != (i.content_type == ContentType::Constructed) {
+                return error("wrong primitive/constructed encoding for universal type");
+            }
+            let c = &i.content;
+            let problem = match UniversalTag::from_tag(i.tag) {
+                Some(UniversalTag::Boolean) if c.len() != 1 || (c[0] != 0x00 && c[0] != 0xFF) => {
+                    Some("boolean must be a single 0x00 or 0xFF byte")
+                }
+                Some(UniversalTag::Integer) | Some(UniversalTag::Enumerated) => {
+                    if c.is_empty() {
+                        Some("integer without content")
+                    } else if c.len() > 1
+                        && ((c[0] == 0x00 && c[1] & 0x80 == 0)
+                            || (c[0] == 0xFF && c[1] & 0x80 != 0))
+                    {
+                        Some("integer is not minimally encoded")
+                    } else {
+                        None
+                    }
+                }
+                Some(UniversalTag::Null) if !c.is_empty() => Some("null with content"),
+                Some(UniversalTag::BitString) => match c.split_first() {
+                    None => Some("bit string without unused bits byte"),
+                    Some((&unused, bits)) if unused > 7 || (bits.is_empty() && unused != 0) => {
+                        Some("invalid number of unused bits")
+                    }
+                    Some((&unused, bits))
+                        if bits.last().unwrap_or(&0) & ((1 << unused) - 1) != 0 =>
+                    {
+                        Some("unused bits of bit string are not zero")
+                    }
+                    Some(_) => None,
+                },
+                Some(UniversalTag::ObjectIdentifier) | Some(UniversalTag::RelativeOID) => {
+                    if c.is_empty() || c[c.len() - 1] & 0x80 != 0 {
+                        Some("truncated object identifier")
+                    } else if c
+                        .iter()
+                        .zip(Some(&0).into_iter().chain(c.iter()))
+                        .any(|(byte, previous)| *byte == 0x80 && previous & 0x80 == 0)
+                    {
+                        Some("object identifier arc is not minimally encoded")
+                    } else {
+                        None
+                    }
+                }
+                Some(UniversalTag::UTCTime) | Some(UniversalTag::GeneralizedTime)
+                    if c.last() != Some(&b'Z') =>
+                {
+                    Some("time is not in UTC")
+                }
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                return error(problem);
+            }
+        }
+
+        for child in self.children.iter() {
+            child.check_der()?;
+        }
+        Ok(())
+    }
+}
+
+impl Intermediate {
+    /// Decode the content octets of a constructed Intermediate into its child elements
+    pub fn children(&self) -> io::Result<Vec<Intermediate>> {
+        if self.content_type != ContentType::Constructed {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "primitive element does not have children",
+            ));
+        }
+        Ok(Node::parse_all(&self.content)?
+            .into_iter()
+            .map(|node| node.intermediate)
+            .collect())
+    }
+
+    /// Recursively parse this Intermediate into a tree
+    ///
+    /// Offsets are counted from the first tag byte of this Intermediate.
+    pub fn tree(&self) -> io::Result<Node> {
+        let mut buf = vec![];
+        self.encode(&mut buf)?;
+        Node::parse(&buf)
+    }
+}
+
+#[test]
+fn tree_offsets_and_paths() {
+    let data = vec![
+        vec!["a".to_string()],
+        vec![],
+        vec!["b".to_string(), "cd".to_string()],
+    ];
+    let tree = Node::parse(&data.der_bytes().unwrap()).unwrap();
+    assert_eq!(tree.children.len(), 3);
+    assert_eq!(tree.get("1").unwrap().children.len(), 0);
+    let node = tree.get("2/1").unwrap();
+    assert_eq!(node.offset, 14);
+    assert_eq!(node.header_length, 2);
+    assert_eq!(node.intermediate.content, b"cd");
+    assert!(tree.get("2/2").is_none());
+    assert!(tree.get("0/0/0").is_none());
+}
+
+#[test]
+fn check_strict_der() {
+    let valid = vec![-129, 0, 127, 128, 65535].der_bytes().unwrap();
+    assert!(Node::parse(&valid).unwrap().check_der().is_ok());
+    for invalid in [
+        vec![0x02, 0x02, 0x00, 0x7F],
+        vec![0x02, 0x81, 0x01, 0x00],
+        vec![0x01, 0x01, 0x01],
+        vec![0x22, 0x00],
+        vec![0x03, 0x02, 0x01, 0x01],
+        vec![0x30, 0x03, 0x06, 0x01, 0x80],
+    ] {
+        assert!(Node::parse(&invalid).unwrap().check_der().is_err());
+    }
+}
+
+#[test]
+fn tree_rejects_truncated_content() {
+    assert!(Node::parse(&[0x30, 0x03, 0x02, 0x01]).is_err());
+    assert!(Node::parse(&[0x30, 0x03, 0x02, 0x02, 0x01]).is_err());
+}
+
+#[test]
+fn nesting_limit() {
+    // `depth + 1` nested sequences
+    let nested = |depth: usize| {
+        let mut bytes = vec![0x30, 0x00];
+        for _ in 0..depth {
+            let mut outer = vec![0x30];
+            der_encode_length_bytes(bytes.len(), &mut outer).unwrap();
+            outer.extend_from_slice(&bytes);
+            bytes = outer;
+        }
+        bytes
+    };
+    assert!(Node::parse(&nested(MAX_NESTING_DEPTH - 1)).is_ok());
+    let e = Node::parse(&nested(MAX_NESTING_DEPTH)).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    // Far deeper input is rejected instead of overflowing the stack
+    let levels: u32 = 100_000;
+    let mut deep = vec![];
+    for level in 1..=levels {
+        deep.extend_from_slice(&[0x30, 0x84]);
+        deep.extend_from_slice(&((levels - level) * 6).to_be_bytes());
+    }
+    assert!(Node::parse_all(&deep).is_err());
+}
@@ -0,0 +1,425 @@
+//! Readers and writers used by the encoders and decoders
+//!
+//! With the `std` feature these are the types and traits of `std::io`. Without it, this module
+//! provides minimal replacements with the same names working on byte slices and `Vec<u8>`, so
+//! the crate can be used with just `core` and `alloc`.
+
+#[cfg(feature = "std")]
+pub use std::io::{Cursor, Error, ErrorKind, Read, Result, Take, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::bare::*;
+
+/// Single octet reads, used by the tag and length decoders
+pub(crate) trait ReadOctet: Read {
+    fn read_u8(&mut self) -> Result<u8> {
+        let mut octet = [0];
+        self.read_exact(&mut octet)?;
+        Ok(octet[0])
+    }
+}
+
+impl<R: Read + ?Sized> ReadOctet for R {}
+
+/// Single octet writes, used by the tag and length encoders
+pub(crate) trait WriteOctet: Write {
+    fn write_u8(&mut self, octet: u8) -> Result<()> {
+        self.write_all(&[octet])
+    }
+}
+
+impl<W: Write + ?Sized> WriteOctet for W {}
+
+#[cfg(not(feature = "std"))]
+mod bare {
+    use alloc::boxed::Box;
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::cmp;
+    use core::error;
+    use core::fmt;
+
+    /// Result of reading or writing
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Kind of an `Error`
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum ErrorKind {
+        /// Something was not found
+        NotFound,
+        /// An input parameter or the decoded input was invalid
+        InvalidInput,
+        /// Data read was not valid, like a string that is not UTF-8
+        InvalidData,
+        /// The input ended early
+        UnexpectedEof,
+        /// The output is full
+        WriteZero,
+        /// Any other error
+        Other,
+    }
+
+    impl ErrorKind {
+        fn description(self) -> &'static str {
+            match self {
+                ErrorKind::NotFound => "entity not found",
+                ErrorKind::InvalidInput => "invalid input parameter",
+                ErrorKind::InvalidData => "invalid data",
+                ErrorKind::UnexpectedEof => "unexpected end of file",
+                ErrorKind::WriteZero => "write zero",
+                ErrorKind::Other => "other error",
+            }
+        }
+    }
+
+    /// Error of reading, writing, encoding or decoding
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+        error: Box<dyn error::Error + Send + Sync>,
+    }
+
+    impl Error {
+        /// Create an Error of `kind` from a message or another error
+        pub fn new<E: Into<Box<dyn error::Error + Send + Sync>>>(
+            kind: ErrorKind,
+            error: E,
+        ) -> Error {
+            Error {
+                kind,
+                error: error.into(),
+            }
+        }
+
+        /// Return the kind of this Error
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+
+        /// Return the wrapped error
+        pub fn get_ref(&self) -> Option<&(dyn error::Error + Send + Sync + 'static)> {
+            Some(&*self.error)
+        }
+
+        /// Unwrap the wrapped error
+        pub fn into_inner(self) -> Option<Box<dyn error::Error + Send + Sync>> {
+            Some(self.error)
+        }
+    }
+
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Error {
+            Error::new(kind, kind.description())
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            self.error.fmt(f)
+        }
+    }
+
+    impl error::Error for Error {
+        fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+            self.error.source()
+        }
+    }
+
+    /// Source of bytes
+    pub trait Read {
+        /// Read some bytes into `buf`, returning how many, `0` at the end of the input
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        /// Fill `buf` completely
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::UnexpectedEof,
+                            "failed to fill whole buffer",
+                        ))
+                    }
+                    n => buf = &mut buf[n..],
+                }
+            }
+            Ok(())
+        }
+
+        /// Read everything up to the end of the input
+        fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
+            let mut chunk = [0; 64];
+            let mut total = 0;
+            loop {
+                match self.read(&mut chunk)? {
+                    0 => return Ok(total),
+                    n => {
+                        buf.extend_from_slice(&chunk[..n]);
+                        total += n;
+                    }
+                }
+            }
+        }
+
+        /// Read everything up to the end of the input, which has to be UTF-8
+        fn read_to_string(&mut self, buf: &mut String) -> Result<usize> {
+            let mut bytes = vec![];
+            let n = self.read_to_end(&mut bytes)?;
+            let text = String::from_utf8(bytes).map_err(|_| {
+                Error::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8")
+            })?;
+            buf.push_str(&text);
+            Ok(n)
+        }
+
+        /// Read at most `limit` bytes
+        fn take(self, limit: u64) -> Take<Self>
+        where
+            Self: Sized,
+        {
+            Take { inner: self, limit }
+        }
+
+        /// Borrow this reader
+        fn by_ref(&mut self) -> &mut Self
+        where
+            Self: Sized,
+        {
+            self
+        }
+    }
+
+    /// Sink of bytes
+    pub trait Write {
+        /// Write some bytes of `buf`, returning how many
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        /// Flush buffered bytes
+        fn flush(&mut self) -> Result<()>;
+
+        /// Write all of `buf`
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        ))
+                    }
+                    n => buf = &buf[n..],
+                }
+            }
+            Ok(())
+        }
+
+        /// Write formatted text, used by `write!`
+        fn write_fmt(&mut self, args: fmt::Arguments) -> Result<()> {
+            struct Adapter<'a, W: ?Sized> {
+                inner: &'a mut W,
+                error: Option<Error>,
+            }
+
+            impl<W: Write + ?Sized> fmt::Write for Adapter<'_, W> {
+                fn write_str(&mut self, s: &str) -> fmt::Result {
+                    self.inner.write_all(s.as_bytes()).map_err(|e| {
+                        self.error = Some(e);
+                        fmt::Error
+                    })
+                }
+            }
+
+            let mut adapter = Adapter {
+                inner: self,
+                error: None,
+            };
+            fmt::write(&mut adapter, args).map_err(|_| {
+                adapter
+                    .error
+                    .take()
+                    .unwrap_or_else(|| Error::new(ErrorKind::Other, "formatter error"))
+            })
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let n = cmp::min(buf.len(), self.len());
+            buf[..n].copy_from_slice(&self[..n]);
+            *self = &self[n..];
+            Ok(n)
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for Box<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Writes into the slice and advances it, like `std::io` does
+    impl Write for &mut [u8] {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            let n = cmp::min(buf.len(), self.len());
+            let (head, tail) = core::mem::take(self).split_at_mut(n);
+            head.copy_from_slice(&buf[..n]);
+            *self = tail;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for Box<W> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+    }
+
+    /// Reader and writer over an in-memory buffer, keeping track of the position
+    #[derive(Debug, Default, Clone, PartialEq, Eq)]
+    pub struct Cursor<T> {
+        inner: T,
+        pos: u64,
+    }
+
+    impl<T> Cursor<T> {
+        /// Create a Cursor at the start of `inner`
+        pub fn new(inner: T) -> Cursor<T> {
+            Cursor { inner, pos: 0 }
+        }
+
+        /// Return the buffer
+        pub fn into_inner(self) -> T {
+            self.inner
+        }
+
+        /// Borrow the buffer
+        pub fn get_ref(&self) -> &T {
+            &self.inner
+        }
+
+        /// Borrow the buffer mutably
+        pub fn get_mut(&mut self) -> &mut T {
+            &mut self.inner
+        }
+
+        /// Return the position
+        pub fn position(&self) -> u64 {
+            self.pos
+        }
+
+        /// Set the position
+        pub fn set_position(&mut self, pos: u64) {
+            self.pos = pos;
+        }
+    }
+
+    impl<T: AsRef<[u8]>> Read for Cursor<T> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let data = self.inner.as_ref();
+            let start = cmp::min(self.pos, data.len() as u64) as usize;
+            let n = (&data[start..]).read(buf)?;
+            self.pos += n as u64;
+            Ok(n)
+        }
+    }
+
+    impl Write for Cursor<Vec<u8>> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            let pos = self.pos as usize;
+            if self.inner.len() < pos {
+                self.inner.resize(pos, 0);
+            }
+            let overlap = cmp::min(buf.len(), self.inner.len() - pos);
+            self.inner[pos..pos + overlap].copy_from_slice(&buf[..overlap]);
+            self.inner.extend_from_slice(&buf[overlap..]);
+            self.pos += buf.len() as u64;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Write for Cursor<&mut [u8]> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            let start = cmp::min(self.pos, self.inner.len() as u64) as usize;
+            let n = (&mut self.inner[start..]).write(buf)?;
+            self.pos += n as u64;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Reader of at most `limit` bytes of another reader
+    #[derive(Debug)]
+    pub struct Take<R> {
+        inner: R,
+        limit: u64,
+    }
+
+    impl<R> Take<R> {
+        /// Number of bytes that can still be read
+        pub fn limit(&self) -> u64 {
+            self.limit
+        }
+
+        /// Return the underlying reader
+        pub fn into_inner(self) -> R {
+            self.inner
+        }
+
+        /// Borrow the underlying reader
+        pub fn get_ref(&self) -> &R {
+            &self.inner
+        }
+    }
+
+    impl<R: Read> Read for Take<R> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let max = cmp::min(buf.len() as u64, self.limit) as usize;
+            let n = self.inner.read(&mut buf[..max])?;
+            self.limit -= n as u64;
+            Ok(n)
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use crate::der::MAX_NESTING_DEPTH;
+use crate::io;
+use crate::prelude::*;
+use core::fmt;
+
+/// JSON Value
+///
+/// Intermediate representation of JER encoded values. Numbers are kept as their literal text so
+/// that no precision is lost, object members keep their order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// Number literal
+    Number(String),
+    /// String
+    String(String),
+    /// Array
+    Array(Vec<Value>),
+    /// Object
+    Object(Vec<(String, Value)>),
+}
+
+fn type_error(expected: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("expected JSON {}", expected),
+    )
+}
+
+impl Value {
+    /// Return the string if this is a string value
+    pub fn as_str(&self) -> io::Result<&str> {
+        match self {
+            Value::String(s) => Ok(s),
+            _ => Err(type_error("string")),
+        }
+    }
+
+    /// Return the elements if this is an array value
+    pub fn as_array(&self) -> io::Result<&[Value]> {
+        match self {
+            Value::Array(a) => Ok(a),
+            _ => Err(type_error("array")),
+        }
+    }
+
+    /// Return the members if this is an object value
+    pub fn as_object(&self) -> io::Result<&[(String, Value)]> {
+        match self {
+            Value::Object(o) => Ok(o),
+            _ => Err(type_error("object")),
+        }
+    }
+
+    /// Return the number literal if this is a number value
+    pub fn as_number(&self) -> io::Result<&str> {
+        match self {
+            Value::Number(n) => Ok(n),
+            _ => Err(type_error("number")),
+        }
+    }
+
+    /// Look up an object member
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(o) => o.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Parse a JSON text
+    pub fn parse(text: &str) -> io::Result<Value> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != text.len() {
+            return Err(parser.error("content after JSON value"));
+        }
+        Ok(value)
+    }
+}
+
+/// Writes compact JSON
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write_string(f, s),
+            Value::Array(a) => {
+                write!(f, "[")?;
+                for (i, v) in a.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", v)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(o) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in o.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, k)?;
+                    write!(f, ":{}", v)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            '\u{0}'..='\u{1f}' => write!(f, "\\u{:04x}", c as u32)?,
+            _ => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+    /// Number of currently open arrays and objects
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("JSON syntax error at byte {}: {}", self.pos, msg),
+        )
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.pos).cloned()
+    }
+
+    fn expect(&mut self, s: &str) -> io::Result<()> {
+        if !self.text[self.pos..].starts_with(s.as_bytes()) {
+            return Err(self.error(&format!("expected \"{}\"", s)));
+        }
+        self.pos += s.len();
+        Ok(())
+    }
+
+    fn value(&mut self) -> io::Result<Value> {
+        self.skip_whitespace();
+        if let Some(b'[') | Some(b'{') = self.peek() {
+            if self.depth >= MAX_NESTING_DEPTH {
+                return Err(self.error("arrays and objects are nested too deeply"));
+            }
+            self.depth += 1;
+            let value = self.compound();
+            self.depth -= 1;
+            return value;
+        }
+        match self.peek() {
+            Some(b'n') => self.expect("null").map(|_| Value::Null),
+            Some(b't') => self.expect("true").map(|_| Value::Bool(true)),
+            Some(b'f') => self.expect("false").map(|_| Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            _ => Err(self.error("expected value")),
+        }
+    }
+
+    /// Parse an array or object
+    fn compound(&mut self) -> io::Result<Value> {
+        match self.peek() {
+            Some(b'[') => {
+                self.pos += 1;
+                let mut array = vec![];
+                self.skip_whitespace();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Value::Array(array));
+                }
+                loop {
+                    array.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Value::Array(array));
+                        }
+                        _ => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut object = vec![];
+                self.skip_whitespace();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Value::Object(object));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.skip_whitespace();
+                    self.expect(":")?;
+                    object.push((key, self.value()?));
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Value::Object(object));
+                        }
+                        _ => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            _ => Err(self.error("expected '[' or '{'")),
+        }
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn number(&mut self) -> io::Result<Value> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        let first = self.peek();
+        let digits = self.digits();
+        if digits == 0 || (digits > 1 && first == Some(b'0')) {
+            return Err(self.error("invalid number"));
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if self.digits() == 0 {
+                return Err(self.error("invalid number"));
+            }
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if self.digits() == 0 {
+                return Err(self.error("invalid number"));
+            }
+        }
+        // Only ascii was consumed
+        let literal = ::core::str::from_utf8(&self.text[start..self.pos]).unwrap();
+        Ok(Value::Number(literal.to_string()))
+    }
+
+    fn hex4(&mut self) -> io::Result<u32> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .filter(|d| d.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|d| ::core::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        self.expect("\"")?;
+        let mut bytes = vec![];
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let c = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.pos += 1;
+                            let mut code = self.hex4()?;
+                            if (0xD800..0xDC00).contains(&code) {
+                                self.expect("\\u")?;
+                                let low = self.hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("invalid surrogate pair"));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            self.pos -= 1;
+                            ::core::char::from_u32(code)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.pos += 1;
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                Some(b) if b < 0x20 => return Err(self.error("control character in string")),
+                Some(b) => {
+                    self.pos += 1;
+                    bytes.push(b);
+                }
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| self.error("invalid utf8 in string"))
+    }
+}
+
+#[test]
+fn parse_and_write() {
+    let text = " {\"a\" : [1, -2.5e3, true, null], \"b\\u00e4\\ud83d\\ude00\": \"x\\\"\\n\"} ";
+    let value = Value::parse(text).unwrap();
+    assert_eq!(
+        value,
+        Value::Object(vec![
+            (
+                "a".to_string(),
+                Value::Array(vec![
+                    Value::Number("1".to_string()),
+                    Value::Number("-2.5e3".to_string()),
+                    Value::Bool(true),
+                    Value::Null,
+                ])
+            ),
+            (
+                "b\u{e4}\u{1F600}".to_string(),
+                Value::String("x\"\n".to_string())
+            ),
+        ])
+    );
+    assert_eq!(
+        value.to_string(),
+        "{\"a\":[1,-2.5e3,true,null],\"b\u{e4}\u{1F600}\":\"x\\\"\\n\"}"
+    );
+}
+
+#[test]
+fn parse_rejects_malformed() {
+    for text in [
+        "",
+        "[1,]",
+        "{\"a\" 1}",
+        "01",
+        "\"\\x\"",
+        "[1] 2",
+        "tru",
+        "\"\\u+041\"",
+    ] {
+        assert!(Value::parse(text).is_err(), "{}", text);
+    }
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    assert!(Value::parse(&nested(MAX_NESTING_DEPTH)).is_ok());
+    let e = Value::parse(&nested(MAX_NESTING_DEPTH + 1)).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    assert!(Value::parse(&"[{\"a\":".repeat(1_000_000)).is_err());
+}
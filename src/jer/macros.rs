@@ -0,0 +1,119 @@
+/// JER implementation for sequences, used by `der_sequence!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! jer_sequence {
+    ($struct_name:ident $(<$lt:lifetime>)? [$extensible:tt $(, $unknown:ident)?] : $($field_name:ident $(BY $id:ident)? = [$($c:tt)*]),+) => {
+        impl $crate::jer::JER for $struct_name $(<$lt>)? {
+            fn jer_value(&self) -> $crate::io::Result<$crate::jer::Value> {
+                let mut members = $crate::prelude::vec![];
+                $(
+                    if $crate::jer::JER::jer_present(&self.$field_name) {
+                        $crate::der_constraints!(@check &self.$field_name, stringify!($field_name), stringify!($struct_name); $($c)*);
+                        members.push(($crate::prelude::String::from(stringify!($field_name)), $crate::jer::JER::jer_value(&self.$field_name)?));
+                    }
+                )+
+                Ok($crate::jer::Value::Object(members))
+            }
+
+            fn jer_from_value(v: &$crate::jer::Value) -> $crate::io::Result<Self> {
+                // Extension additions of a newer version are skipped
+                let extensible: bool = $extensible;
+                let members = v.as_object()?;
+                for (i, (key, _)) in members.iter().enumerate() {
+                    if members[..i].iter().any(|(k, _)| k == key) {
+                        return Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                            $crate::prelude::format!("duplicate member \"{}\" in {}", key, stringify!($struct_name))));
+                    }
+                    if !extensible && ![$(stringify!($field_name)),+].contains(&key.as_str()) {
+                        return Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                            $crate::prelude::format!("unknown member \"{}\" in {}", key, stringify!($struct_name))));
+                    }
+                }
+                $(
+                    let $field_name = match (v.get(stringify!($field_name)), $crate::jer::JER::jer_absent()) {
+                        (Some(field), _) => $crate::jer::JER::jer_from_value(field)?,
+                        (None, Some(absent)) => absent,
+                        (None, None) => return Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                            $crate::prelude::format!("missing member \"{}\" in {}", stringify!($field_name), stringify!($struct_name)))),
+                    };
+                    $crate::der_constraints!(@check &$field_name, stringify!($field_name), stringify!($struct_name); $($c)*);
+                    $(let $field_name = $crate::types::OpenType::open_resolve($field_name, &$id)?;)?
+                )+
+                Ok($struct_name {
+                    $(
+                        $field_name: $field_name,
+                    )+
+                    $(
+                        $unknown: $crate::prelude::Vec::new(),
+                    )?
+                })
+            }
+        }
+    };
+}
+
+/// JER implementation for choices, used by `der_choice!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! jer_choice {
+    ($choice_name:ident [$extensible:tt $(, $unknown:ident)?] : $($variant_name:ident [$($shape:tt)*] = [$($c:tt)*]),+) => {
+        impl $crate::jer::JER for $choice_name {
+            fn jer_value(&self) -> $crate::io::Result<$crate::jer::Value> {
+                Ok($crate::jer::Value::Object($crate::prelude::vec![match self {
+                    $($crate::der_choice!(@pattern $choice_name $variant_name [$($shape)*] payload) => {
+                        let val = $crate::der_choice!(@payload $variant_name [$($shape)*] payload);
+                        $crate::der_constraints!(@check val, stringify!($variant_name), stringify!($choice_name); $($c)*);
+                        ($crate::prelude::String::from(stringify!($variant_name)), $crate::jer::JER::jer_value(val)?)
+                    },)+
+                    $(&$choice_name::$unknown(_) => return Err($crate::der::unknown_alternative(stringify!($choice_name))),)?
+                }]))
+            }
+
+            fn jer_from_value(v: &$crate::jer::Value) -> $crate::io::Result<Self> {
+                let (key, value) = match v.as_object()? {
+                    [member] => member,
+                    _ => return Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                        $crate::prelude::format!("expected exactly one alternative in {}", stringify!($choice_name)))),
+                };
+                $(
+                    if key == stringify!($variant_name) {
+                        let value = $crate::jer::JER::jer_from_value(value)?;
+                        $crate::der_constraints!(@check &value, stringify!($variant_name), stringify!($choice_name); $($c)*);
+                        return Ok($crate::der_choice!(@construct $choice_name $variant_name [$($shape)*] value));
+                    }
+                )+
+                Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                    $crate::prelude::format!("unknown alternative \"{}\" in {}", key, stringify!($choice_name))))
+            }
+        }
+    };
+}
+
+/// JER implementation for enumerations, used by `der_enumerated!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! jer_enumerated {
+    ($enum_name:ident [$extensible:tt $(, $unknown:ident)? $(; $other:ident)?], $($enum_variant:ident = $value:expr),+) => {
+        impl $crate::jer::JER for $enum_name {
+            fn jer_value(&self) -> $crate::io::Result<$crate::jer::Value> {
+                Ok($crate::jer::Value::String($crate::prelude::String::from(match self {
+                    $(&$enum_name::$enum_variant => stringify!($enum_variant),)+
+                    $(&$enum_name::$unknown => return Err($crate::der::unknown_value(stringify!($enum_name))),)?
+                    $(&$enum_name::$other(_) => return Err($crate::der::unknown_value(stringify!($enum_name))),)?
+                })))
+            }
+
+            fn jer_from_value(v: &$crate::jer::Value) -> $crate::io::Result<Self> {
+                let name = v.as_str()?;
+                $(
+                    if name == stringify!($enum_variant) {
+                        return Ok($enum_name::$enum_variant);
+                    }
+                )+
+                let unknown: Option<Self> = None $(.or(Some($enum_name::$unknown)))?;
+                unknown.ok_or_else(|| $crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                    $crate::prelude::format!("unknown enumeration value \"{}\" in {}", name, stringify!($enum_name))))
+            }
+        }
+    };
+}
@@ -0,0 +1,242 @@
+use crate::der::{into_array, tuple_mismatch, Component};
+use crate::io::{self, Read, Write};
+use crate::prelude::*;
+use crate::xer::{hex_decode, hex_encode};
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+
+/// JSON Value type, writer and parser
+pub mod json;
+#[doc(hidden)]
+#[macro_use]
+pub mod macros;
+
+#[cfg(test)]
+mod test;
+
+pub use self::json::Value;
+
+/// The trait for JER encoding
+///
+/// Values are converted to and from a JSON `Value` following X.697. Types implementing `DER`
+/// through `der_sequence!`, `der_choice!` or `der_enumerated!` implement this trait when `JER` is
+/// listed in `WITH(...)` after the type name: sequences become objects keyed by field name, choices become objects with a single member
+/// named after the alternative and enumerations become the identifier as a string.
+///
+/// # Example
+/// ```
+/// # #[macro_use]
+/// # extern crate eagre_asn1;
+/// use eagre_asn1::jer::JER;
+///
+/// # #[derive(Debug, PartialEq)]
+/// struct User {
+///     pub name: String,
+///     pub admin: bool,
+/// }
+///
+/// der_sequence! {
+///     User WITH(JER):
+///         name:  NOTAG TYPE String,
+///         admin: NOTAG TYPE bool,
+/// }
+///
+/// # fn main() {
+/// let user = User { name: "Rahix".to_string(), admin: true };
+/// let encoded = user.jer_string().unwrap();
+/// assert_eq!(encoded, "{\"name\":\"Rahix\",\"admin\":true}");
+/// assert_eq!(user, User::jer_from_str(&encoded).unwrap());
+/// # }
+/// ```
+pub trait JER: Sized {
+    /// Convert this value into a JSON value
+    fn jer_value(&self) -> io::Result<Value>;
+    /// Create object from a JSON value
+    fn jer_from_value(v: &Value) -> io::Result<Self>;
+    /// Value to use if this type is missing from a sequence, `Some` only for optional types
+    fn jer_absent() -> Option<Self> {
+        None
+    }
+    /// Return whether this value is encoded at all, `false` only for absent optional values
+    fn jer_present(&self) -> bool {
+        true
+    }
+    /// Return the JER encoding as a string
+    fn jer_string(&self) -> io::Result<String> {
+        Ok(self.jer_value()?.to_string())
+    }
+    /// Encode into stream
+    fn jer_encode(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(self.jer_string()?.as_bytes())
+    }
+    /// Create object from a string
+    fn jer_from_str(text: &str) -> io::Result<Self> {
+        Self::jer_from_value(&Value::parse(text)?)
+    }
+    /// Create object from stream
+    fn jer_decode(r: &mut dyn Read) -> io::Result<Self> {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+        Self::jer_from_str(&text)
+    }
+}
+
+impl JER for bool {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::Bool(*self))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        match v {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "expected JSON boolean",
+            )),
+        }
+    }
+}
+
+impl JER for i32 {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::Number(self.to_string()))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        v.as_number()?
+            .parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid integer"))
+    }
+}
+
+impl JER for String {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::String(self.clone()))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        Ok(v.as_str()?.to_string())
+    }
+}
+
+impl JER for Vec<u8> {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::String(hex_encode(self)))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        hex_decode(v.as_str()?)
+    }
+}
+
+impl<T: JER> JER for Vec<T> {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::Array(
+            self.iter()
+                .map(JER::jer_value)
+                .collect::<io::Result<Vec<Value>>>()?,
+        ))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        v.as_array()?.iter().map(T::jer_from_value).collect()
+    }
+}
+
+impl<const N: usize> JER for [u8; N] {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::String(hex_encode(self)))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        into_array(hex_decode(v.as_str()?)?)
+    }
+}
+
+impl<T: JER, const N: usize> JER for [T; N] {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::Array(
+            self.iter()
+                .map(JER::jer_value)
+                .collect::<io::Result<Vec<Value>>>()?,
+        ))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        into_array(Vec::<T>::jer_from_value(v)?)
+    }
+}
+
+/// Implement `JER` for tuples, which are encoded as an array of their components
+macro_rules! jer_tuple {
+    () => {};
+    (($first:ident $first_var:ident) $(($ty:ident $var:ident))*) => {
+        impl<$first: JER, $($ty: JER),*> JER for ($first, $($ty,)*) {
+            fn jer_value(&self) -> io::Result<Value> {
+                let ($first_var, $($var,)*) = self;
+                Ok(Value::Array(vec![$first_var.jer_value()?, $($var.jer_value()?,)*]))
+            }
+
+            fn jer_from_value(v: &Value) -> io::Result<Self> {
+                let mut items = v.as_array()?.iter();
+                let $first_var = $first::jer_from_value(items.next().ok_or_else(tuple_mismatch)?)?;
+                $(let $var = $ty::jer_from_value(items.next().ok_or_else(tuple_mismatch)?)?;)*
+                if items.next().is_some() {
+                    return Err(tuple_mismatch());
+                }
+                Ok(($first_var, $($var,)*))
+            }
+        }
+
+        jer_tuple!($(($ty $var))*);
+    };
+}
+
+jer_tuple!((A a) (B b) (C c) (D d) (E e) (F f) (G g) (H h) (I i) (J j) (K k) (L l));
+
+/// Implement `JER` for smart pointers, which are encoded like the value they point to
+macro_rules! jer_pointer {
+    ($($pointer:ident $(<$lt:lifetime>)?),+) => {$(
+        impl<T: JER> JER for $pointer<$($lt,)? T> {
+            fn jer_value(&self) -> io::Result<Value> {
+                (**self).jer_value()
+            }
+
+            fn jer_from_value(v: &Value) -> io::Result<Self> {
+                T::jer_from_value(v).map($pointer::new)
+            }
+
+            fn jer_absent() -> Option<Self> {
+                T::jer_absent().map($pointer::new)
+            }
+
+            fn jer_present(&self) -> bool {
+                (**self).jer_present()
+            }
+        }
+    )+};
+}
+
+jer_pointer!(Box, Rc, Arc, Component<'_>);
+
+/// Absent values are left out of the enclosing object
+impl<T: JER> JER for Option<T> {
+    fn jer_value(&self) -> io::Result<Value> {
+        match self {
+            Some(value) => value.jer_value(),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        T::jer_from_value(v).map(Some)
+    }
+
+    fn jer_absent() -> Option<Self> {
+        Some(None)
+    }
+
+    fn jer_present(&self) -> bool {
+        self.is_some()
+    }
+}
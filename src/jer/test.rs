@@ -0,0 +1,192 @@
+use super::*;
+use crate::types::{BitString, IA5String, Null};
+
+#[derive(Debug, PartialEq)]
+struct TestStruct {
+    pub alpha: i32,
+    pub beta: Vec<bool>,
+    pub gamma: String,
+    pub delta: TestChoice,
+    pub epsilon: Vec<TestEnum>,
+    pub zeta: BitString,
+}
+
+der_sequence! {TestStruct WITH(JER):
+    alpha: NOTAG TYPE i32,
+    beta: EXPLICIT TAG CONTEXT 42; TYPE Vec<bool>,
+    gamma: IMPLICIT TAG APPLICATION 397; TYPE String,
+    delta: EXPLICIT TAG CONTEXT 1; TYPE TestChoice,
+    epsilon: NOTAG TYPE Vec<TestEnum>,
+    zeta: NOTAG TYPE BitString,
+}
+
+#[derive(Debug, PartialEq)]
+enum TestChoice {
+    Alpha(Vec<u8>),
+    Beta(Null),
+}
+
+der_choice! {TestChoice WITH(JER):
+    Alpha: IMPLICIT TAG CONTEXT 0; TYPE Vec<u8>,
+    Beta: IMPLICIT TAG CONTEXT 1; TYPE Null,
+}
+
+#[derive(Debug, PartialEq)]
+enum TestEnum {
+    Alpha = 1,
+    Beta = 2,
+}
+
+der_enumerated!(TestEnum WITH(JER), Alpha, Beta);
+
+fn test_struct() -> TestStruct {
+    TestStruct {
+        alpha: -42,
+        beta: vec![true, false],
+        gamma: "\"Hello\"\n".to_string(),
+        delta: TestChoice::Alpha(vec![0x0A, 0xFF]),
+        epsilon: vec![TestEnum::Beta],
+        zeta: BitString::from_bits(&[true, true, false]),
+    }
+}
+
+#[test]
+fn encode() {
+    assert_eq!(
+        test_struct().jer_string().unwrap(),
+        "{\"alpha\":-42,\"beta\":[true,false],\"gamma\":\"\\\"Hello\\\"\\n\",\
+         \"delta\":{\"Alpha\":\"0AFF\"},\"epsilon\":[\"Beta\"],\
+         \"zeta\":{\"value\":\"C0\",\"length\":3}}"
+    );
+    assert_eq!(
+        TestChoice::Beta(Null).jer_string().unwrap(),
+        "{\"Beta\":null}"
+    );
+}
+
+#[test]
+fn roundtrip() {
+    let data = test_struct();
+    assert_eq!(
+        data,
+        TestStruct::jer_from_str(&data.jer_string().unwrap()).unwrap()
+    );
+    let mut stream = vec![];
+    data.jer_encode(&mut stream).unwrap();
+    assert_eq!(data, TestStruct::jer_decode(&mut &stream[..]).unwrap());
+
+    let strings = vec![IA5String::from("foo".to_string())];
+    let decoded = Vec::<IA5String>::jer_from_str(&strings.jer_string().unwrap()).unwrap();
+    assert_eq!("foo", String::from(decoded.into_iter().next().unwrap()));
+}
+
+#[test]
+fn decode_member_order_and_whitespace() {
+    let text = "{ \"zeta\": {\"length\": 0, \"value\": \"\"}, \"epsilon\": [\"Alpha\"],
+        \"delta\": {\"Beta\": null}, \"gamma\": \"\", \"beta\": [], \"alpha\": 7 }";
+    assert_eq!(
+        TestStruct::jer_from_str(text).unwrap(),
+        TestStruct {
+            alpha: 7,
+            beta: vec![],
+            gamma: String::new(),
+            delta: TestChoice::Beta(Null),
+            epsilon: vec![TestEnum::Alpha],
+            zeta: BitString::from_bits(&[]),
+        }
+    );
+    assert_eq!(
+        Vec::<u8>::jer_from_str("\"0aff\"").unwrap(),
+        vec![0x0A, 0xFF]
+    );
+}
+
+#[test]
+fn decode_errors() {
+    let error = TestChoice::jer_from_str("{\"Gamma\":1}").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unknown alternative \"Gamma\" in TestChoice"
+    );
+    assert!(TestChoice::jer_from_str("{\"Alpha\":\"00\",\"Beta\":null}").is_err());
+    let error = TestStruct::jer_from_str("{\"alpha\":1}").unwrap_err();
+    assert_eq!(error.to_string(), "missing member \"beta\" in TestStruct");
+    let mut value = test_struct().jer_value().unwrap();
+    if let Value::Object(ref mut members) = value {
+        members.push(("eta".to_string(), Value::Null));
+    }
+    let error = TestStruct::jer_from_value(&value).unwrap_err();
+    assert_eq!(error.to_string(), "unknown member \"eta\" in TestStruct");
+    let error = TestStruct::jer_from_str("{\"alpha\":1,\"alpha\":2}").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "duplicate member \"alpha\" in TestStruct"
+    );
+    assert!(TestEnum::jer_from_str("\"Gamma\"").is_err());
+    assert!(i32::jer_from_str("1.5").is_err());
+    assert!(i32::jer_from_str("\"1\"").is_err());
+}
+
+#[derive(Debug, PartialEq)]
+struct OptionalStruct {
+    pub alpha: Option<i32>,
+    pub beta: bool,
+}
+
+der_sequence! {OptionalStruct WITH(JER):
+    alpha: IMPLICIT TAG CONTEXT 0; TYPE Option<i32>,
+    beta: NOTAG TYPE bool,
+}
+
+#[test]
+fn optional_members() {
+    let data = OptionalStruct {
+        alpha: None,
+        beta: true,
+    };
+    assert_eq!(data.jer_string().unwrap(), "{\"beta\":true}");
+    assert_eq!(
+        data,
+        OptionalStruct::jer_from_str("{\"beta\":true}").unwrap()
+    );
+    assert_eq!(
+        OptionalStruct::jer_from_str("{\"alpha\":3,\"beta\":false}").unwrap(),
+        OptionalStruct {
+            alpha: Some(3),
+            beta: false,
+        }
+    );
+}
+
+#[derive(Debug, PartialEq)]
+struct Extensible {
+    pub alpha: i32,
+    pub shade: Shade,
+}
+
+der_sequence! {Extensible WITH(JER):
+    alpha: NOTAG TYPE i32,
+    shade: NOTAG TYPE Shade,
+    ...
+}
+
+#[derive(Debug, PartialEq)]
+enum Shade {
+    Red = 0,
+    Unknown,
+}
+
+der_enumerated!(Shade WITH(JER), Red, ... Unknown);
+
+#[test]
+fn extension_markers() {
+    let decoded = Extensible::jer_from_str("{\"eta\":[2],\"alpha\":1,\"shade\":\"Blue\"}").unwrap();
+    assert_eq!(
+        decoded,
+        Extensible {
+            alpha: 1,
+            shade: Shade::Unknown,
+        }
+    );
+    assert!(decoded.jer_string().is_err());
+}
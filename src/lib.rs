@@ -5,7 +5,7 @@
 //!
 //! It makes heavy use of macros to make the interface easy to use.
 //!
-//! Currently only DER and a very small bit of XER is supported.
+//! Currently DER, XER, JER, UPER, APER and OER are supported.
 //!
 //! ## Example ##
 //! Say you have the following asn1 structure:
@@ -51,36 +51,66 @@
 //! ```
 //!
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![deny(
     missing_docs,
     missing_debug_implementations,
     missing_copy_implementations
 )]
 
+extern crate alloc;
+
+/// Readers and writers, `std::io` or a replacement without `std`
+pub mod io;
+
+/// The items of the `std` prelude missing in `no_std` crates, used by the modules and macros
 #[doc(hidden)]
-extern crate byteorder;
+pub mod prelude {
+    pub use alloc::boxed::Box;
+    pub use alloc::string::{String, ToString};
+    pub use alloc::vec::Vec;
+    pub use alloc::{format, vec};
+}
+
+/// DER Implementation
+#[macro_use]
+pub mod der;
 
-/// **UNFINISHED** XER Implementation
+/// XER Implementation (BASIC-XER and CANONICAL-XER)
 #[macro_use]
 pub mod xer;
 
-/// DER Implementation
+/// JER Implementation (JSON Encoding Rules)
 #[macro_use]
-pub mod der;
+pub mod jer;
+
+/// PER Implementation (Unaligned and Aligned PER)
+#[macro_use]
+pub mod per;
+
+/// OER Implementation (BASIC-OER and CANONICAL-OER)
+#[macro_use]
+pub mod oer;
+
+/// Subtype Constraints
+pub mod constraints;
 
 /// Asn1 Types
 pub mod types;
 
+/// PEM Implementation ([RFC 7468](https://tools.ietf.org/html/rfc7468))
+pub mod pem;
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! debug_xer {
     ($struct_name:ident) => {
-        impl ::std::fmt::Debug for $struct_name {
-            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        impl ::core::fmt::Debug for $struct_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                 use $crate::xer::XEREncodeable;
-                let mut stream = Vec::<u8>::new();
+                let mut stream = $crate::prelude::Vec::<u8>::new();
                 self.xer_encode(&mut stream).unwrap();
-                write!(f, "{}", ::std::string::String::from_utf8(stream).unwrap())
+                write!(f, "{}", $crate::prelude::String::from_utf8(stream).unwrap())
             }
         }
     };
@@ -0,0 +1,155 @@
+/// OER implementation for sequences, used by `der_sequence!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! oer_sequence {
+    ($struct_name:ident $(<$lt:lifetime>)? [$extensible:tt $(, $unknown:ident)?] : $($field_name:ident $(BY $id:ident)? : $field_type:ty = [$($c:tt)*]),+) => {
+        impl $crate::oer::OER for $struct_name $(<$lt>)? {
+            fn oer_encode(&self, w: &mut $crate::prelude::Vec<u8>, _: &$crate::constraints::Constraints) -> $crate::io::Result<()> {
+                // The bitmap of an extensible sequence starts with the extension bit, which stays
+                // unset because extension additions of a newer version are not kept
+                let extensible: bool = $extensible;
+                let mut bitmap = $crate::prelude::vec![];
+                if extensible {
+                    bitmap.push(false);
+                }
+                $(
+                    if <$field_type as $crate::oer::OER>::oer_absent().is_some() {
+                        bitmap.push($crate::oer::OER::oer_present(&self.$field_name));
+                    }
+                )+
+                $crate::oer::encode_bitmap(w, &bitmap);
+                $(
+                    if $crate::oer::OER::oer_present(&self.$field_name) {
+                        $crate::der_constraints!(@check &self.$field_name, stringify!($field_name), stringify!($struct_name); $($c)*);
+                        $crate::constraints::in_field($crate::oer::OER::oer_encode(&self.$field_name, w, &$crate::der_constraints!($($c)*)),
+                                                      stringify!($field_name), stringify!($struct_name))?;
+                    }
+                )+
+                Ok(())
+            }
+
+            fn oer_decode(r: &mut $crate::oer::Reader, _: &$crate::constraints::Constraints) -> $crate::io::Result<Self> {
+                let extensible: bool = $extensible;
+                let optional = [$(<$field_type as $crate::oer::OER>::oer_absent().is_some()),+];
+                let count = optional.iter().filter(|o| **o).count() + usize::from(extensible);
+                let mut bitmap = $crate::oer::decode_bitmap(r, count)?.into_iter();
+                let extended = extensible && bitmap.next() == Some(true);
+                let mut present = optional.iter().map(|o| !*o || bitmap.next() == Some(true));
+                $(
+                    let $field_name = match (present.next(), <$field_type as $crate::oer::OER>::oer_absent()) {
+                        (Some(false), Some(absent)) => absent,
+                        _ => $crate::constraints::in_field(<$field_type as $crate::oer::OER>::oer_decode(r, &$crate::der_constraints!($($c)*)),
+                                                           stringify!($field_name), stringify!($struct_name))?,
+                    };
+                    $crate::der_constraints!(@check &$field_name, stringify!($field_name), stringify!($struct_name); $($c)*);
+                    $(let $field_name = $crate::types::OpenType::open_resolve($field_name, &$id)?;)?
+                )+
+                if extended {
+                    $crate::oer::skip_extensions(r)?;
+                }
+                Ok($struct_name {
+                    $(
+                        $field_name: $field_name,
+                    )+
+                    $(
+                        $unknown: $crate::prelude::Vec::new(),
+                    )?
+                })
+            }
+        }
+    };
+}
+
+/// OER implementation for choices, used by `der_choice!`
+///
+/// The alternative is identified by its outermost tag. An untagged CHOICE alternative has no tag
+/// of its own, it is identified by the tag its own encoding starts with.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! oer_choice {
+    ($choice_name:ident [$extensible:tt $(, $unknown:ident)?] : $($variant_name:ident [$($shape:tt)*] : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $variant_type:ty = [$($c:tt)*]),+) => {
+        impl $crate::oer::OER for $choice_name {
+            fn oer_encode(&self, w: &mut $crate::prelude::Vec<u8>, _: &$crate::constraints::Constraints) -> $crate::io::Result<()> {
+                match self {
+                    $($crate::der_choice!(@pattern $choice_name $variant_name [$($shape)*] payload) => {
+                        let val = $crate::der_choice!(@payload $variant_name [$($shape)*] payload);
+                        if let Some((class, tag)) = $crate::oer_choice!(@tag $tagtype $(TAG $tagclass $tagval;)* TYPE $variant_type) {
+                            $crate::oer::encode_tag(w, class, tag);
+                        }
+                        $crate::der_constraints!(@check val, stringify!($variant_name), stringify!($choice_name); $($c)*);
+                        $crate::constraints::in_field($crate::oer::OER::oer_encode(val, w, &$crate::der_constraints!($($c)*)),
+                                                      stringify!($variant_name), stringify!($choice_name))
+                    },)+
+                    $(&$choice_name::$unknown(_) => Err($crate::der::unknown_alternative(stringify!($choice_name))),)?
+                }
+            }
+
+            fn oer_decode(r: &mut $crate::oer::Reader, _: &$crate::constraints::Constraints) -> $crate::io::Result<Self> {
+                let (class, tag) = $crate::oer::decode_tag(&mut r.clone())?;
+                $(
+                    let own = $crate::oer_choice!(@tag $tagtype $(TAG $tagclass $tagval;)* TYPE $variant_type);
+                    let matches = match own {
+                        Some(own) => own == (class, tag),
+                        None => <$variant_type as $crate::der::DER>::der_matches(class, tag),
+                    };
+                    if matches {
+                        if own.is_some() {
+                            $crate::oer::decode_tag(r)?;
+                        }
+                        let value = $crate::constraints::in_field($crate::oer::OER::oer_decode(r, &$crate::der_constraints!($($c)*)),
+                                                                  stringify!($variant_name), stringify!($choice_name))?;
+                        $crate::der_constraints!(@check &value, stringify!($variant_name), stringify!($choice_name); $($c)*);
+                        return Ok($crate::der_choice!(@construct $choice_name $variant_name [$($shape)*] value));
+                    }
+                )+
+                Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                    $crate::prelude::format!("unknown alternative tag {:?} {} in {}", class, tag, stringify!($choice_name))))
+            }
+        }
+    };
+    (@tag $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $variant_type:ty) => {
+        match stringify!($tagtype) {
+            "NOTAG" if <$variant_type as $crate::der::DER>::DER_EXPLICIT_ONLY => None,
+            "NOTAG" => Some(($crate::der::Class::Universal, <$variant_type as $crate::der::DER>::der_universal_tag() as u32)),
+            $("EXPLICIT" | "IMPLICIT" | "AUTOMATIC" => Some((match stringify!($tagclass) {
+                                              "UNIVERSAL" => $crate::der::Class::Universal,
+                                              "APPLICATION" => $crate::der::Class::Application,
+                                              "CONTEXT" => $crate::der::Class::ContextSpecific,
+                                              "PRIVATE" => $crate::der::Class::Private,
+                                              _ => unreachable!(),
+                                          }, $tagval)),)*
+            _ => unreachable!(),
+        }
+    };
+}
+
+/// OER implementation for enumerations, used by `der_enumerated!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! oer_enumerated {
+    ($enum_name:ident [$extensible:tt $(, $unknown:ident)? $(; $other:ident)?], $($enum_variant:ident = $value:expr),+) => {
+        impl $crate::oer::OER for $enum_name {
+            fn oer_encode(&self, w: &mut $crate::prelude::Vec<u8>, _: &$crate::constraints::Constraints) -> $crate::io::Result<()> {
+                $crate::oer::encode_enumerated(w, match self {
+                    $(&$enum_name::$enum_variant => $value,)+
+                    $(&$enum_name::$unknown => return Err($crate::der::unknown_value(stringify!($enum_name))),)?
+                    $(&$enum_name::$other(value) => value,)?
+                });
+                Ok(())
+            }
+
+            fn oer_decode(r: &mut $crate::oer::Reader, _: &$crate::constraints::Constraints) -> $crate::io::Result<Self> {
+                let value = $crate::oer::decode_enumerated(r)?;
+                $(
+                    if value == $value {
+                        return Ok($enum_name::$enum_variant);
+                    }
+                )+
+                // Values added by a newer version map to the unknown variant or are kept as they are
+                let unknown: Option<Self> = None $(.or(Some($enum_name::$unknown)))? $(.or(Some($enum_name::$other(value))))?;
+                unknown.ok_or_else(|| $crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                    $crate::prelude::format!("invalid enumeration value {} in {}", value, stringify!($enum_name))))
+            }
+        }
+    };
+}
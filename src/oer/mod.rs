@@ -0,0 +1,634 @@
+use crate::constraints::Constraints;
+use crate::der::{into_array, Class, Component};
+use crate::io;
+use crate::per::Alphabet;
+use crate::prelude::*;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::convert::TryFrom;
+
+/// Octet reader
+pub mod reader;
+#[doc(hidden)]
+#[macro_use]
+pub mod macros;
+
+#[cfg(test)]
+mod test;
+
+pub use self::reader::Reader;
+
+/// The trait for OER encoding
+///
+/// Like PER, OER depends on the constraints of a value, so they are passed to every call. Types
+/// implementing `DER` through `der_sequence!`, `der_choice!` or `der_enumerated!` implement this
+/// trait when `OER` is listed in `WITH(...)` after the type name: sequences start with a bitmap of
+/// their present `OPTIONAL` components, choices with the tag of the alternative and enumerations are encoded as their value.
+///
+/// The encoder always produces CANONICAL-OER, which is also valid BASIC-OER. `oer_from_bytes()`
+/// accepts any BASIC-OER encoding while `coer_from_bytes()` rejects everything but the canonical
+/// one.
+///
+/// # Example
+/// ```
+/// # #[macro_use]
+/// # extern crate eagre_asn1;
+/// use eagre_asn1::oer::OER;
+///
+/// # #[derive(Debug, PartialEq)]
+/// struct Position {
+///     pub valid: bool,
+///     pub speed: Option<i32>,
+/// }
+///
+/// der_sequence! {
+///     Position WITH(OER):
+///         valid: NOTAG TYPE bool,
+///         speed: IMPLICIT TAG CONTEXT 0; TYPE Option<i32>,
+/// }
+///
+/// # fn main() {
+/// let position = Position { valid: true, speed: None };
+/// let encoded = position.oer_bytes().unwrap();
+/// // The bitmap octet, then the boolean
+/// assert_eq!(encoded, vec![0x00, 0xFF]);
+/// assert_eq!(position, Position::coer_from_bytes(&encoded).unwrap());
+/// # }
+/// ```
+pub trait OER: Sized {
+    /// Encode this value with constraints `c`
+    fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()>;
+    /// Decode a value with constraints `c`
+    fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self>;
+    /// Value to use if this type is missing from a sequence, `Some` only for optional types
+    fn oer_absent() -> Option<Self> {
+        None
+    }
+    /// Return whether this value is encoded at all, `false` only for absent optional values
+    fn oer_present(&self) -> bool {
+        true
+    }
+    /// Return the complete (canonical) OER encoding
+    fn oer_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut w = vec![];
+        self.oer_encode(&mut w, &Constraints::NONE)?;
+        Ok(w)
+    }
+    /// Decode a complete BASIC-OER encoding
+    fn oer_from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        decode_complete(Reader::new(bytes))
+    }
+    /// Decode a complete CANONICAL-OER encoding
+    fn coer_from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        decode_complete(Reader::canonical(bytes))
+    }
+}
+
+fn decode_complete<T: OER>(mut r: Reader) -> io::Result<T> {
+    let value = T::oer_decode(&mut r, &Constraints::NONE)?;
+    if r.remaining() > 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "trailing data after OER encoding",
+        ));
+    }
+    Ok(value)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Return the size of a fixed size constraint `SIZE(n)`
+pub fn fixed_size(c: &Constraints) -> Option<usize> {
+    match (c.size_min, c.size_max) {
+        (Some(min), Some(max)) if min == max => Some(min),
+        _ => None,
+    }
+}
+
+fn unsigned_octets(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take(7).take_while(|b| **b == 0).count();
+    bytes[skip..].to_vec()
+}
+
+fn signed_octets(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes
+        .windows(2)
+        .take_while(|w| (w[0] == 0x00 && w[1] & 0x80 == 0) || (w[0] == 0xFF && w[1] & 0x80 != 0))
+        .count();
+    bytes[skip..].to_vec()
+}
+
+/// Convert big endian octets into an integer, rejecting redundant leading octets in COER
+fn integer_from_octets(r: &Reader, mut bytes: &[u8], signed: bool) -> io::Result<i64> {
+    if bytes.is_empty() {
+        return Err(invalid("empty OER integer"));
+    }
+    let redundant = |b: &[u8]| {
+        b.len() > 1
+            && if signed {
+                (b[0] == 0x00 && b[1] & 0x80 == 0) || (b[0] == 0xFF && b[1] & 0x80 != 0)
+            } else {
+                b[0] == 0x00
+            }
+    };
+    if redundant(bytes) {
+        r.non_canonical("integer")?;
+        while redundant(bytes) {
+            bytes = &bytes[1..];
+        }
+    }
+    integer_value(bytes, signed)
+}
+
+/// Convert at most eight big endian octets into an integer
+fn integer_value(bytes: &[u8], signed: bool) -> io::Result<i64> {
+    if bytes.len() > 8 || (!signed && bytes.len() == 8 && bytes[0] & 0x80 != 0) {
+        return Err(invalid("OER integer too large"));
+    }
+    let negative = signed && bytes[0] & 0x80 != 0;
+    let start = if negative { -1 } else { 0 };
+    Ok(bytes
+        .iter()
+        .fold(start, |value: i64, b| value << 8 | i64::from(*b)))
+}
+
+/// Encode a length determinant
+pub fn encode_length(w: &mut Vec<u8>, length: usize) {
+    if length < 0x80 {
+        w.push(length as u8);
+    } else {
+        let bytes = unsigned_octets(length as u64);
+        w.push(0x80 | bytes.len() as u8);
+        w.extend(bytes);
+    }
+}
+
+/// Decode a length determinant
+pub fn decode_length(r: &mut Reader) -> io::Result<usize> {
+    let first = r.read_u8()?;
+    if first < 0x80 {
+        return Ok(usize::from(first));
+    }
+    let count = usize::from(first & 0x7F);
+    if count == 0 || count > 8 {
+        return Err(invalid("invalid OER length"));
+    }
+    let bytes = r.read_bytes(count)?;
+    let length = integer_from_octets(r, bytes, false)?;
+    if length < 0x80 {
+        r.non_canonical("length")?;
+    }
+    usize::try_from(length).map_err(|_| invalid("OER length too large"))
+}
+
+/// Encode the number of items of a `SEQUENCE OF`
+pub fn encode_quantity(w: &mut Vec<u8>, count: usize) {
+    let bytes = unsigned_octets(count as u64);
+    encode_length(w, bytes.len());
+    w.extend(bytes);
+}
+
+/// Decode the number of items of a `SEQUENCE OF`
+pub fn decode_quantity(r: &mut Reader) -> io::Result<usize> {
+    let length = decode_length(r)?;
+    let bytes = r.read_bytes(length)?;
+    let count = integer_from_octets(r, bytes, false)?;
+    usize::try_from(count).map_err(|_| invalid("OER quantity too large"))
+}
+
+/// Return the number of octets and signedness of the integer encoding for constraints `c`
+///
+/// Value ranges that fit into 1, 2, 4 or 8 octets use that many octets, everything else is
+/// length prefixed.
+fn integer_form(c: &Constraints) -> (Option<usize>, bool) {
+    match (c.min, c.max) {
+        (Some(min), Some(max)) if min >= 0 => {
+            let octets = match max {
+                0..=0xFF => 1,
+                0x100..=0xFFFF => 2,
+                0x1_0000..=0xFFFF_FFFF => 4,
+                _ => 8,
+            };
+            (Some(octets), false)
+        }
+        (Some(min), Some(max)) => {
+            let octets = if min >= -0x80 && max <= 0x7F {
+                1
+            } else if min >= -0x8000 && max <= 0x7FFF {
+                2
+            } else if min >= -0x8000_0000 && max <= 0x7FFF_FFFF {
+                4
+            } else {
+                8
+            };
+            (Some(octets), true)
+        }
+        (Some(min), None) if min >= 0 => (None, false),
+        _ => (None, true),
+    }
+}
+
+/// Encode an integer with constraints `c`
+pub fn encode_integer(w: &mut Vec<u8>, value: i64, c: &Constraints) -> io::Result<()> {
+    c.check_value(value)?;
+    match integer_form(c) {
+        (Some(octets), _) => w.extend(&value.to_be_bytes()[8 - octets..]),
+        (None, signed) => {
+            let bytes = if signed {
+                signed_octets(value)
+            } else {
+                unsigned_octets(value as u64)
+            };
+            encode_length(w, bytes.len());
+            w.extend(bytes);
+        }
+    }
+    Ok(())
+}
+
+/// Decode an integer with constraints `c`
+pub fn decode_integer(r: &mut Reader, c: &Constraints) -> io::Result<i64> {
+    let value = match integer_form(c) {
+        (Some(octets), signed) => integer_value(r.read_bytes(octets)?, signed)?,
+        (None, signed) => {
+            let length = decode_length(r)?;
+            let bytes = r.read_bytes(length)?;
+            integer_from_octets(r, bytes, signed)?
+        }
+    };
+    c.check_value(value)?;
+    Ok(value)
+}
+
+/// Encode the value of an enumeration
+pub fn encode_enumerated(w: &mut Vec<u8>, value: i64) {
+    if (0..0x80).contains(&value) {
+        w.push(value as u8);
+    } else {
+        let bytes = signed_octets(value);
+        w.push(0x80 | bytes.len() as u8);
+        w.extend(bytes);
+    }
+}
+
+/// Decode the value of an enumeration
+pub fn decode_enumerated(r: &mut Reader) -> io::Result<i64> {
+    let first = r.read_u8()?;
+    if first < 0x80 {
+        return Ok(i64::from(first));
+    }
+    let count = usize::from(first & 0x7F);
+    if count == 0 {
+        return Err(invalid("invalid OER enumerated value"));
+    }
+    let bytes = r.read_bytes(count)?;
+    let value = integer_from_octets(r, bytes, true)?;
+    if (0..0x80).contains(&value) {
+        r.non_canonical("enumerated value")?;
+    }
+    Ok(value)
+}
+
+/// Encode the tag of a `CHOICE` alternative
+pub fn encode_tag(w: &mut Vec<u8>, class: Class, tag: u32) {
+    let class = (class as u8) << 6;
+    if tag < 0x3F {
+        w.push(class | tag as u8);
+        return;
+    }
+    w.push(class | 0x3F);
+    let octets = (32 - tag.leading_zeros() as usize).div_ceil(7);
+    for i in (0..octets).rev() {
+        let more = if i > 0 { 0x80 } else { 0 };
+        w.push(more | (tag >> (7 * i)) as u8 & 0x7F);
+    }
+}
+
+/// Decode the tag of a `CHOICE` alternative
+pub fn decode_tag(r: &mut Reader) -> io::Result<(Class, u32)> {
+    let first = r.read_u8()?;
+    let class = match first >> 6 {
+        0 => Class::Universal,
+        1 => Class::Application,
+        2 => Class::ContextSpecific,
+        _ => Class::Private,
+    };
+    let mut tag = u32::from(first & 0x3F);
+    if tag < 0x3F {
+        return Ok((class, tag));
+    }
+    tag = 0;
+    loop {
+        let octet = r.read_u8()?;
+        if tag == 0 && octet == 0x80 {
+            return Err(invalid("invalid OER tag"));
+        }
+        if tag > u32::MAX >> 7 {
+            return Err(invalid("OER tag too large"));
+        }
+        tag = tag << 7 | u32::from(octet & 0x7F);
+        if octet & 0x80 == 0 {
+            break;
+        }
+    }
+    if tag < 0x3F {
+        r.non_canonical("tag")?;
+    }
+    Ok((class, tag))
+}
+
+/// Encode the presence bitmap of a sequence, padded with zero bits to whole octets
+pub fn encode_bitmap(w: &mut Vec<u8>, bits: &[bool]) {
+    for chunk in bits.chunks(8) {
+        let octet = chunk
+            .iter()
+            .enumerate()
+            .fold(0u8, |octet, (i, bit)| octet | (u8::from(*bit) << (7 - i)));
+        w.push(octet);
+    }
+}
+
+/// Decode a presence bitmap of `count` bits
+pub fn decode_bitmap(r: &mut Reader, count: usize) -> io::Result<Vec<bool>> {
+    let bytes = r.read_bytes(count.div_ceil(8))?;
+    if count & 7 != 0 && bytes[bytes.len() - 1] & (0xFF >> (count & 7)) != 0 {
+        r.non_canonical("bitmap padding")?;
+    }
+    Ok((0..count)
+        .map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+        .collect())
+}
+
+/// Encode octets, length prefixed unless `c` has a fixed size
+pub fn encode_octets(w: &mut Vec<u8>, bytes: &[u8], c: &Constraints) -> io::Result<()> {
+    c.check_size(bytes.len())?;
+    if fixed_size(c).is_none() {
+        encode_length(w, bytes.len());
+    }
+    w.extend(bytes);
+    Ok(())
+}
+
+/// Decode octets, the counterpart of `encode_octets()`
+pub fn decode_octets(r: &mut Reader, c: &Constraints) -> io::Result<Vec<u8>> {
+    let length = match fixed_size(c) {
+        Some(size) => size,
+        None => decode_length(r)?,
+    };
+    let bytes = r.read_bytes(length)?.to_vec();
+    c.check_size(bytes.len())?;
+    Ok(bytes)
+}
+
+/// Skip the extension additions of a sequence whose extension bit is set
+///
+/// They are preceded by a length prefixed bitmap of the present additions, each of which is an
+/// open type.
+pub fn skip_extensions(r: &mut Reader) -> io::Result<()> {
+    let length = decode_length(r)?;
+    let bytes = r.read_bytes(length)?;
+    let (unused, bitmap) = match bytes.split_first() {
+        Some((unused, bitmap)) if *unused < 8 && (*unused == 0 || !bitmap.is_empty()) => {
+            (usize::from(*unused), bitmap)
+        }
+        _ => return Err(invalid("invalid OER extension bitmap")),
+    };
+    let count = bitmap.len() * 8 - unused;
+    let present = (0..count)
+        .filter(|i| bitmap[i / 8] & (0x80 >> (i % 8)) != 0)
+        .count();
+    for _ in 0..present {
+        let length = decode_length(r)?;
+        r.read_bytes(length)?;
+    }
+    Ok(())
+}
+
+/// Encode a known-multiplier character string, `name` is used in error messages
+///
+/// Every character takes the same number of octets, so fixed size strings have no length.
+pub fn encode_chars(
+    w: &mut Vec<u8>,
+    s: &str,
+    c: &Constraints,
+    alphabet: &Alphabet,
+    name: &str,
+) -> io::Result<()> {
+    let octets = alphabet.octets();
+    let mut bytes = vec![];
+    for ch in s.chars() {
+        if !alphabet.contains(ch) {
+            return Err(invalid(&format!("invalid character {:?} in {}", ch, name)));
+        }
+        bytes.extend(&(ch as u32).to_be_bytes()[4 - octets..]);
+    }
+    let count = bytes.len() / octets;
+    c.check_size(count)?;
+    let c = match fixed_size(c) {
+        Some(_) => Constraints::size(bytes.len(), bytes.len()),
+        None => Constraints::NONE,
+    };
+    encode_octets(w, &bytes, &c)
+}
+
+/// Decode a known-multiplier character string
+pub fn decode_chars(
+    r: &mut Reader,
+    c: &Constraints,
+    alphabet: &Alphabet,
+    name: &str,
+) -> io::Result<String> {
+    let octets = alphabet.octets();
+    let bytes = match fixed_size(c) {
+        Some(size) => r.read_bytes(size.saturating_mul(octets))?.to_vec(),
+        None => decode_octets(r, &Constraints::NONE)?,
+    };
+    if bytes.len() % octets != 0 {
+        return Err(invalid(&format!("invalid length of {}", name)));
+    }
+    let s = bytes
+        .chunks(octets)
+        .map(|chunk| {
+            let code = chunk.iter().fold(0, |code, b| code << 8 | u32::from(*b));
+            ::core::char::from_u32(code)
+                .filter(|ch| alphabet.contains(*ch))
+                .ok_or_else(|| invalid(&format!("invalid character in {}", name)))
+        })
+        .collect::<io::Result<String>>()?;
+    c.check_size(s.chars().count())?;
+    Ok(s)
+}
+
+impl OER for bool {
+    fn oer_encode(&self, w: &mut Vec<u8>, _: &Constraints) -> io::Result<()> {
+        w.push(if *self { 0xFF } else { 0x00 });
+        Ok(())
+    }
+
+    fn oer_decode(r: &mut Reader, _: &Constraints) -> io::Result<Self> {
+        match r.read_u8()? {
+            0x00 => Ok(false),
+            0xFF => Ok(true),
+            _ => r.non_canonical("boolean").map(|_| true),
+        }
+    }
+}
+
+impl OER for i32 {
+    fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+        encode_integer(w, i64::from(*self), c)
+    }
+
+    fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self> {
+        i32::try_from(decode_integer(r, c)?).map_err(|_| invalid("integer out of range"))
+    }
+}
+
+/// UTF8String is not a known-multiplier type, size constraints are checked but not visible
+impl OER for String {
+    fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+        c.check_size(self.chars().count())?;
+        encode_octets(w, self.as_bytes(), &Constraints::NONE)
+    }
+
+    fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self> {
+        let s = String::from_utf8(decode_octets(r, &Constraints::NONE)?)
+            .map_err(|_| invalid("invalid UTF8String"))?;
+        c.check_size(s.chars().count())?;
+        Ok(s)
+    }
+}
+
+impl OER for Vec<u8> {
+    fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+        encode_octets(w, self, c)
+    }
+
+    fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self> {
+        decode_octets(r, c)
+    }
+}
+
+/// Items of a `SEQUENCE OF` are not constrained
+impl<T: OER> OER for Vec<T> {
+    fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+        c.check_size(self.len())?;
+        encode_quantity(w, self.len());
+        for item in self {
+            item.oer_encode(w, &Constraints::NONE)?;
+        }
+        Ok(())
+    }
+
+    fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self> {
+        let count = decode_quantity(r)?;
+        c.check_size(count)?;
+        let mut vector = vec![];
+        for _ in 0..count {
+            vector.push(T::oer_decode(r, &Constraints::NONE)?);
+        }
+        Ok(vector)
+    }
+}
+
+/// `OCTET STRING SIZE(N)`, encoded without a length
+impl<const N: usize> OER for [u8; N] {
+    fn oer_encode(&self, w: &mut Vec<u8>, _: &Constraints) -> io::Result<()> {
+        encode_octets(w, self, &Constraints::size(N, N))
+    }
+
+    fn oer_decode(r: &mut Reader, _: &Constraints) -> io::Result<Self> {
+        into_array(decode_octets(r, &Constraints::size(N, N))?)
+    }
+}
+
+/// `SEQUENCE SIZE(N) OF`, the quantity is encoded even though it is fixed
+impl<T: OER, const N: usize> OER for [T; N] {
+    fn oer_encode(&self, w: &mut Vec<u8>, _: &Constraints) -> io::Result<()> {
+        encode_quantity(w, N);
+        for item in self {
+            item.oer_encode(w, &Constraints::NONE)?;
+        }
+        Ok(())
+    }
+
+    fn oer_decode(r: &mut Reader, _: &Constraints) -> io::Result<Self> {
+        into_array(Vec::<T>::oer_decode(r, &Constraints::size(N, N))?)
+    }
+}
+
+/// Implement `OER` for tuples, which are encoded like a `SEQUENCE` without optional components
+macro_rules! oer_tuple {
+    () => {};
+    (($first:ident $first_var:ident) $(($ty:ident $var:ident))*) => {
+        impl<$first: OER, $($ty: OER),*> OER for ($first, $($ty,)*) {
+            fn oer_encode(&self, w: &mut Vec<u8>, _: &Constraints) -> io::Result<()> {
+                let ($first_var, $($var,)*) = self;
+                $first_var.oer_encode(w, &Constraints::NONE)?;
+                $($var.oer_encode(w, &Constraints::NONE)?;)*
+                Ok(())
+            }
+
+            fn oer_decode(r: &mut Reader, _: &Constraints) -> io::Result<Self> {
+                let $first_var = $first::oer_decode(r, &Constraints::NONE)?;
+                $(let $var = $ty::oer_decode(r, &Constraints::NONE)?;)*
+                Ok(($first_var, $($var,)*))
+            }
+        }
+
+        oer_tuple!($(($ty $var))*);
+    };
+}
+
+oer_tuple!((A a) (B b) (C c) (D d) (E e) (F f) (G g) (H h) (I i) (J j) (K k) (L l));
+
+/// Implement `OER` for smart pointers, which are encoded like the value they point to
+macro_rules! oer_pointer {
+    ($($pointer:ident $(<$lt:lifetime>)?),+) => {$(
+        impl<T: OER> OER for $pointer<$($lt,)? T> {
+            fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+                (**self).oer_encode(w, c)
+            }
+
+            fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self> {
+                T::oer_decode(r, c).map($pointer::new)
+            }
+
+            fn oer_absent() -> Option<Self> {
+                T::oer_absent().map($pointer::new)
+            }
+
+            fn oer_present(&self) -> bool {
+                (**self).oer_present()
+            }
+        }
+    )+};
+}
+
+oer_pointer!(Box, Rc, Arc, Component<'_>);
+
+/// Absent values are only recorded in the bitmap of the enclosing sequence
+impl<T: OER> OER for Option<T> {
+    fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+        match self {
+            Some(value) => value.oer_encode(w, c),
+            None => Ok(()),
+        }
+    }
+
+    fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self> {
+        T::oer_decode(r, c).map(Some)
+    }
+
+    fn oer_absent() -> Option<Self> {
+        Some(None)
+    }
+
+    fn oer_present(&self) -> bool {
+        self.is_some()
+    }
+}
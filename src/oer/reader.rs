@@ -0,0 +1,95 @@
+use crate::io;
+use crate::prelude::*;
+
+/// Octet reader for OER
+///
+/// A reader either accepts every BASIC-OER encoding or, if created with `canonical()`, only the
+/// single encoding allowed by CANONICAL-OER.
+#[derive(Clone, Debug)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    canonical: bool,
+}
+
+impl<'a> Reader<'a> {
+    /// Create a BASIC-OER reader starting at the first octet of `bytes`
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader {
+            bytes,
+            pos: 0,
+            canonical: false,
+        }
+    }
+
+    /// Create a CANONICAL-OER reader starting at the first octet of `bytes`
+    pub fn canonical(bytes: &'a [u8]) -> Reader<'a> {
+        Reader {
+            bytes,
+            pos: 0,
+            canonical: true,
+        }
+    }
+
+    /// Return whether this reader only accepts canonical encodings
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// Number of octets read so far
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of octets left
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    /// Read a single octet
+    pub fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    /// Read `count` octets
+    pub fn read_bytes(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        if count > self.remaining() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "unexpected end of OER data",
+            ));
+        }
+        let bytes = &self.bytes[self.pos..self.pos + count];
+        self.pos += count;
+        Ok(bytes)
+    }
+
+    /// Fail with `message` if this reader only accepts canonical encodings
+    pub fn non_canonical(&self, message: &str) -> io::Result<()> {
+        if self.canonical {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("non-canonical {}", message),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn read_octets() {
+    let bytes = [0x01, 0x02, 0x03];
+    let mut r = Reader::new(&bytes);
+    assert_eq!(r.read_u8().unwrap(), 0x01);
+    assert_eq!(r.read_bytes(2).unwrap(), &[0x02, 0x03]);
+    assert_eq!(r.position(), 3);
+    assert!(r.read_u8().is_err());
+    assert!(r.non_canonical("length").is_ok());
+    assert_eq!(
+        Reader::canonical(&bytes)
+            .non_canonical("length")
+            .unwrap_err()
+            .to_string(),
+        "non-canonical length"
+    );
+}
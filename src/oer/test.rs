@@ -0,0 +1,370 @@
+use super::*;
+use crate::types::{BitString, IA5String, Null, NumericString, UniversalString};
+
+#[derive(Debug, PartialEq)]
+struct Message {
+    pub id: i32,
+    pub flag: bool,
+    pub name: Option<String>,
+    pub kind: Kind,
+    pub payload: Payload,
+    pub items: Vec<bool>,
+}
+
+der_sequence! {Message WITH(OER):
+    id: NOTAG TYPE i32,
+    flag: NOTAG TYPE bool,
+    name: IMPLICIT TAG CONTEXT 0; TYPE Option<String>,
+    kind: NOTAG TYPE Kind,
+    payload: EXPLICIT TAG CONTEXT 1; TYPE Payload,
+    items: NOTAG TYPE Vec<bool>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Low = 5,
+    High = 1,
+    Huge = 200,
+    Negative = -1,
+}
+
+der_enumerated!(Kind WITH(OER), Low, High, Huge, Negative);
+
+#[derive(Debug, PartialEq)]
+enum Payload {
+    Empty(Null),
+    Data(Vec<u8>),
+    Number(i32),
+    Far(bool),
+    Farther(bool),
+}
+
+der_choice! {Payload WITH(OER):
+    Empty: IMPLICIT TAG CONTEXT 2; TYPE Null,
+    Data: IMPLICIT TAG CONTEXT 0; TYPE Vec<u8>,
+    Number: IMPLICIT TAG CONTEXT 1; TYPE i32,
+    Far: IMPLICIT TAG APPLICATION 100; TYPE bool,
+    Farther: IMPLICIT TAG PRIVATE 200; TYPE bool,
+}
+
+fn encode<T: OER>(value: &T, c: &Constraints) -> io::Result<Vec<u8>> {
+    let mut w = vec![];
+    value.oer_encode(&mut w, c)?;
+    Ok(w)
+}
+
+fn decode<T: OER>(bytes: &[u8], c: &Constraints) -> io::Result<T> {
+    T::oer_decode(&mut Reader::canonical(bytes), c)
+}
+
+#[test]
+fn encode_message() {
+    let message = Message {
+        id: 5,
+        flag: true,
+        name: None,
+        kind: Kind::Low,
+        payload: Payload::Number(-1),
+        items: vec![true, false, true],
+    };
+    let encoded = message.oer_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        vec![0x00, 0x01, 0x05, 0xFF, 0x05, 0x81, 0x01, 0xFF, 0x01, 0x03, 0xFF, 0x00, 0xFF]
+    );
+    assert_eq!(message, Message::coer_from_bytes(&encoded).unwrap());
+
+    let message = Message {
+        name: Some("ab".to_string()),
+        kind: Kind::High,
+        payload: Payload::Empty(Null),
+        items: vec![],
+        ..message
+    };
+    let encoded = message.oer_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        vec![0x80, 0x01, 0x05, 0xFF, 0x02, 0x61, 0x62, 0x01, 0x82, 0x01, 0x00]
+    );
+    assert_eq!(message, Message::coer_from_bytes(&encoded).unwrap());
+}
+
+#[test]
+fn encode_integers() {
+    let semi = Constraints {
+        min: Some(0),
+        ..Constraints::NONE
+    };
+    let cases: Vec<(i32, Constraints, Vec<u8>)> = vec![
+        (5, Constraints::value(0, 7), vec![0x05]),
+        (1, Constraints::value(1, 1), vec![0x01]),
+        (256, Constraints::value(0, 65535), vec![0x01, 0x00]),
+        (
+            70000,
+            Constraints::value(0, 0xFFFF_FFFF),
+            vec![0x00, 0x01, 0x11, 0x70],
+        ),
+        (
+            7,
+            Constraints::value(0, 1 << 40),
+            vec![0, 0, 0, 0, 0, 0, 0, 7],
+        ),
+        (-1, Constraints::value(-128, 127), vec![0xFF]),
+        (0, Constraints::value(-1, 254), vec![0x00, 0x00]),
+        (-129, Constraints::value(-32768, 32767), vec![0xFF, 0x7F]),
+        (
+            i32::MIN,
+            Constraints::value(i64::from(i32::MIN), i64::from(i32::MAX)),
+            vec![0x80, 0x00, 0x00, 0x00],
+        ),
+        (256, semi, vec![0x02, 0x01, 0x00]),
+        (0, semi, vec![0x01, 0x00]),
+        (128, Constraints::NONE, vec![0x02, 0x00, 0x80]),
+        (-129, Constraints::NONE, vec![0x02, 0xFF, 0x7F]),
+    ];
+    for (value, c, expected) in cases {
+        let encoded = encode(&value, &c).unwrap();
+        assert_eq!(encoded, expected, "{} {}", value, c);
+        assert_eq!(value, decode::<i32>(&encoded, &c).unwrap());
+    }
+
+    let error = encode(&8, &Constraints::value(0, 7)).unwrap_err();
+    assert_eq!(error.to_string(), "value 8 violates constraint (0..7)");
+    assert!(decode::<i32>(&[0x08], &Constraints::value(0, 7)).is_err());
+    assert!(decode::<i32>(&[0x05, 0x00, 0x00, 0x00, 0x00, 0x01], &Constraints::NONE).is_err());
+}
+
+#[test]
+fn encode_enumerations_and_tags() {
+    let cases = vec![
+        (Kind::High, vec![0x01]),
+        (Kind::Huge, vec![0x82, 0x00, 0xC8]),
+        (Kind::Negative, vec![0x81, 0xFF]),
+    ];
+    for (value, expected) in cases {
+        let encoded = value.oer_bytes().unwrap();
+        assert_eq!(encoded, expected);
+        assert_eq!(value, Kind::coer_from_bytes(&encoded).unwrap());
+    }
+
+    let far = Payload::Far(true);
+    assert_eq!(far.oer_bytes().unwrap(), vec![0x7F, 0x64, 0xFF]);
+    assert_eq!(far, Payload::coer_from_bytes(&[0x7F, 0x64, 0xFF]).unwrap());
+    let farther = Payload::Farther(false);
+    assert_eq!(farther.oer_bytes().unwrap(), vec![0xFF, 0x81, 0x48, 0x00]);
+    assert_eq!(
+        farther,
+        Payload::coer_from_bytes(&[0xFF, 0x81, 0x48, 0x00]).unwrap()
+    );
+}
+
+#[test]
+fn encode_sizes() {
+    let key = vec![0xAB; 16];
+    let fixed = Constraints::size(16, 16);
+    assert_eq!(encode(&key, &fixed).unwrap(), key);
+    assert_eq!(key, decode::<Vec<u8>>(&key, &fixed).unwrap());
+    assert!(encode(&vec![0xAB; 15], &fixed).is_err());
+
+    let long = vec![0x11u8; 200];
+    let encoded = long.oer_bytes().unwrap();
+    assert_eq!(&encoded[..2], &[0x81, 0xC8]);
+    assert_eq!(encoded.len(), 202);
+    assert_eq!(long, Vec::<u8>::coer_from_bytes(&encoded).unwrap());
+
+    let many = vec![false; 300];
+    let encoded = many.oer_bytes().unwrap();
+    assert_eq!(&encoded[..3], &[0x02, 0x01, 0x2C]);
+    assert_eq!(many, Vec::<bool>::coer_from_bytes(&encoded).unwrap());
+
+    let flags = BitString::from_bits(&[true, false, true]);
+    assert_eq!(flags.oer_bytes().unwrap(), vec![0x02, 0x05, 0xA0]);
+    assert_eq!(
+        flags,
+        BitString::coer_from_bytes(&[0x02, 0x05, 0xA0]).unwrap()
+    );
+    let exact = Constraints::size(3, 3);
+    assert_eq!(encode(&flags, &exact).unwrap(), vec![0xA0]);
+    assert_eq!(flags, decode::<BitString>(&[0xA0], &exact).unwrap());
+    assert_eq!(
+        BitString::from_bits(&[]).oer_bytes().unwrap(),
+        vec![0x01, 0x00]
+    );
+}
+
+#[test]
+fn encode_strings() {
+    let text = IA5String::from("Hi".to_string());
+    assert_eq!(text.oer_bytes().unwrap(), vec![0x02, 0x48, 0x69]);
+    let fixed = Constraints::size(2, 2);
+    assert_eq!(encode(&text, &fixed).unwrap(), vec![0x48, 0x69]);
+    assert_eq!(
+        "Hi",
+        String::from(decode::<IA5String>(&[0x48, 0x69], &fixed).unwrap())
+    );
+
+    // Four octets per character
+    let universal = UniversalString::from("A".to_string());
+    let fixed = Constraints::size(1, 1);
+    assert_eq!(
+        encode(&universal, &fixed).unwrap(),
+        vec![0x00, 0x00, 0x00, 0x41]
+    );
+    assert_eq!(
+        universal.oer_bytes().unwrap(),
+        vec![0x04, 0x00, 0x00, 0x00, 0x41]
+    );
+    assert_eq!(
+        "A",
+        String::from(UniversalString::coer_from_bytes(&[0x04, 0x00, 0x00, 0x00, 0x41]).unwrap())
+    );
+
+    assert!(NumericString::from("1a".to_string()).oer_bytes().is_err());
+    assert!(NumericString::coer_from_bytes(&[0x01, 0x61]).is_err());
+    assert!(UniversalString::coer_from_bytes(&[0x03, 0x00, 0x00, 0x41]).is_err());
+}
+
+#[test]
+fn basic_and_canonical() {
+    // Each case is valid BASIC-OER but not CANONICAL-OER
+    assert!(bool::oer_from_bytes(&[0x01]).unwrap());
+    assert!(bool::coer_from_bytes(&[0x01]).is_err());
+
+    let long_length = [0x81, 0x02, 0xAA, 0xBB];
+    assert_eq!(
+        Vec::<u8>::oer_from_bytes(&long_length).unwrap(),
+        vec![0xAA, 0xBB]
+    );
+    let error = Vec::<u8>::coer_from_bytes(&long_length).unwrap_err();
+    assert_eq!(error.to_string(), "non-canonical length");
+
+    assert_eq!(i32::oer_from_bytes(&[0x02, 0x00, 0x05]).unwrap(), 5);
+    assert!(i32::coer_from_bytes(&[0x02, 0x00, 0x05]).is_err());
+
+    assert_eq!(Kind::oer_from_bytes(&[0x81, 0x05]).unwrap(), Kind::Low);
+    assert!(Kind::coer_from_bytes(&[0x81, 0x05]).is_err());
+
+    let long_tag = [0xBF, 0x01, 0x01, 0xFF];
+    assert_eq!(
+        Payload::oer_from_bytes(&long_tag).unwrap(),
+        Payload::Number(-1)
+    );
+    assert!(Payload::coer_from_bytes(&long_tag).is_err());
+
+    let padding = [0x01, 0x01, 0x05, 0xFF, 0x05, 0x82, 0x01, 0x00];
+    assert!(Message::oer_from_bytes(&padding).is_ok());
+    assert!(Message::coer_from_bytes(&padding).is_err());
+
+    let unused = [0x02, 0x05, 0xA1];
+    assert_eq!(
+        BitString::oer_from_bytes(&unused).unwrap(),
+        BitString::from_bits(&[true, false, true])
+    );
+    assert!(BitString::coer_from_bytes(&unused).is_err());
+}
+
+#[test]
+fn decode_errors() {
+    let error = Payload::coer_from_bytes(&[0x83]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unknown alternative tag ContextSpecific 3 in Payload"
+    );
+    let error = Kind::coer_from_bytes(&[0x02]).unwrap_err();
+    assert_eq!(error.to_string(), "invalid enumeration value 2 in Kind");
+    assert!(bool::coer_from_bytes(&[0xFF, 0x00]).is_err());
+    assert!(Message::coer_from_bytes(&[0x00]).is_err());
+    assert!(BitString::coer_from_bytes(&[0x01, 0x03]).is_err());
+    assert!(Vec::<u8>::coer_from_bytes(&[0x80]).is_err());
+    assert_eq!(Null::coer_from_bytes(&[]).unwrap(), Null);
+}
+
+#[derive(Debug)]
+struct Reading {
+    pub level: i32,
+    pub code: Vec<u8>,
+    pub label: IA5String,
+}
+
+der_sequence! {Reading WITH(OER):
+    level: NOTAG VALUE(0..7); TYPE i32,
+    code: NOTAG SIZE(2); TYPE Vec<u8>,
+    label: NOTAG SIZE(0..3); TYPE IA5String,
+}
+
+#[test]
+fn encode_annotated_constraints() {
+    let reading = Reading {
+        level: 5,
+        code: vec![0x01, 0x02],
+        label: IA5String::from("ab".to_string()),
+    };
+    let encoded = reading.oer_bytes().unwrap();
+    assert_eq!(encoded, vec![0x05, 0x01, 0x02, 0x02, 0x61, 0x62]);
+    let decoded = Reading::coer_from_bytes(&encoded).unwrap();
+    assert_eq!((decoded.level, decoded.code), (5, vec![0x01, 0x02]));
+
+    // Four characters do not fit SIZE(0..3)
+    let error =
+        Reading::coer_from_bytes(&[0x05, 0x01, 0x02, 0x04, 0x61, 0x62, 0x63, 0x64]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Reading.label: size 4 violates constraint SIZE(0..3)"
+    );
+}
+
+#[derive(Debug, PartialEq)]
+struct Extensible {
+    pub id: i32,
+    pub shade: Shade,
+    pub note: Option<bool>,
+}
+
+der_sequence! {Extensible WITH(OER):
+    id: NOTAG VALUE(0..7); TYPE i32,
+    shade: NOTAG TYPE Shade,
+    note: IMPLICIT TAG CONTEXT 0; TYPE Option<bool>,
+    ...
+}
+
+#[derive(Debug, PartialEq)]
+enum Shade {
+    Red = 0,
+    Green = 1,
+    Unknown,
+}
+
+der_enumerated!(Shade WITH(OER), Red, Green, ... Unknown);
+
+#[test]
+fn encode_extension_markers() {
+    let value = Extensible {
+        id: 5,
+        shade: Shade::Green,
+        note: Some(true),
+    };
+    // The extension bit comes first in the bitmap
+    let encoded = vec![0x40, 0x05, 0x01, 0xFF];
+    assert_eq!(encode(&value, &Constraints::NONE).unwrap(), encoded);
+    assert_eq!(
+        decode::<Extensible>(&encoded, &Constraints::NONE).unwrap(),
+        value
+    );
+
+    // One extension addition, an open type of one octet
+    let extended = [0xC0, 0x05, 0x01, 0xFF, 0x02, 0x07, 0x80, 0x01, 0xAB];
+    assert_eq!(
+        decode::<Extensible>(&extended, &Constraints::NONE).unwrap(),
+        value
+    );
+    assert!(decode::<Extensible>(&extended[..8], &Constraints::NONE).is_err());
+
+    let unknown = [0x40, 0x05, 0x02, 0xFF];
+    assert_eq!(
+        decode::<Extensible>(&unknown, &Constraints::NONE)
+            .unwrap()
+            .shade,
+        Shade::Unknown
+    );
+    assert!(encode(&Shade::Unknown, &Constraints::NONE).is_err());
+}
@@ -0,0 +1,223 @@
+use crate::io;
+use crate::prelude::*;
+
+use crate::der::DER;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const LINE_LENGTH: usize = 64;
+
+/// A single PEM block
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    /// Label of the block, e.g. `CERTIFICATE`
+    pub label: String,
+    /// Decoded content
+    pub data: Vec<u8>,
+}
+
+impl Block {
+    /// Create a new block
+    pub fn new(label: &str, data: Vec<u8>) -> Block {
+        Block {
+            label: label.to_string(),
+            data,
+        }
+    }
+
+    /// Encode this block, wrapping the base64 text after 64 characters
+    pub fn encode(&self) -> String {
+        encode(&self.label, &self.data)
+    }
+}
+
+/// Encode `data` as a PEM block with the given label
+pub fn encode(label: &str, data: &[u8]) -> String {
+    let encoded = base64_encode(data);
+    let mut text = format!("-----BEGIN {}-----\n", label);
+    for line in encoded.as_bytes().chunks(LINE_LENGTH) {
+        // base64 output is always ascii
+        text.push_str(::core::str::from_utf8(line).unwrap());
+        text.push('\n');
+    }
+    text.push_str(&format!("-----END {}-----\n", label));
+    text
+}
+
+/// Decode the first PEM block in `text`
+pub fn decode(text: &str) -> io::Result<Block> {
+    Ok(decode_all(text)?.remove(0))
+}
+
+/// Decode all PEM blocks in `text`
+///
+/// Text outside of the blocks is ignored. It is an error if no block is found, if the labels of
+/// the BEGIN and END lines differ or if the base64 content is malformed.
+pub fn decode_all(text: &str) -> io::Result<Vec<Block>> {
+    let mut blocks = vec![];
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let label = match boundary(line, "BEGIN") {
+            Some(label) => label,
+            None => continue,
+        };
+        if !valid_label(label) {
+            return Err(invalid(format!("invalid PEM label \"{}\"", label)));
+        }
+        let mut body = String::new();
+        loop {
+            let line = lines
+                .next()
+                .ok_or_else(|| invalid(format!("missing END line for \"{}\"", label)))?;
+            if let Some(end) = boundary(line, "END") {
+                if end != label {
+                    return Err(invalid(format!(
+                        "PEM label mismatch: BEGIN \"{}\", END \"{}\"",
+                        label, end
+                    )));
+                }
+                break;
+            }
+            if boundary(line, "BEGIN").is_some() {
+                return Err(invalid(format!("missing END line for \"{}\"", label)));
+            }
+            body.push_str(line);
+        }
+        blocks.push(Block::new(label, base64_decode(&body)?));
+    }
+    if blocks.is_empty() {
+        return Err(invalid("no PEM block found".to_string()));
+    }
+    Ok(blocks)
+}
+
+/// PEM helpers for DER types
+///
+/// # Example
+/// ```
+/// use eagre_asn1::pem::{self, PEM};
+///
+/// let text = "Hello World".to_string().to_pem("MESSAGE").unwrap();
+/// assert_eq!(text, "-----BEGIN MESSAGE-----\nDAtIZWxsbyBXb3JsZA==\n-----END MESSAGE-----\n");
+/// assert_eq!(String::from_pem(&text, "MESSAGE").unwrap(), "Hello World");
+/// assert_eq!(pem::decode(&text).unwrap().label, "MESSAGE");
+/// ```
+pub trait PEM: DER {
+    /// Encode into a PEM block with the given label
+    fn to_pem(&self, label: &str) -> io::Result<String> {
+        Ok(encode(label, &self.der_bytes()?))
+    }
+
+    /// Decode from the first PEM block with the given label
+    fn from_pem(text: &str, label: &str) -> io::Result<Self> {
+        let block = decode_all(text)?
+            .into_iter()
+            .find(|b| b.label == label)
+            .ok_or_else(|| invalid(format!("no PEM block with label \"{}\"", label)))?;
+        Self::der_from_bytes(block.data)
+    }
+}
+
+impl<T: DER> PEM for T {}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Return the label of a BEGIN or END line
+fn boundary<'a>(line: &'a str, kind: &str) -> Option<&'a str> {
+    line.trim_end()
+        .strip_prefix("-----")?
+        .strip_prefix(kind)?
+        .strip_prefix(' ')?
+        .strip_suffix("-----")
+}
+
+fn valid_label(label: &str) -> bool {
+    let mut previous_separator = true;
+    for c in label.chars() {
+        let separator = c == '-' || c == ' ';
+        if (separator && previous_separator) || !(' '..='~').contains(&c) {
+            return false;
+        }
+        previous_separator = separator;
+    }
+    label.is_empty() || !previous_separator
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut encoded = String::new();
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn base64_decode(text: &str) -> io::Result<Vec<u8>> {
+    let chars: Vec<u8> = text.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    if !chars.chunks_exact(4).remainder().is_empty() {
+        return Err(invalid("base64 text has invalid length".to_string()));
+    }
+    let mut data = vec![];
+    for (i, quad) in chars.chunks(4).enumerate() {
+        let last = i == chars.len() / 4 - 1;
+        let padding = quad.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return Err(invalid("invalid base64 padding".to_string()));
+        }
+        let mut n = 0u32;
+        for c in quad[..4 - padding].iter() {
+            let value = BASE64
+                .iter()
+                .position(|b| b == c)
+                .ok_or_else(|| invalid(format!("invalid base64 character '{}'", *c as char)))?;
+            n = n << 6 | value as u32;
+        }
+        n <<= 6 * padding;
+        data.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8][..3 - padding]);
+    }
+    Ok(data)
+}
+
+#[test]
+fn decode_multiple_blocks() {
+    let text = "Subject: Foo
+-----BEGIN FIRST BLOCK-----
+AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8gISIjJCUmJygpKissLS4v
+MDE=
+-----END FIRST BLOCK-----
+-----BEGIN X509 CRL-----
+/w==
+-----END X509 CRL-----
+";
+    let blocks = decode_all(text).unwrap();
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].label, "FIRST BLOCK");
+    assert_eq!(blocks[0].data, (0..50).collect::<Vec<u8>>());
+    assert_eq!(&text[13..], blocks[0].encode() + &blocks[1].encode());
+    assert_eq!(blocks[1], Block::new("X509 CRL", vec![0xFF]));
+}
+
+#[test]
+fn decode_rejects_malformed() {
+    let texts = vec![
+        "-----BEGIN A-----\nAA==\n-----END B-----\n",
+        "-----BEGIN A-----\nAA==\n",
+        "-----BEGIN A-----\nAA=A\n-----END A-----\n",
+        "-----BEGIN A-----\nAA=\n-----END A-----\n",
+        "-----BEGIN -A-----\nAA==\n-----END -A-----\n",
+        "no block",
+    ];
+    for text in texts {
+        assert!(decode_all(text).is_err(), "{}", text);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::io;
+use crate::prelude::*;
+
+/// Bit level writer
+///
+/// Bits are written most significant bit first. A writer either produces the UNALIGNED or the
+/// ALIGNED variant of PER, which adds padding bits in front of some fields.
+#[derive(Clone, Debug, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    bits: usize,
+    aper: bool,
+}
+
+impl BitWriter {
+    /// Create a new empty writer for UPER
+    pub fn new() -> BitWriter {
+        BitWriter::default()
+    }
+
+    /// Create a new empty writer for APER
+    pub fn aper() -> BitWriter {
+        BitWriter {
+            aper: true,
+            ..BitWriter::default()
+        }
+    }
+
+    /// Return whether this writer produces the ALIGNED variant
+    pub fn is_aper(&self) -> bool {
+        self.aper
+    }
+
+    /// Pad with zero bits to the next octet boundary, does nothing in UPER
+    pub fn align(&mut self) {
+        if self.aper {
+            self.bits = self.bytes.len() * 8;
+        }
+    }
+
+    /// Number of bits written so far
+    pub fn len(&self) -> usize {
+        self.bits
+    }
+
+    /// Return whether nothing was written yet
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Write a single bit
+    pub fn write_bit(&mut self, bit: bool) {
+        if self.bits & 7 == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= 0x80 >> (self.bits % 8);
+        }
+        self.bits += 1;
+    }
+
+    /// Write the lowest `count` bits of `value`
+    pub fn write_bits(&mut self, value: u64, count: usize) {
+        for i in (0..count).rev() {
+            self.write_bit(i < 64 && (value >> i) & 1 == 1);
+        }
+    }
+
+    /// Write whole octets, which do not need to start at an octet boundary
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        if self.bits & 7 == 0 {
+            self.bytes.extend_from_slice(bytes);
+            self.bits += bytes.len() * 8;
+        } else {
+            for byte in bytes {
+                self.write_bits(u64::from(*byte), 8);
+            }
+        }
+    }
+
+    /// Return the written bits, padded with zero bits to a whole octet
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Bit level reader, the counterpart of `BitWriter`
+#[derive(Clone, Debug)]
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    aper: bool,
+}
+
+impl<'a> BitReader<'a> {
+    /// Create a UPER reader starting at the first bit of `bytes`
+    pub fn new(bytes: &'a [u8]) -> BitReader<'a> {
+        BitReader {
+            bytes,
+            pos: 0,
+            aper: false,
+        }
+    }
+
+    /// Create an APER reader starting at the first bit of `bytes`
+    pub fn aper(bytes: &'a [u8]) -> BitReader<'a> {
+        BitReader {
+            bytes,
+            pos: 0,
+            aper: true,
+        }
+    }
+
+    /// Return whether this reader expects the ALIGNED variant
+    pub fn is_aper(&self) -> bool {
+        self.aper
+    }
+
+    /// Skip the padding bits up to the next octet boundary, does nothing in UPER
+    pub fn align(&mut self) -> io::Result<()> {
+        if self.aper {
+            let padding = (8 - self.pos % 8) % 8;
+            self.check_remaining(padding)?;
+            self.pos += padding;
+        }
+        Ok(())
+    }
+
+    /// Number of bits read so far
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Number of bits left
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() * 8 - self.pos
+    }
+
+    fn check_remaining(&self, bits: usize) -> io::Result<()> {
+        if bits > self.remaining() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "unexpected end of PER data",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Read a single bit
+    pub fn read_bit(&mut self) -> io::Result<bool> {
+        self.check_remaining(1)?;
+        let bit = self.bytes[self.pos / 8] & (0x80 >> (self.pos % 8)) != 0;
+        self.pos += 1;
+        Ok(bit)
+    }
+
+    /// Read `count` bits, at most 64
+    pub fn read_bits(&mut self, count: usize) -> io::Result<u64> {
+        assert!(count <= 64);
+        self.check_remaining(count)?;
+        let mut value = 0;
+        for _ in 0..count {
+            value = value << 1 | u64::from(self.read_bit()?);
+        }
+        Ok(value)
+    }
+
+    /// Read `count` whole octets
+    pub fn read_bytes(&mut self, count: usize) -> io::Result<Vec<u8>> {
+        self.check_remaining(count.saturating_mul(8))?;
+        if self.pos & 7 == 0 {
+            let start = self.pos / 8;
+            self.pos += count * 8;
+            return Ok(self.bytes[start..start + count].to_vec());
+        }
+        (0..count)
+            .map(|_| self.read_bits(8).map(|b| b as u8))
+            .collect()
+    }
+}
+
+#[test]
+fn write_and_read_bits() {
+    let mut w = BitWriter::new();
+    w.write_bit(true);
+    w.write_bits(0b010, 3);
+    w.write_bytes(&[0xFF, 0x00]);
+    w.write_bits(0x3, 2);
+    assert_eq!(w.len(), 22);
+    let bytes = w.into_bytes();
+    assert_eq!(bytes, vec![0xAF, 0xF0, 0x0C]);
+
+    let mut r = BitReader::new(&bytes);
+    assert!(r.read_bit().unwrap());
+    assert_eq!(r.read_bits(3).unwrap(), 0b010);
+    assert_eq!(r.read_bytes(2).unwrap(), vec![0xFF, 0x00]);
+    assert_eq!(r.read_bits(2).unwrap(), 0x3);
+    assert_eq!(r.remaining(), 2);
+    assert!(r.read_bits(3).is_err());
+    assert!(r.read_bytes(usize::MAX).is_err());
+}
+
+#[test]
+fn align_only_in_aper() {
+    for aper in [false, true] {
+        let mut w = if aper {
+            BitWriter::aper()
+        } else {
+            BitWriter::new()
+        };
+        w.write_bit(true);
+        w.align();
+        w.write_bits(0x3, 2);
+        w.align();
+        let bytes = w.into_bytes();
+
+        let mut r = if aper {
+            BitReader::aper(&bytes)
+        } else {
+            BitReader::new(&bytes)
+        };
+        assert!(r.read_bit().unwrap());
+        r.align().unwrap();
+        assert_eq!(r.read_bits(2).unwrap(), 0x3);
+        if aper {
+            assert_eq!(bytes, vec![0x80, 0xC0]);
+            assert_eq!(r.position(), 10);
+        } else {
+            assert_eq!(bytes, vec![0xE0]);
+            assert_eq!(r.position(), 3);
+        }
+    }
+}
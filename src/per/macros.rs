@@ -0,0 +1,191 @@
+/// PER implementation for sequences, used by `der_sequence!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! per_sequence {
+    ($struct_name:ident $(<$lt:lifetime>)? [$extensible:tt $(, $unknown:ident)?] : $($field_name:ident $(BY $id:ident)? : $field_type:ty = [$($c:tt)*]),+) => {
+        impl $crate::per::PER for $struct_name $(<$lt>)? {
+            fn per_encode(&self, w: &mut $crate::per::BitWriter, _: &$crate::constraints::Constraints) -> $crate::io::Result<()> {
+                // Extensible sequences never carry extension additions of their own
+                let extensible: bool = $extensible;
+                if extensible {
+                    w.write_bit(false);
+                }
+                $(
+                    if <$field_type as $crate::per::PER>::per_absent().is_some() {
+                        w.write_bit($crate::per::PER::per_present(&self.$field_name));
+                    }
+                )+
+                $(
+                    if $crate::per::PER::per_present(&self.$field_name) {
+                        $crate::der_constraints!(@check &self.$field_name, stringify!($field_name), stringify!($struct_name); $($c)*);
+                        $crate::constraints::in_field($crate::per::PER::per_encode(&self.$field_name, w, &$crate::der_constraints!($($c)*)),
+                                                      stringify!($field_name), stringify!($struct_name))?;
+                    }
+                )+
+                Ok(())
+            }
+
+            fn per_decode(r: &mut $crate::per::BitReader, _: &$crate::constraints::Constraints) -> $crate::io::Result<Self> {
+                let extensible: bool = $extensible;
+                let extended = extensible && r.read_bit()?;
+                let mut present = $crate::prelude::vec![$(<$field_type as $crate::per::PER>::per_absent().is_none()),+];
+                for p in present.iter_mut().filter(|p| !**p) {
+                    *p = r.read_bit()?;
+                }
+                let mut present = present.into_iter();
+                $(
+                    let $field_name = match (present.next(), <$field_type as $crate::per::PER>::per_absent()) {
+                        (Some(false), Some(absent)) => absent,
+                        _ => $crate::constraints::in_field(<$field_type as $crate::per::PER>::per_decode(r, &$crate::der_constraints!($($c)*)),
+                                                           stringify!($field_name), stringify!($struct_name))?,
+                    };
+                    $crate::der_constraints!(@check &$field_name, stringify!($field_name), stringify!($struct_name); $($c)*);
+                    $(let $field_name = $crate::types::OpenType::open_resolve($field_name, &$id)?;)?
+                )+
+                if extended {
+                    $crate::per::skip_extensions(r)?;
+                }
+                Ok($struct_name {
+                    $(
+                        $field_name: $field_name,
+                    )+
+                    $(
+                        $unknown: $crate::prelude::Vec::new(),
+                    )?
+                })
+            }
+        }
+    };
+}
+
+/// PER implementation for choices, used by `der_choice!`
+///
+/// Alternatives are numbered in the canonical order of their tags.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! per_choice {
+    ($choice_name:ident [$extensible:tt $(, $unknown:ident)?] : $($variant_name:ident [$($shape:tt)*] : $tagtype:ident $(TAG $tagclass:ident $tagval:expr ;)* TYPE $variant_type:ty = [$($c:tt)*]),+) => {
+        impl $choice_name {
+            fn per_canonical_index(position: usize) -> u64 {
+                let tags = [$(
+                    match stringify!($tagtype) {
+                        // An untagged CHOICE is ordered by the smallest tag of its alternatives
+                        "NOTAG" => <$variant_type as $crate::der::DER>::der_tags().into_iter()
+                            .map(|(class, tag)| (class as u32, tag))
+                            .min()
+                            .unwrap_or((0, 0)),
+                        $("EXPLICIT" | "IMPLICIT" | "AUTOMATIC" => (match stringify!($tagclass) {
+                                                          "UNIVERSAL" => $crate::der::Class::Universal,
+                                                          "APPLICATION" => $crate::der::Class::Application,
+                                                          "CONTEXT" => $crate::der::Class::ContextSpecific,
+                                                          "PRIVATE" => $crate::der::Class::Private,
+                                                          _ => unreachable!(),
+                                                      } as u32, $tagval),)*
+                        _ => unreachable!(),
+                    }
+                ),+];
+                tags.iter().filter(|tag| **tag < tags[position]).count() as u64
+            }
+        }
+
+        impl $crate::per::PER for $choice_name {
+            fn per_encode(&self, w: &mut $crate::per::BitWriter, _: &$crate::constraints::Constraints) -> $crate::io::Result<()> {
+                let names = [$(stringify!($variant_name)),+];
+                let name = match self {
+                    $($crate::der_choice!(@ignore $choice_name $variant_name [$($shape)*]) => stringify!($variant_name),)+
+                    $(&$choice_name::$unknown(_) => return Err($crate::der::unknown_alternative(stringify!($choice_name))),)?
+                };
+                let position = names.iter().position(|n| *n == name).unwrap();
+                let extensible: bool = $extensible;
+                if extensible {
+                    w.write_bit(false);
+                }
+                $crate::per::encode_constrained_whole_number(w, Self::per_canonical_index(position), names.len() as u64 - 1);
+                match self {
+                    $($crate::der_choice!(@pattern $choice_name $variant_name [$($shape)*] payload) => {
+                        let val = $crate::der_choice!(@payload $variant_name [$($shape)*] payload);
+                        $crate::der_constraints!(@check val, stringify!($variant_name), stringify!($choice_name); $($c)*);
+                        $crate::constraints::in_field($crate::per::PER::per_encode(val, w, &$crate::der_constraints!($($c)*)),
+                                                      stringify!($variant_name), stringify!($choice_name))
+                    },)+
+                    $(&$choice_name::$unknown(_) => unreachable!(),)?
+                }
+            }
+
+            fn per_decode(r: &mut $crate::per::BitReader, _: &$crate::constraints::Constraints) -> $crate::io::Result<Self> {
+                let names = [$(stringify!($variant_name)),+];
+                let extensible: bool = $extensible;
+                if extensible && r.read_bit()? {
+                    let index = $crate::per::decode_normally_small(r)?;
+                    return Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                        $crate::prelude::format!("unknown extension alternative {} in {}", index, stringify!($choice_name))));
+                }
+                let index = $crate::per::decode_constrained_whole_number(r, names.len() as u64 - 1)?;
+                let name = (0..names.len())
+                    .find(|p| Self::per_canonical_index(*p) == index)
+                    .map(|p| names[p])
+                    .ok_or_else(|| $crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                        $crate::prelude::format!("invalid alternative index {} in {}", index, stringify!($choice_name))))?;
+                $(
+                    if name == stringify!($variant_name) {
+                        let value = $crate::constraints::in_field($crate::per::PER::per_decode(r, &$crate::der_constraints!($($c)*)),
+                                                                  stringify!($variant_name), stringify!($choice_name))?;
+                        $crate::der_constraints!(@check &value, stringify!($variant_name), stringify!($choice_name); $($c)*);
+                        return Ok($crate::der_choice!(@construct $choice_name $variant_name [$($shape)*] value));
+                    }
+                )+
+                unreachable!()
+            }
+        }
+    };
+}
+
+/// PER implementation for enumerations, used by `der_enumerated!`
+///
+/// Values are numbered in ascending order of their discriminants.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! per_enumerated {
+    ($enum_name:ident [$extensible:tt $(, $unknown:ident)? $(; $other:ident)?], $($enum_variant:ident = $value:expr),+) => {
+        impl $crate::per::PER for $enum_name {
+            fn per_encode(&self, w: &mut $crate::per::BitWriter, _: &$crate::constraints::Constraints) -> $crate::io::Result<()> {
+                let mut values: $crate::prelude::Vec<i64> = $crate::prelude::vec![$($value),+];
+                values.sort_unstable();
+                let value = match self {
+                    $(&$enum_name::$enum_variant => $value,)+
+                    $(&$enum_name::$unknown => return Err($crate::der::unknown_value(stringify!($enum_name))),)?
+                    $(&$enum_name::$other(_) => return Err($crate::der::unknown_value(stringify!($enum_name))),)?
+                };
+                let index = values.iter().position(|v| *v == value).unwrap();
+                let extensible: bool = $extensible;
+                if extensible {
+                    w.write_bit(false);
+                }
+                $crate::per::encode_constrained_whole_number(w, index as u64, values.len() as u64 - 1);
+                Ok(())
+            }
+
+            fn per_decode(r: &mut $crate::per::BitReader, _: &$crate::constraints::Constraints) -> $crate::io::Result<Self> {
+                let mut values: $crate::prelude::Vec<i64> = $crate::prelude::vec![$($value),+];
+                values.sort_unstable();
+                let extensible: bool = $extensible;
+                if extensible && r.read_bit()? {
+                    // Values added by a newer version map to the unknown variant
+                    let index = $crate::per::decode_normally_small(r)?;
+                    let unknown: Option<Self> = None $(.or(Some($enum_name::$unknown)))?;
+                    return unknown.ok_or_else(|| $crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                        $crate::prelude::format!("unknown extension value {} in {}", index, stringify!($enum_name))));
+                }
+                let index = $crate::per::decode_constrained_whole_number(r, values.len() as u64 - 1)?;
+                let value = *values.get(index as usize).ok_or_else(|| $crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                    $crate::prelude::format!("invalid enumeration index {} in {}", index, stringify!($enum_name))))?;
+                $(
+                    if value == $value {
+                        return Ok($enum_name::$enum_variant);
+                    }
+                )+
+                unreachable!()
+            }
+        }
+    };
+}
@@ -0,0 +1,749 @@
+use crate::constraints::Constraints;
+use crate::der::{into_array, Component};
+use crate::io;
+use crate::prelude::*;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::ops::Range;
+
+/// Bit level reader and writer
+pub mod bits;
+#[doc(hidden)]
+#[macro_use]
+pub mod macros;
+
+#[cfg(test)]
+mod test;
+
+pub use self::bits::{BitReader, BitWriter};
+
+/// The trait for PER encoding
+///
+/// Unlike DER, PER depends on the constraints of a value, so they are passed to every call. Types
+/// implementing `DER` through `der_sequence!`, `der_choice!` or `der_enumerated!` implement this
+/// trait when `PER` is listed in `WITH(...)` after the type name: sequences start with a bitmap of
+/// their present `OPTIONAL` components, choices with the index of the alternative and enumerations are encoded as their index.
+///
+/// Both variants of PER are supported: `uper_bytes()` produces UNALIGNED PER, `aper_bytes()`
+/// ALIGNED PER, which pads lengths, large constrained numbers and most strings to octet
+/// boundaries and rounds the bits per character up to a power of two.
+///
+/// # Example
+/// ```
+/// # #[macro_use]
+/// # extern crate eagre_asn1;
+/// use eagre_asn1::per::PER;
+///
+/// # #[derive(Debug, PartialEq)]
+/// struct Position {
+///     pub valid: bool,
+///     pub speed: Option<i32>,
+/// }
+///
+/// der_sequence! {
+///     Position WITH(PER):
+///         valid: NOTAG TYPE bool,
+///         speed: IMPLICIT TAG CONTEXT 0; TYPE Option<i32>,
+/// }
+///
+/// # fn main() {
+/// let position = Position { valid: true, speed: None };
+/// let encoded = position.uper_bytes().unwrap();
+/// // One bit for the bitmap, one for the boolean
+/// assert_eq!(encoded, vec![0b0100_0000]);
+/// assert_eq!(position, Position::uper_from_bytes(&encoded).unwrap());
+/// assert_eq!(position.aper_bytes().unwrap(), encoded);
+/// # }
+/// ```
+pub trait PER: Sized {
+    /// Encode this value with constraints `c`
+    fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()>;
+    /// Decode a value with constraints `c`
+    fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self>;
+    /// Value to use if this type is missing from a sequence, `Some` only for optional types
+    fn per_absent() -> Option<Self> {
+        None
+    }
+    /// Return whether this value is encoded at all, `false` only for absent optional values
+    fn per_present(&self) -> bool {
+        true
+    }
+    /// Return the complete UPER encoding
+    fn uper_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut w = BitWriter::new();
+        self.per_encode(&mut w, &Constraints::NONE)?;
+        Ok(complete_encoding(w))
+    }
+    /// Decode a complete UPER encoding
+    fn uper_from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = BitReader::new(bytes);
+        let value = Self::per_decode(&mut r, &Constraints::NONE)?;
+        check_complete(&r, bytes)?;
+        Ok(value)
+    }
+    /// Return the complete APER encoding
+    fn aper_bytes(&self) -> io::Result<Vec<u8>> {
+        let mut w = BitWriter::aper();
+        self.per_encode(&mut w, &Constraints::NONE)?;
+        Ok(complete_encoding(w))
+    }
+    /// Decode a complete APER encoding
+    fn aper_from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = BitReader::aper(bytes);
+        let value = Self::per_decode(&mut r, &Constraints::NONE)?;
+        check_complete(&r, bytes)?;
+        Ok(value)
+    }
+}
+
+/// An empty complete encoding is replaced by a single zero octet
+fn complete_encoding(w: BitWriter) -> Vec<u8> {
+    let mut bytes = w.into_bytes();
+    if bytes.is_empty() {
+        bytes.push(0);
+    }
+    bytes
+}
+
+fn check_complete(r: &BitReader, bytes: &[u8]) -> io::Result<()> {
+    if r.remaining() >= 8 && !(r.position() == 0 && bytes == [0]) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "trailing data after PER encoding",
+        ));
+    }
+    Ok(())
+}
+
+fn bits_for(max_offset: u64) -> usize {
+    64 - max_offset.leading_zeros() as usize
+}
+
+fn octets_for(value: u64) -> usize {
+    bits_for(value).div_ceil(8).max(1)
+}
+
+/// Encode `offset` as constrained whole number in the range `0..=max_offset`
+///
+/// In APER, ranges of 256 values and above are octet-aligned and use whole octets. Ranges above
+/// 64K are prefixed with the number of octets.
+pub fn encode_constrained_whole_number(w: &mut BitWriter, offset: u64, max_offset: u64) {
+    if !w.is_aper() || max_offset < 255 {
+        w.write_bits(offset, bits_for(max_offset));
+    } else if max_offset < 65536 {
+        w.align();
+        w.write_bits(offset, octets_for(max_offset) * 8);
+    } else {
+        let octets = octets_for(offset);
+        w.write_bits(
+            (octets - 1) as u64,
+            bits_for((octets_for(max_offset) - 1) as u64),
+        );
+        w.align();
+        w.write_bits(offset, octets * 8);
+    }
+}
+
+/// Decode a constrained whole number in the range `0..=max_offset`
+pub fn decode_constrained_whole_number(r: &mut BitReader, max_offset: u64) -> io::Result<u64> {
+    let offset = if !r.is_aper() || max_offset < 255 {
+        r.read_bits(bits_for(max_offset))?
+    } else if max_offset < 65536 {
+        r.align()?;
+        r.read_bits(octets_for(max_offset) * 8)?
+    } else {
+        let octets = r.read_bits(bits_for((octets_for(max_offset) - 1) as u64))? as usize + 1;
+        r.align()?;
+        r.read_bits(octets * 8)?
+    };
+    if offset > max_offset {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "constrained whole number out of range",
+        ));
+    }
+    Ok(offset)
+}
+
+const FRAGMENT: usize = 16384;
+
+/// Encode a length determinant for `count` items
+///
+/// `items` is called to encode consecutive ranges of the items, more than once if the length has
+/// to be fragmented. If the size constraint has an upper bound below 64K the length is encoded as
+/// constrained whole number, or not at all for fixed sizes. Otherwise the length octets are
+/// octet-aligned in APER. `align_items` tells whether the items are octet-aligned in APER as well.
+pub fn encode_length<F>(
+    w: &mut BitWriter,
+    count: usize,
+    c: &Constraints,
+    align_items: bool,
+    mut items: F,
+) -> io::Result<()>
+where
+    F: FnMut(&mut BitWriter, Range<usize>) -> io::Result<()>,
+{
+    c.check_size(count)?;
+    let lb = c.size_min.unwrap_or(0);
+    if let Some(ub) = c.size_max.filter(|ub| *ub < 65536) {
+        encode_constrained_whole_number(w, (count - lb) as u64, (ub - lb) as u64);
+        if align_items {
+            w.align();
+        }
+        return items(w, 0..count);
+    }
+    let mut start = 0;
+    loop {
+        w.align();
+        let remaining = count - start;
+        if remaining < 128 {
+            w.write_bits(remaining as u64, 8);
+            return items(w, start..count);
+        } else if remaining < FRAGMENT {
+            w.write_bits(0x8000 | remaining as u64, 16);
+            return items(w, start..count);
+        }
+        let m = (remaining / FRAGMENT).min(4);
+        w.write_bits(0xC0 | m as u64, 8);
+        items(w, start..start + m * FRAGMENT)?;
+        start += m * FRAGMENT;
+    }
+}
+
+/// Decode a length determinant, the counterpart of `encode_length()`
+///
+/// `items` is called with the number of items to decode next. Returns the total number of items.
+pub fn decode_length<F>(
+    r: &mut BitReader,
+    c: &Constraints,
+    align_items: bool,
+    mut items: F,
+) -> io::Result<usize>
+where
+    F: FnMut(&mut BitReader, usize) -> io::Result<()>,
+{
+    let lb = c.size_min.unwrap_or(0);
+    if let Some(ub) = c.size_max.filter(|ub| *ub < 65536) {
+        if ub < lb {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "empty size constraint",
+            ));
+        }
+        let count = lb + decode_constrained_whole_number(r, (ub - lb) as u64)? as usize;
+        if align_items {
+            r.align()?;
+        }
+        items(r, count)?;
+        return Ok(count);
+    }
+    let mut count = 0;
+    loop {
+        r.align()?;
+        let first = r.read_bits(8)? as usize;
+        let n = if first & 0x80 == 0 {
+            first
+        } else if first & 0x40 == 0 {
+            (first & 0x3F) << 8 | r.read_bits(8)? as usize
+        } else {
+            match first & 0x3F {
+                m @ 1..=4 => m * FRAGMENT,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "invalid length fragment",
+                    ))
+                }
+            }
+        };
+        items(r, n)?;
+        count += n;
+        if first & 0xC0 != 0xC0 {
+            break;
+        }
+    }
+    c.check_size(count)?;
+    Ok(count)
+}
+
+/// Encode octets with an unconstrained length determinant
+pub fn encode_octets(w: &mut BitWriter, bytes: &[u8]) -> io::Result<()> {
+    encode_length(w, bytes.len(), &Constraints::NONE, true, |w, range| {
+        w.write_bytes(&bytes[range]);
+        Ok(())
+    })
+}
+
+/// Decode octets with an unconstrained length determinant
+pub fn decode_octets(r: &mut BitReader) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    decode_length(r, &Constraints::NONE, true, |r, n| {
+        bytes.extend(r.read_bytes(n)?);
+        Ok(())
+    })?;
+    Ok(bytes)
+}
+
+/// Encode a normally small non-negative whole number, as used for extension indices
+///
+/// Numbers up to 63 take a zero bit and six bits, larger ones a one bit and the octets of the
+/// number with a length determinant.
+pub fn encode_normally_small(w: &mut BitWriter, n: u64) -> io::Result<()> {
+    if n < 64 {
+        w.write_bit(false);
+        w.write_bits(n, 6);
+        return Ok(());
+    }
+    w.write_bit(true);
+    let octets = octets_for(n);
+    encode_octets(w, &n.to_be_bytes()[8 - octets..])
+}
+
+/// Decode a normally small non-negative whole number
+pub fn decode_normally_small(r: &mut BitReader) -> io::Result<u64> {
+    if !r.read_bit()? {
+        return r.read_bits(6);
+    }
+    let bytes = decode_octets(r)?;
+    if bytes.is_empty() || bytes.len() > 8 {
+        return Err(integer_error());
+    }
+    Ok(bytes.iter().fold(0, |n, b| n << 8 | u64::from(*b)))
+}
+
+/// Skip the extension additions of a sequence whose extension bit is set
+///
+/// They are preceded by a bitmap of the present additions, each of which is an open type.
+pub fn skip_extensions(r: &mut BitReader) -> io::Result<()> {
+    let count = decode_normally_small(r)? + 1;
+    let mut present = 0;
+    for _ in 0..count {
+        if r.read_bit()? {
+            present += 1;
+        }
+    }
+    for _ in 0..present {
+        decode_octets(r)?;
+    }
+    Ok(())
+}
+
+fn integer_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "integer too big")
+}
+
+/// Encode an `INTEGER` with value range constraints
+///
+/// Fully constrained values are encoded as constrained whole number, values with only a lower
+/// bound as non-negative offset from it and everything else as two's complement.
+pub fn encode_integer(w: &mut BitWriter, value: i64, c: &Constraints) -> io::Result<()> {
+    c.check_value(value)?;
+    match (c.min, c.max) {
+        (Some(lb), Some(ub)) => {
+            encode_constrained_whole_number(
+                w,
+                value.wrapping_sub(lb) as u64,
+                ub.wrapping_sub(lb) as u64,
+            );
+            Ok(())
+        }
+        (Some(lb), None) => {
+            let offset = value.wrapping_sub(lb) as u64;
+            let length = bits_for(offset).div_ceil(8).max(1);
+            encode_octets(w, &offset.to_be_bytes()[8 - length..])
+        }
+        _ => {
+            let length = (65 - value.max(!value).leading_zeros() as usize).div_ceil(8);
+            encode_octets(w, &value.to_be_bytes()[8 - length..])
+        }
+    }
+}
+
+/// Decode an `INTEGER` with value range constraints
+pub fn decode_integer(r: &mut BitReader, c: &Constraints) -> io::Result<i64> {
+    let value = match (c.min, c.max) {
+        (Some(lb), Some(ub)) => {
+            if ub < lb {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "empty value constraint",
+                ));
+            }
+            let offset = decode_constrained_whole_number(r, ub.wrapping_sub(lb) as u64)?;
+            lb.wrapping_add(offset as i64)
+        }
+        (Some(lb), None) => {
+            let bytes = decode_octets(r)?;
+            if bytes.is_empty() || bytes.len() > 8 {
+                return Err(integer_error());
+            }
+            let offset = bytes.iter().fold(0u64, |v, b| v << 8 | u64::from(*b));
+            let value = i128::from(lb) + i128::from(offset);
+            if value > i128::from(i64::MAX) {
+                return Err(integer_error());
+            }
+            value as i64
+        }
+        _ => {
+            let bytes = decode_octets(r)?;
+            if bytes.is_empty() || bytes.len() > 8 {
+                return Err(integer_error());
+            }
+            let sign = if bytes[0] & 0x80 != 0 { -1i64 } else { 0 };
+            bytes.iter().fold(sign, |v, b| v << 8 | i64::from(*b))
+        }
+    };
+    c.check_value(value)?;
+    Ok(value)
+}
+
+/// Effective character set of a known-multiplier character string type
+///
+/// Characters are encoded with the minimum number of bits for the size of the character set.
+/// If the largest character fits into that number of bits the character code is used directly,
+/// otherwise the index of the character in the set.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Alphabet {
+    /// All characters with codes in the range
+    Range(u32, u32),
+    /// The listed characters, sorted
+    Chars(Vec<char>),
+}
+
+impl Alphabet {
+    /// Create alphabet from the characters in `chars`
+    pub fn chars(chars: &str) -> Alphabet {
+        let mut chars: Vec<char> = chars.chars().collect();
+        chars.sort_unstable();
+        chars.dedup();
+        Alphabet::Chars(chars)
+    }
+
+    fn max_index(&self) -> u64 {
+        match self {
+            Alphabet::Range(first, last) => u64::from(last - first),
+            Alphabet::Chars(chars) => chars.len().saturating_sub(1) as u64,
+        }
+    }
+
+    fn max_code(&self) -> u32 {
+        match self {
+            Alphabet::Range(_, last) => *last,
+            Alphabet::Chars(chars) => chars.last().map_or(0, |c| *c as u32),
+        }
+    }
+
+    /// Bits per character, rounded up to a power of two in APER
+    fn bits(&self, aper: bool) -> usize {
+        let bits = bits_for(self.max_index());
+        if aper {
+            bits.next_power_of_two()
+        } else {
+            bits
+        }
+    }
+
+    fn direct(&self, aper: bool) -> bool {
+        u64::from(self.max_code()) < 1 << self.bits(aper)
+    }
+
+    /// Octets per character in the octet-based encodings like OER
+    pub fn octets(&self) -> usize {
+        match self.max_code() {
+            0..=0xFF => 1,
+            0x100..=0xFFFF => 2,
+            _ => 4,
+        }
+    }
+
+    /// Return whether `c` is part of the alphabet
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            Alphabet::Range(first, last) => (*first..=*last).contains(&(c as u32)),
+            Alphabet::Chars(chars) => chars.binary_search(&c).is_ok(),
+        }
+    }
+
+    fn value(&self, c: char, aper: bool) -> u64 {
+        match self {
+            _ if self.direct(aper) => u64::from(c as u32),
+            Alphabet::Range(first, _) => u64::from(c as u32 - first),
+            Alphabet::Chars(chars) => chars.binary_search(&c).unwrap_or(0) as u64,
+        }
+    }
+
+    fn char(&self, value: u64, aper: bool) -> Option<char> {
+        let code = match self {
+            _ if self.direct(aper) => value as u32,
+            Alphabet::Range(first, _) => first + value as u32,
+            Alphabet::Chars(chars) => return chars.get(value as usize).cloned(),
+        };
+        ::core::char::from_u32(code).filter(|c| self.contains(*c))
+    }
+}
+
+/// Return whether string items of `bits` bits each are octet-aligned in APER
+///
+/// Only fixed-size strings of up to two octets are left unaligned.
+pub fn align_items(c: &Constraints, bits: usize) -> bool {
+    match (c.size_min, c.size_max) {
+        (Some(min), Some(max)) if min == max => max * bits > 16,
+        _ => true,
+    }
+}
+
+/// Known-multiplier strings are also unaligned if their upper bound is below two octets
+fn align_chars(c: &Constraints, bits: usize) -> bool {
+    match (c.size_min, c.size_max) {
+        (Some(min), Some(max)) if min == max => max * bits > 16,
+        (_, Some(max)) => max * bits >= 16,
+        _ => true,
+    }
+}
+
+/// Encode a known-multiplier character string, `name` is used in error messages
+pub fn encode_chars(
+    w: &mut BitWriter,
+    s: &str,
+    c: &Constraints,
+    alphabet: &Alphabet,
+    name: &str,
+) -> io::Result<()> {
+    let chars: Vec<char> = s.chars().collect();
+    if let Some(bad) = chars.iter().find(|ch| !alphabet.contains(**ch)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid character {:?} in {}", bad, name),
+        ));
+    }
+    let aper = w.is_aper();
+    let bits = alphabet.bits(aper);
+    encode_length(w, chars.len(), c, align_chars(c, bits), |w, range| {
+        for ch in &chars[range] {
+            w.write_bits(alphabet.value(*ch, aper), bits);
+        }
+        Ok(())
+    })
+}
+
+/// Decode a known-multiplier character string
+pub fn decode_chars(
+    r: &mut BitReader,
+    c: &Constraints,
+    alphabet: &Alphabet,
+    name: &str,
+) -> io::Result<String> {
+    let mut s = String::new();
+    let aper = r.is_aper();
+    let bits = alphabet.bits(aper);
+    decode_length(r, c, align_chars(c, bits), |r, n| {
+        for _ in 0..n {
+            let value = r.read_bits(bits)?;
+            s.push(alphabet.char(value, aper).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid character in {}", name),
+                )
+            })?);
+        }
+        Ok(())
+    })?;
+    Ok(s)
+}
+
+impl PER for bool {
+    fn per_encode(&self, w: &mut BitWriter, _: &Constraints) -> io::Result<()> {
+        w.write_bit(*self);
+        Ok(())
+    }
+
+    fn per_decode(r: &mut BitReader, _: &Constraints) -> io::Result<Self> {
+        r.read_bit()
+    }
+}
+
+impl PER for i32 {
+    fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+        encode_integer(w, i64::from(*self), c)
+    }
+
+    fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self> {
+        let value = decode_integer(r, c)?;
+        if value < i64::from(i32::MIN) || value > i64::from(i32::MAX) {
+            return Err(integer_error());
+        }
+        Ok(value as i32)
+    }
+}
+
+/// `UTF8String` is not a known-multiplier type, its size constraint is not PER-visible
+impl PER for String {
+    fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+        c.check_size(self.chars().count())?;
+        encode_octets(w, self.as_bytes())
+    }
+
+    fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self> {
+        let s = String::from_utf8(decode_octets(r)?)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid UTF8String"))?;
+        c.check_size(s.chars().count())?;
+        Ok(s)
+    }
+}
+
+impl PER for Vec<u8> {
+    fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+        encode_length(w, self.len(), c, align_items(c, 8), |w, range| {
+            w.write_bytes(&self[range]);
+            Ok(())
+        })
+    }
+
+    fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self> {
+        let mut bytes = vec![];
+        decode_length(r, c, align_items(c, 8), |r, n| {
+            bytes.extend(r.read_bytes(n)?);
+            Ok(())
+        })?;
+        Ok(bytes)
+    }
+}
+
+/// Items of a `SEQUENCE OF` are not constrained
+impl<T: PER> PER for Vec<T> {
+    fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+        encode_length(w, self.len(), c, false, |w, range| {
+            for item in &self[range] {
+                item.per_encode(w, &Constraints::NONE)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self> {
+        let mut vector = vec![];
+        decode_length(r, c, false, |r, n| {
+            for _ in 0..n {
+                vector.push(T::per_decode(r, &Constraints::NONE)?);
+            }
+            Ok(())
+        })?;
+        Ok(vector)
+    }
+}
+
+/// `OCTET STRING SIZE(N)`, encoded without a length
+impl<const N: usize> PER for [u8; N] {
+    fn per_encode(&self, w: &mut BitWriter, _: &Constraints) -> io::Result<()> {
+        let c = Constraints::size(N, N);
+        encode_length(w, N, &c, align_items(&c, 8), |w, _| {
+            w.write_bytes(self);
+            Ok(())
+        })
+    }
+
+    fn per_decode(r: &mut BitReader, _: &Constraints) -> io::Result<Self> {
+        let c = Constraints::size(N, N);
+        let mut bytes = vec![];
+        decode_length(r, &c, align_items(&c, 8), |r, n| {
+            bytes.extend(r.read_bytes(n)?);
+            Ok(())
+        })?;
+        into_array(bytes)
+    }
+}
+
+/// `SEQUENCE SIZE(N) OF`, encoded without a length
+impl<T: PER, const N: usize> PER for [T; N] {
+    fn per_encode(&self, w: &mut BitWriter, _: &Constraints) -> io::Result<()> {
+        encode_length(w, N, &Constraints::size(N, N), false, |w, range| {
+            for item in &self[range] {
+                item.per_encode(w, &Constraints::NONE)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn per_decode(r: &mut BitReader, _: &Constraints) -> io::Result<Self> {
+        let mut vector = vec![];
+        decode_length(r, &Constraints::size(N, N), false, |r, n| {
+            for _ in 0..n {
+                vector.push(T::per_decode(r, &Constraints::NONE)?);
+            }
+            Ok(())
+        })?;
+        into_array(vector)
+    }
+}
+
+/// Implement `PER` for tuples, which are encoded like a `SEQUENCE` without optional components
+macro_rules! per_tuple {
+    () => {};
+    (($first:ident $first_var:ident) $(($ty:ident $var:ident))*) => {
+        impl<$first: PER, $($ty: PER),*> PER for ($first, $($ty,)*) {
+            fn per_encode(&self, w: &mut BitWriter, _: &Constraints) -> io::Result<()> {
+                let ($first_var, $($var,)*) = self;
+                $first_var.per_encode(w, &Constraints::NONE)?;
+                $($var.per_encode(w, &Constraints::NONE)?;)*
+                Ok(())
+            }
+
+            fn per_decode(r: &mut BitReader, _: &Constraints) -> io::Result<Self> {
+                let $first_var = $first::per_decode(r, &Constraints::NONE)?;
+                $(let $var = $ty::per_decode(r, &Constraints::NONE)?;)*
+                Ok(($first_var, $($var,)*))
+            }
+        }
+
+        per_tuple!($(($ty $var))*);
+    };
+}
+
+per_tuple!((A a) (B b) (C c) (D d) (E e) (F f) (G g) (H h) (I i) (J j) (K k) (L l));
+
+/// Implement `PER` for smart pointers, which are encoded like the value they point to
+macro_rules! per_pointer {
+    ($($pointer:ident $(<$lt:lifetime>)?),+) => {$(
+        impl<T: PER> PER for $pointer<$($lt,)? T> {
+            fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+                (**self).per_encode(w, c)
+            }
+
+            fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self> {
+                T::per_decode(r, c).map($pointer::new)
+            }
+
+            fn per_absent() -> Option<Self> {
+                T::per_absent().map($pointer::new)
+            }
+
+            fn per_present(&self) -> bool {
+                (**self).per_present()
+            }
+        }
+    )+};
+}
+
+per_pointer!(Box, Rc, Arc, Component<'_>);
+
+/// Absent values are only recorded in the bitmap of the enclosing sequence
+impl<T: PER> PER for Option<T> {
+    fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+        match self {
+            Some(value) => value.per_encode(w, c),
+            None => Ok(()),
+        }
+    }
+
+    fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self> {
+        T::per_decode(r, c).map(Some)
+    }
+
+    fn per_absent() -> Option<Self> {
+        Some(None)
+    }
+
+    fn per_present(&self) -> bool {
+        self.is_some()
+    }
+}
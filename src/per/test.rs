@@ -0,0 +1,492 @@
+use super::*;
+use crate::types::{BitString, IA5String, Null, NumericString, PrintableString};
+
+#[derive(Debug, PartialEq)]
+struct Message {
+    pub id: i32,
+    pub flag: bool,
+    pub name: Option<String>,
+    pub kind: Kind,
+    pub payload: Payload,
+    pub items: Vec<bool>,
+}
+
+der_sequence! {Message WITH(PER):
+    id: NOTAG TYPE i32,
+    flag: NOTAG TYPE bool,
+    name: IMPLICIT TAG CONTEXT 0; TYPE Option<String>,
+    kind: NOTAG TYPE Kind,
+    payload: EXPLICIT TAG CONTEXT 1; TYPE Payload,
+    items: NOTAG TYPE Vec<bool>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Low = 5,
+    High = 1,
+    Mid = 3,
+}
+
+der_enumerated!(Kind WITH(PER), Low, High, Mid);
+
+#[derive(Debug, PartialEq)]
+enum Payload {
+    Empty(Null),
+    Data(Vec<u8>),
+    Number(i32),
+}
+
+der_choice! {Payload WITH(PER):
+    Empty: IMPLICIT TAG CONTEXT 2; TYPE Null,
+    Data: IMPLICIT TAG CONTEXT 0; TYPE Vec<u8>,
+    Number: IMPLICIT TAG CONTEXT 1; TYPE i32,
+}
+
+/// Pack a string of '0' and '1' into octets, ignoring spaces
+fn bits(pattern: &str) -> Vec<u8> {
+    let mut w = BitWriter::new();
+    for c in pattern.chars().filter(|c| *c != ' ') {
+        w.write_bit(c == '1');
+    }
+    w.into_bytes()
+}
+
+fn encode<T: PER>(value: &T, c: &Constraints) -> io::Result<Vec<u8>> {
+    let mut w = BitWriter::new();
+    value.per_encode(&mut w, c)?;
+    Ok(w.into_bytes())
+}
+
+fn decode<T: PER>(bytes: &[u8], c: &Constraints) -> io::Result<T> {
+    T::per_decode(&mut BitReader::new(bytes), c)
+}
+
+/// Encode `value` in APER after a single `1` bit, to make alignment padding visible
+fn encode_aper<T: PER>(value: &T, c: &Constraints) -> io::Result<Vec<u8>> {
+    let mut w = BitWriter::aper();
+    w.write_bit(true);
+    value.per_encode(&mut w, c)?;
+    Ok(w.into_bytes())
+}
+
+fn decode_aper<T: PER>(bytes: &[u8], c: &Constraints) -> io::Result<T> {
+    let mut r = BitReader::aper(bytes);
+    assert!(r.read_bit()?);
+    T::per_decode(&mut r, c)
+}
+
+#[test]
+fn encode_message() {
+    let message = Message {
+        id: 5,
+        flag: true,
+        name: None,
+        kind: Kind::Low,
+        payload: Payload::Number(-1),
+        items: vec![true, false, true],
+    };
+    let encoded = message.uper_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        bits(
+            "0 \
+             00000001 00000101 \
+             1 \
+             10 \
+             01 00000001 11111111 \
+             00000011 101"
+        )
+    );
+    assert_eq!(message, Message::uper_from_bytes(&encoded).unwrap());
+
+    let message = Message {
+        name: Some("ab".to_string()),
+        kind: Kind::High,
+        payload: Payload::Empty(Null),
+        items: vec![],
+        ..message
+    };
+    let encoded = message.uper_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        bits(
+            "1 \
+             00000001 00000101 \
+             1 \
+             00000010 01100001 01100010 \
+             00 \
+             10 \
+             00000000"
+        )
+    );
+    assert_eq!(message, Message::uper_from_bytes(&encoded).unwrap());
+}
+
+#[test]
+fn encode_integers() {
+    let cases: Vec<(i32, Constraints, &str)> = vec![
+        (5, Constraints::value(0, 7), "101"),
+        (1, Constraints::value(1, 1), ""),
+        (0, Constraints::value(-1, 254), "00000001"),
+        (
+            256,
+            Constraints {
+                min: Some(0),
+                ..Constraints::NONE
+            },
+            "00000010 00000001 00000000",
+        ),
+        (
+            3,
+            Constraints {
+                min: Some(3),
+                ..Constraints::NONE
+            },
+            "00000001 00000000",
+        ),
+        (128, Constraints::NONE, "00000010 00000000 10000000"),
+        (-129, Constraints::NONE, "00000010 11111111 01111111"),
+        (-1, Constraints::NONE, "00000001 11111111"),
+        (
+            i32::MIN,
+            Constraints::value(i64::from(i32::MIN), i64::from(i32::MAX)),
+            "00000000 00000000 00000000 00000000",
+        ),
+    ];
+    for (value, c, pattern) in cases {
+        let encoded = encode(&value, &c).unwrap();
+        assert_eq!(encoded, bits(pattern), "{} {}", value, c);
+        assert_eq!(value, decode::<i32>(&encoded, &c).unwrap());
+    }
+
+    let error = encode(&8, &Constraints::value(0, 7)).unwrap_err();
+    assert_eq!(error.to_string(), "value 8 violates constraint (0..7)");
+    // 6 is the largest value of the three bits used for (0..5)
+    assert!(decode::<i32>(&bits("111"), &Constraints::value(0, 5)).is_err());
+    assert!(decode::<i32>(&bits("00000101 0 0 0 0 0"), &Constraints::NONE).is_err());
+}
+
+#[test]
+fn encode_sizes() {
+    let key = vec![0xAB; 16];
+    let fixed = Constraints::size(16, 16);
+    assert_eq!(encode(&key, &fixed).unwrap(), key);
+    assert_eq!(key, decode::<Vec<u8>>(&key, &fixed).unwrap());
+    assert!(encode(&vec![0xAB; 15], &fixed).is_err());
+
+    let small = Constraints::size(1, 4);
+    let encoded = encode(&vec![0xFFu8, 0x00], &small).unwrap();
+    assert_eq!(encoded, bits("01 11111111 00000000"));
+    assert_eq!(
+        vec![0xFF, 0x00],
+        decode::<Vec<u8>>(&encoded, &small).unwrap()
+    );
+
+    let flags = BitString::from_bits(&[true, false, true]);
+    assert_eq!(
+        encode(&flags, &Constraints::size(0, 7)).unwrap(),
+        bits("011 101")
+    );
+    assert_eq!(
+        encode(&flags, &Constraints::NONE).unwrap(),
+        bits("00000011 101")
+    );
+    let list = vec![true, true];
+    assert_eq!(encode(&list, &Constraints::size(2, 2)).unwrap(), bits("11"));
+}
+
+#[test]
+fn encode_fragmented() {
+    let data: Vec<u8> = (0..16384 + 200).map(|i| i as u8).collect();
+    let encoded = data.uper_bytes().unwrap();
+    assert_eq!(encoded.len(), 1 + 16384 + 2 + 200);
+    assert_eq!(encoded[0], 0xC1);
+    assert_eq!(&encoded[16385..16387], &[0x80, 200]);
+    assert_eq!(data, Vec::<u8>::uper_from_bytes(&encoded).unwrap());
+
+    let exact = vec![0u8; 16384];
+    let encoded = exact.uper_bytes().unwrap();
+    assert_eq!(encoded.len(), 1 + 16384 + 1);
+    assert_eq!(encoded[16385], 0x00);
+    assert_eq!(exact, Vec::<u8>::uper_from_bytes(&encoded).unwrap());
+}
+
+#[test]
+fn encode_strings() {
+    let text = IA5String::from("Hi".to_string());
+    let encoded = text.uper_bytes().unwrap();
+    assert_eq!(encoded, bits("00000010 1001000 1101001"));
+    assert_eq!(
+        "Hi",
+        String::from(IA5String::uper_from_bytes(&encoded).unwrap())
+    );
+
+    // Space is index 0, digits follow
+    let number = NumericString::from("12 ".to_string());
+    let encoded = encode(&number, &Constraints::size(3, 3)).unwrap();
+    assert_eq!(encoded, bits("0010 0011 0000"));
+    assert_eq!(
+        "12 ",
+        String::from(decode::<NumericString>(&encoded, &Constraints::size(3, 3)).unwrap())
+    );
+
+    assert!(IA5String::from("\u{e4}".to_string()).uper_bytes().is_err());
+    assert!(NumericString::from("1a".to_string()).uper_bytes().is_err());
+    assert!(NumericString::uper_from_bytes(&bits("00000001 1111")).is_err());
+}
+
+#[test]
+fn decode_errors() {
+    // Three alternatives, index 3 does not exist
+    assert!(Payload::uper_from_bytes(&bits("11")).is_err());
+    assert!(Kind::uper_from_bytes(&bits("11")).is_err());
+    assert!(bool::uper_from_bytes(&[0x80, 0x00]).is_err());
+    assert!(Message::uper_from_bytes(&[0x00]).is_err());
+    assert_eq!(Null::uper_from_bytes(&[0x00]).unwrap(), Null);
+    assert_eq!(Null.uper_bytes().unwrap(), vec![0x00]);
+}
+
+#[test]
+fn aper_message() {
+    let message = Message {
+        id: 5,
+        flag: true,
+        name: None,
+        kind: Kind::Low,
+        payload: Payload::Number(-1),
+        items: vec![true, false, true],
+    };
+    let encoded = message.aper_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        bits(
+            "0 0000000 \
+             00000001 00000101 \
+             1 \
+             10 \
+             01 000 \
+             00000001 11111111 \
+             00000011 101"
+        )
+    );
+    assert_eq!(message, Message::aper_from_bytes(&encoded).unwrap());
+    assert_ne!(encoded, message.uper_bytes().unwrap());
+}
+
+#[test]
+fn aper_integers() {
+    let semi = Constraints {
+        min: Some(0),
+        ..Constraints::NONE
+    };
+    let cases: Vec<(i32, Constraints, &str)> = vec![
+        (5, Constraints::value(0, 7), "1 101"),
+        (5, Constraints::value(0, 254), "1 00000101"),
+        (5, Constraints::value(0, 255), "1 0000000 00000101"),
+        (
+            256,
+            Constraints::value(0, 65535),
+            "1 0000000 00000001 00000000",
+        ),
+        (1, Constraints::value(0, 1 << 24), "1 00 00000 00000001"),
+        (
+            1 << 16,
+            Constraints::value(0, 1 << 24),
+            "1 10 00000 00000001 00000000 00000000",
+        ),
+        (256, semi, "1 0000000 00000010 00000001 00000000"),
+        (-1, Constraints::NONE, "1 0000000 00000001 11111111"),
+    ];
+    for (value, c, pattern) in cases {
+        let encoded = encode_aper(&value, &c).unwrap();
+        assert_eq!(encoded, bits(pattern), "{} {}", value, c);
+        assert_eq!(value, decode_aper::<i32>(&encoded, &c).unwrap());
+    }
+}
+
+#[test]
+fn aper_strings() {
+    let cases: Vec<(Vec<u8>, Constraints, &str)> = vec![
+        (
+            vec![0xFF, 0x00],
+            Constraints::size(2, 2),
+            "1 11111111 00000000",
+        ),
+        (
+            vec![0xFF, 0x00, 0xAA],
+            Constraints::size(3, 3),
+            "1 0000000 11111111 00000000 10101010",
+        ),
+        (
+            vec![0xFF, 0x00],
+            Constraints::size(1, 4),
+            "1 01 00000 11111111 00000000",
+        ),
+        (vec![0xFF], Constraints::NONE, "1 0000000 00000001 11111111"),
+    ];
+    for (value, c, pattern) in cases {
+        let encoded = encode_aper(&value, &c).unwrap();
+        assert_eq!(encoded, bits(pattern), "{:?} {}", value, c);
+        assert_eq!(value, decode_aper::<Vec<u8>>(&encoded, &c).unwrap());
+    }
+
+    let flags = BitString::from_bits(&[true, false, true]);
+    assert_eq!(
+        encode_aper(&flags, &Constraints::size(3, 3)).unwrap(),
+        bits("1 101")
+    );
+    assert_eq!(
+        encode_aper(&flags, &Constraints::size(0, 7)).unwrap(),
+        bits("1 011 0000 101")
+    );
+
+    // Seven bits per character are rounded up to eight
+    let text = IA5String::from("Hi".to_string());
+    let encoded = encode_aper(&text, &Constraints::NONE).unwrap();
+    assert_eq!(encoded, bits("1 0000000 00000010 01001000 01101001"));
+    assert_eq!(
+        "Hi",
+        String::from(decode_aper::<IA5String>(&encoded, &Constraints::NONE).unwrap())
+    );
+
+    // Eight bits suffice for the character codes, so they are used instead of the index
+    let printable = PrintableString::from("A".to_string());
+    let encoded = encode_aper(&printable, &Constraints::size(1, 1)).unwrap();
+    assert_eq!(encoded, bits("1 01000001"));
+    assert_eq!(
+        "A",
+        String::from(decode_aper::<PrintableString>(&encoded, &Constraints::size(1, 1)).unwrap())
+    );
+
+    let number = NumericString::from("12 ".to_string());
+    let c = Constraints::size(0, 3);
+    let encoded = encode_aper(&number, &c).unwrap();
+    assert_eq!(encoded, bits("1 11 0010 0011 0000"));
+    assert_eq!(
+        "12 ",
+        String::from(decode_aper::<NumericString>(&encoded, &c).unwrap())
+    );
+}
+
+#[test]
+fn aper_fragmented() {
+    let data: Vec<u8> = (0..16384 + 200).map(|i| i as u8).collect();
+    let encoded = encode_aper(&data, &Constraints::NONE).unwrap();
+    assert_eq!(encoded.len(), 1 + 1 + 16384 + 2 + 200);
+    assert_eq!(&encoded[..2], &[0x80, 0xC1]);
+    assert_eq!(&encoded[16386..16388], &[0x80, 200]);
+    assert_eq!(
+        data,
+        decode_aper::<Vec<u8>>(&encoded, &Constraints::NONE).unwrap()
+    );
+}
+
+#[derive(Debug)]
+struct Reading {
+    pub level: i32,
+    pub code: Vec<u8>,
+    pub label: IA5String,
+}
+
+der_sequence! {Reading WITH(PER):
+    level: NOTAG VALUE(0..7); TYPE i32,
+    code: NOTAG SIZE(2); TYPE Vec<u8>,
+    label: NOTAG SIZE(0..3); TYPE IA5String,
+}
+
+#[test]
+fn encode_annotated_constraints() {
+    let reading = Reading {
+        level: 5,
+        code: vec![0x01, 0x02],
+        label: IA5String::from("ab".to_string()),
+    };
+    let encoded = reading.uper_bytes().unwrap();
+    assert_eq!(encoded, bits("101 00000001 00000010 10 1100001 1100010"));
+    let decoded = Reading::uper_from_bytes(&encoded).unwrap();
+    assert_eq!((decoded.level, decoded.code), (5, vec![0x01, 0x02]));
+
+    let error = Reading {
+        level: 8,
+        ..reading
+    }
+    .uper_bytes()
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Reading.level: value 8 violates constraint (0..7)"
+    );
+}
+
+#[derive(Debug, PartialEq)]
+struct Extensible {
+    pub id: i32,
+    pub shade: Shade,
+    pub pick: Pick,
+}
+
+der_sequence! {Extensible WITH(PER):
+    id: NOTAG VALUE(0..7); TYPE i32,
+    shade: NOTAG TYPE Shade,
+    pick: EXPLICIT TAG CONTEXT 0; TYPE Pick,
+    ...
+}
+
+#[derive(Debug, PartialEq)]
+enum Shade {
+    Red = 0,
+    Green = 1,
+    Unknown,
+}
+
+der_enumerated!(Shade WITH(PER), Red, Green, ... Unknown);
+
+#[derive(Debug, PartialEq)]
+enum Pick {
+    Flag(bool),
+    Unknown(crate::der::Intermediate),
+}
+
+der_choice! {Pick WITH(PER):
+    Flag: IMPLICIT TAG CONTEXT 0; TYPE bool,
+    ... Unknown
+}
+
+#[test]
+fn encode_extension_markers() {
+    let value = Extensible {
+        id: 5,
+        shade: Shade::Green,
+        pick: Pick::Flag(true),
+    };
+    // Extension bits of the sequence, the enumeration and the choice are unset
+    let encoded = bits("0 101 0 1 0 1");
+    assert_eq!(encode(&value, &Constraints::NONE).unwrap(), encoded);
+    assert_eq!(
+        decode::<Extensible>(&encoded, &Constraints::NONE).unwrap(),
+        value
+    );
+
+    // One extension addition, an open type of one octet
+    let extended = bits("1 101 0 1 0 1 0000000 1 00000001 10101011");
+    assert_eq!(
+        decode::<Extensible>(&extended, &Constraints::NONE).unwrap(),
+        value
+    );
+
+    let unknown = bits("0 101 1 0000011 0 1");
+    assert_eq!(
+        decode::<Extensible>(&unknown, &Constraints::NONE)
+            .unwrap()
+            .shade,
+        Shade::Unknown
+    );
+    let shade = Extensible {
+        shade: Shade::Unknown,
+        ..value
+    };
+    assert!(encode(&shade, &Constraints::NONE).is_err());
+
+    let unknown = bits("0 101 0 1 1 0000001 00000001 11111111");
+    assert!(decode::<Extensible>(&unknown, &Constraints::NONE).is_err());
+}
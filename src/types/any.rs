@@ -1,24 +1,37 @@
+use crate::constraints::{Constrained, Constraints};
 use crate::der::*;
-use std::io::{self, Read, Write};
+use crate::io::{self, Read, Write};
+use crate::jer::{Value, JER};
+use crate::oer::{self, Reader, OER};
+use crate::per::{self, BitReader, BitWriter, PER};
+use crate::prelude::*;
+use crate::xer::{self, Element, XERDecodeable, XEREncodeable};
+use core::fmt;
 
 /// Asn1 Any Type
 ///
 /// Any encoded "Any" type will not be visible in the encoded bytes.
 /// It is a helper type, which should be used in case the actual type is not known yet.
 ///
+/// It keeps the class, tag and content of the element, which are compared and hashed, and it is
+/// displayed as a dump of the element tree.
+///
 /// # Example
 ///
 /// ```
 /// # use eagre_asn1::types::*;
-/// # use eagre_asn1::der::DER;
+/// # use eagre_asn1::der::*;
 ///
 /// let any = Any::new("I am a random string".to_string()).unwrap();
 /// let encoded = any.der_bytes().unwrap();
 /// // Send to far away planet
 /// let decoded = Any::der_from_bytes(encoded).unwrap();
 /// assert_eq!("I am a random string", &decoded.resolve::<String>().unwrap());
+/// assert_eq!(decoded.tag(), UniversalTag::UTF8String as u32);
+/// assert_eq!(decoded.class(), Class::Universal);
+/// assert!(!decoded.is_constructed());
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Any {
     i: Intermediate,
 }
@@ -35,23 +48,58 @@ impl Any {
     pub fn resolve<T: DER>(&self) -> io::Result<T> {
         <T>::der_from_intermediate(self.i.clone())
     }
+
+    /// Return the tag of the inner value
+    pub fn tag(&self) -> u32 {
+        self.i.tag
+    }
+
+    /// Return the class of the inner value
+    pub fn class(&self) -> Class {
+        self.i.class
+    }
+
+    /// Return whether the inner value is constructed
+    pub fn is_constructed(&self) -> bool {
+        self.i.content_type == ContentType::Constructed
+    }
+
+    /// Return the content octets of the inner value
+    pub fn content(&self) -> &[u8] {
+        &self.i.content
+    }
+}
+
+/// Dump of the element tree, see `der::dump`
+impl fmt::Display for Any {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes = self.der_bytes().map_err(|_| fmt::Error)?;
+        f.write_str(&dump::dump_string(&bytes))
+    }
 }
 
 impl DER for Any {
+    const DER_EXPLICIT_ONLY: bool = true;
+
+    // Any is a hidden type and does not have a Universal Tag of its own, the tag of the inner
+    // value is kept in the Intermediate
     fn der_universal_tag() -> UniversalTag {
-        unimplemented!() // Any is a hidden type and does not have a Universal Tag
+        UniversalTag::EOC
     }
 
     fn der_content() -> ContentType {
-        unimplemented!() // Same as universal tag
+        ContentType::Constructed
     }
 
-    fn der_encode_content(&self, _: &mut dyn Write) -> io::Result<()> {
-        unimplemented!()
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(&self.i.content)
     }
 
     fn der_decode_content(_: &mut dyn Read, _: usize) -> io::Result<Self> {
-        unimplemented!()
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Any can only be decoded with its tag",
+        ))
     }
 
     fn der_intermediate(&self) -> io::Result<Intermediate> {
@@ -61,6 +109,67 @@ impl DER for Any {
     fn der_from_intermediate(i: Intermediate) -> io::Result<Self> {
         Ok(Any { i: i })
     }
+
+    fn der_matches(_: Class, _: u32) -> bool {
+        true
+    }
+
+    fn der_tags() -> Vec<(Class, u32)> {
+        vec![]
+    }
+}
+
+/// As the actual type is not known, XER carries the complete DER encoding as hex
+impl XEREncodeable for Any {
+    fn xer_name(&self) -> String {
+        "ANY".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        e.push_text(&xer::hex_encode(&self.der_bytes()?));
+        Ok(())
+    }
+}
+
+impl XERDecodeable for Any {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        Any::der_from_bytes(xer::hex_decode(&e.text()?)?)
+    }
+}
+
+/// Like XER, JER carries the complete DER encoding as a hex string
+impl JER for Any {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::String(xer::hex_encode(&self.der_bytes()?)))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        Any::der_from_bytes(xer::hex_decode(v.as_str()?)?)
+    }
+}
+
+/// PER carries the complete DER encoding as length prefixed octets, like an open type
+impl PER for Any {
+    fn per_encode(&self, w: &mut BitWriter, _: &Constraints) -> io::Result<()> {
+        per::encode_octets(w, &self.der_bytes()?)
+    }
+
+    fn per_decode(r: &mut BitReader, _: &Constraints) -> io::Result<Self> {
+        Any::der_from_bytes(per::decode_octets(r)?)
+    }
+}
+
+impl Constrained for Any {}
+
+/// OER carries the complete DER encoding as an open type as well
+impl OER for Any {
+    fn oer_encode(&self, w: &mut Vec<u8>, _: &Constraints) -> io::Result<()> {
+        oer::encode_octets(w, &self.der_bytes()?, &Constraints::NONE)
+    }
+
+    fn oer_decode(r: &mut Reader, _: &Constraints) -> io::Result<Self> {
+        Any::der_from_bytes(oer::decode_octets(r, &Constraints::NONE)?)
+    }
 }
 
 #[test]
@@ -69,3 +178,29 @@ fn serialize_any() {
     let decoded = Any::der_from_bytes(val.der_bytes().unwrap()).unwrap();
     assert_eq!(31415, decoded.resolve().unwrap());
 }
+
+#[test]
+fn inspect_any() {
+    use std::collections::HashSet;
+
+    let values = vec![Any::new(1).unwrap(), Any::new(vec![true]).unwrap()];
+    let decoded = Vec::<Any>::der_from_bytes(values.der_bytes().unwrap()).unwrap();
+    assert_eq!(decoded, values);
+    assert_eq!(decoded[1].tag(), UniversalTag::Sequence as u32);
+    assert!(decoded[1].is_constructed());
+    assert_eq!(decoded[1].content(), &[0x01, 0x01, 0xFF][..]);
+    assert_eq!(
+        decoded[1].to_string(),
+        "    0  2      3: UNIVERSAL Sequence\n    2  2      1:   UNIVERSAL Boolean TRUE\n"
+    );
+    let set: HashSet<Any> = decoded.iter().cloned().chain(values).collect();
+    assert_eq!(set.len(), 2);
+
+    let mut content = vec![];
+    Any::new(5)
+        .unwrap()
+        .der_encode_content(&mut content)
+        .unwrap();
+    assert_eq!(content, vec![0x05]);
+    assert!(Any::der_decode_content(&mut &content[..], 1).is_err());
+}
@@ -0,0 +1,294 @@
+use crate::constraints::{Constrained, Constraints};
+use crate::der::*;
+use crate::io::{self, Read, Write};
+use crate::jer::{Value, JER};
+use crate::oer::{self, Reader, OER};
+use crate::per::{self, BitReader, BitWriter, PER};
+use crate::prelude::*;
+use crate::xer::{self, xml, Element, XERDecodeable, XEREncodeable};
+
+/// Asn1 BitString Type
+///
+/// Bits are stored most significant bit first, unused bits of the last byte are always zero.
+///
+/// # Example
+///
+/// ```
+/// # use eagre_asn1::types::BitString;
+/// # use eagre_asn1::der::DER;
+///
+/// let bits = BitString::new(vec![0b1010_0000], 3).unwrap();
+/// assert_eq!(bits.der_bytes().unwrap(), vec![0x03, 0x02, 0x05, 0xA0]);
+/// assert_eq!(bits.bit(0), Some(true));
+/// assert_eq!(bits.bit(1), Some(false));
+/// assert_eq!(bits.bit(3), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitString {
+    bytes: Vec<u8>,
+    length: usize,
+}
+
+impl BitString {
+    /// Create a BitString of `length` bits from `bytes`
+    ///
+    /// Fails if `bytes` does not have exactly the number of bytes needed for `length` bits.
+    /// Unused bits of the last byte are cleared.
+    pub fn new(mut bytes: Vec<u8>, length: usize) -> io::Result<BitString> {
+        if bytes.len() != length.div_ceil(8) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "BitString length does not match number of bytes",
+            ));
+        }
+        if let Some(last) = bytes.last_mut() {
+            *last &= 0xFFu8 << unused_bits(length);
+        }
+        Ok(BitString { bytes, length })
+    }
+
+    /// Create a BitString from a list of bits
+    pub fn from_bits(bits: &[bool]) -> BitString {
+        let mut bytes = vec![0; bits.len().div_ceil(8)];
+        for (i, _) in bits.iter().enumerate().filter(|(_, b)| **b) {
+            bytes[i / 8] |= 0x80 >> (i % 8);
+        }
+        BitString {
+            bytes,
+            length: bits.len(),
+        }
+    }
+
+    /// Number of bits
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Return whether this BitString contains no bits
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Return bit `i`, counting from the most significant bit of the first byte
+    pub fn bit(&self, i: usize) -> Option<bool> {
+        if i >= self.length {
+            return None;
+        }
+        Some(self.bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+    }
+
+    /// Return the bytes holding the bits
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Number of unused bits in the last byte
+fn unused_bits(length: usize) -> usize {
+    length.div_ceil(8) * 8 - length
+}
+
+impl DER for BitString {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::BitString
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Primitive
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(&[unused_bits(self.length) as u8])?;
+        w.write_all(&self.bytes)
+    }
+
+    fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+        let mut content = vec![0; length];
+        r.read_exact(&mut content)?;
+        let (unused, bytes) = content.split_first().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "BitString without content")
+        })?;
+        let unused = *unused as usize;
+        if unused > 7 || (bytes.is_empty() && unused != 0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid number of unused bits in BitString",
+            ));
+        }
+        BitString::new(bytes.to_vec(), bytes.len() * 8 - unused)
+    }
+}
+
+impl XEREncodeable for BitString {
+    fn xer_name(&self) -> String {
+        "BIT_STRING".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        let bits: String = (0..self.length)
+            .map(|i| if self.bit(i) == Some(true) { '1' } else { '0' })
+            .collect();
+        e.push_text(&bits);
+        Ok(())
+    }
+}
+
+impl XERDecodeable for BitString {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        let bits = e
+            .text()?
+            .chars()
+            .filter(|c| !xml::is_whitespace(*c))
+            .map(|c| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid bit in <{}>", e.name),
+                )),
+            })
+            .collect::<io::Result<Vec<bool>>>()?;
+        Ok(BitString::from_bits(&bits))
+    }
+}
+
+/// Encoded as an object with the bits as hex string in `value` and the number of bits in `length`
+impl JER for BitString {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::Object(vec![
+            (
+                "value".to_string(),
+                Value::String(xer::hex_encode(&self.bytes)),
+            ),
+            ("length".to_string(), Value::Number(self.length.to_string())),
+        ]))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        let missing = |name| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("missing member \"{}\" in BIT STRING", name),
+            )
+        };
+        if v.as_object()?.len() != 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "unknown member in BIT STRING",
+            ));
+        }
+        let bytes = xer::hex_decode(v.get("value").ok_or_else(|| missing("value"))?.as_str()?)?;
+        let length = v
+            .get("length")
+            .ok_or_else(|| missing("length"))?
+            .as_number()?
+            .parse()
+            .map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, "invalid BIT STRING length")
+            })?;
+        BitString::new(bytes, length)
+    }
+}
+
+impl PER for BitString {
+    fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+        per::encode_length(w, self.length, c, per::align_items(c, 1), |w, range| {
+            for i in range {
+                w.write_bit(self.bit(i) == Some(true));
+            }
+            Ok(())
+        })
+    }
+
+    fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self> {
+        let mut bits = vec![];
+        per::decode_length(r, c, per::align_items(c, 1), |r, n| {
+            for _ in 0..n {
+                bits.push(r.read_bit()?);
+            }
+            Ok(())
+        })?;
+        Ok(BitString::from_bits(&bits))
+    }
+}
+
+impl Constrained for BitString {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(false, true, false)?;
+        c.check_size(self.length)
+    }
+}
+
+/// Fixed size bit strings are encoded without length and initial octet
+impl OER for BitString {
+    fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+        c.check_size(self.length)?;
+        if oer::fixed_size(c).is_none() {
+            oer::encode_length(w, self.bytes.len() + 1);
+            w.push(unused_bits(self.length) as u8);
+        }
+        w.extend(&self.bytes);
+        Ok(())
+    }
+
+    fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self> {
+        let (bytes, length) = match oer::fixed_size(c) {
+            Some(size) => (r.read_bytes(size.div_ceil(8))?, size),
+            None => {
+                let octets = oer::decode_length(r)?;
+                if octets == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "missing initial octet of BIT STRING",
+                    ));
+                }
+                let unused = usize::from(r.read_u8()?);
+                let bytes = r.read_bytes(octets - 1)?;
+                if unused > 7 || (bytes.is_empty() && unused > 0) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "invalid number of unused bits in BIT STRING",
+                    ));
+                }
+                (bytes, bytes.len() * 8 - unused)
+            }
+        };
+        let bits = BitString::new(bytes.to_vec(), length)?;
+        if bits.bytes != bytes {
+            r.non_canonical("unused bits in BIT STRING")?;
+        }
+        c.check_size(length)?;
+        Ok(bits)
+    }
+}
+
+#[test]
+fn serialize_bitstring() {
+    let bits = BitString::from_bits(&[true, false, true, true, false, false, false, false, true]);
+    let encoded = bits.der_bytes().unwrap();
+    assert_eq!(encoded, vec![0x03, 0x03, 0x07, 0xB0, 0x80]);
+    assert_eq!(bits, BitString::der_from_bytes(encoded).unwrap());
+    assert_eq!(
+        BitString::der_from_bytes(vec![0x03, 0x01, 0x00]).unwrap(),
+        BitString::from_bits(&[])
+    );
+    assert!(BitString::der_from_bytes(vec![0x03, 0x01, 0x01]).is_err());
+    assert!(BitString::der_from_bytes(vec![0x03, 0x02, 0x08, 0x00]).is_err());
+
+    assert_eq!(
+        bits.xer_string().unwrap(),
+        "<BIT_STRING>101100001</BIT_STRING>"
+    );
+    assert_eq!(
+        bits.jer_string().unwrap(),
+        "{\"value\":\"B080\",\"length\":9}"
+    );
+    assert_eq!(
+        bits,
+        BitString::jer_from_str(&bits.jer_string().unwrap()).unwrap()
+    );
+    assert_eq!(
+        bits,
+        BitString::xer_from_str(&bits.xer_string().unwrap()).unwrap()
+    );
+}
@@ -1,7 +1,23 @@
 mod any;
+mod bitstring;
 mod null;
+mod octetstring;
+mod oid;
+mod open;
+#[cfg(feature = "std")]
+mod registry;
+mod sequence;
+mod sequenceof;
 mod strings;
 
 pub use self::any::Any;
+pub use self::bitstring::BitString;
 pub use self::null::Null;
+pub use self::octetstring::OctetString;
+pub use self::oid::ObjectIdentifier;
+pub use self::open::{OpenType, OpenValue};
+#[cfg(feature = "std")]
+pub use self::registry::Registry;
+pub use self::sequence::{Schema, SchemaField, Sequence};
+pub use self::sequenceof::SequenceOf;
 pub use self::strings::*;
@@ -1,5 +1,11 @@
+use crate::constraints::{Constrained, Constraints};
 use crate::der::*;
-use std::io::{self, Read, Write};
+use crate::io::{self, Read, Write};
+use crate::jer::{Value, JER};
+use crate::oer::{Reader, OER};
+use crate::per::{BitReader, BitWriter, PER};
+use crate::prelude::*;
+use crate::xer::{xml, Element, XERDecodeable, XEREncodeable};
 
 /// Asn1 Null Type
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -29,6 +35,66 @@ impl DER for Null {
     }
 }
 
+impl XEREncodeable for Null {
+    fn xer_name(&self) -> String {
+        "NULL".to_string()
+    }
+
+    fn xer_encode_content(&self, _: &mut Element) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl XERDecodeable for Null {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        if !e.elements()?.is_empty() || !xml::trim(&e.text()?).is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Null Type with content",
+            ));
+        }
+        Ok(Null)
+    }
+}
+
+impl JER for Null {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::Null)
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        match v {
+            Value::Null => Ok(Null),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "expected JSON null",
+            )),
+        }
+    }
+}
+
+impl PER for Null {
+    fn per_encode(&self, _: &mut BitWriter, _: &Constraints) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn per_decode(_: &mut BitReader, _: &Constraints) -> io::Result<Self> {
+        Ok(Null)
+    }
+}
+
+impl Constrained for Null {}
+
+impl OER for Null {
+    fn oer_encode(&self, _: &mut Vec<u8>, _: &Constraints) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn oer_decode(_: &mut Reader, _: &Constraints) -> io::Result<Self> {
+        Ok(Null)
+    }
+}
+
 #[test]
 fn encode_null() {
     let mut stream = Vec::new();
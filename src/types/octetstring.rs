@@ -0,0 +1,264 @@
+use crate::constraints::{Constrained, Constraints};
+use crate::der::*;
+use crate::io::{self, Read, Write};
+use crate::jer::{Value, JER};
+use crate::oer::{self, Reader, OER};
+use crate::per::{self, BitReader, BitWriter, PER};
+use crate::prelude::*;
+use crate::xer::{self, Element, XERDecodeable, XEREncodeable};
+use alloc::sync::Arc;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::{Bound, Deref, RangeBounds};
+
+/// Asn1 OctetString Type
+///
+/// Unlike `Vec<u8>`, this type can only ever be an `OCTET STRING`. Clones and slices share the
+/// same buffer, so taking them apart is cheap.
+///
+/// # Example
+///
+/// ```
+/// # use eagre_asn1::types::OctetString;
+/// # use eagre_asn1::der::DER;
+///
+/// let octets = OctetString::from(vec![0xDE, 0xAD, 0xBE, 0xEF]);
+/// assert_eq!(octets.der_bytes().unwrap(), vec![0x04, 0x04, 0xDE, 0xAD, 0xBE, 0xEF]);
+/// let tail = octets.slice(2..);
+/// assert_eq!(tail.as_bytes(), &[0xBE, 0xEF]);
+/// assert_eq!(tail.der_bytes().unwrap(), vec![0x04, 0x02, 0xBE, 0xEF]);
+/// ```
+#[derive(Clone)]
+pub struct OctetString {
+    bytes: Arc<[u8]>,
+    start: usize,
+    end: usize,
+}
+
+impl OctetString {
+    /// Create an empty OctetString
+    pub fn new() -> OctetString {
+        OctetString::from(Vec::new())
+    }
+
+    /// Number of octets
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Return whether this OctetString contains no octets
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Return the octets
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[self.start..self.end]
+    }
+
+    /// Return the octets in `range`, sharing the buffer with this OctetString
+    ///
+    /// Panics if `range` is out of bounds, just like slicing does.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> OctetString {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end + 1,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(
+            start <= end && end <= self.len(),
+            "range {}..{} out of bounds for OctetString of length {}",
+            start,
+            end,
+            self.len()
+        );
+        OctetString {
+            bytes: self.bytes.clone(),
+            start: self.start + start,
+            end: self.start + end,
+        }
+    }
+}
+
+impl Default for OctetString {
+    fn default() -> OctetString {
+        OctetString::new()
+    }
+}
+
+impl From<Vec<u8>> for OctetString {
+    fn from(bytes: Vec<u8>) -> OctetString {
+        let end = bytes.len();
+        OctetString {
+            bytes: bytes.into(),
+            start: 0,
+            end,
+        }
+    }
+}
+
+impl From<&[u8]> for OctetString {
+    fn from(bytes: &[u8]) -> OctetString {
+        OctetString {
+            bytes: bytes.into(),
+            start: 0,
+            end: bytes.len(),
+        }
+    }
+}
+
+impl From<OctetString> for Vec<u8> {
+    fn from(octets: OctetString) -> Vec<u8> {
+        octets.as_bytes().to_vec()
+    }
+}
+
+impl Deref for OctetString {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<[u8]> for OctetString {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl PartialEq for OctetString {
+    fn eq(&self, other: &OctetString) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for OctetString {}
+
+impl Hash for OctetString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
+}
+
+impl fmt::Debug for OctetString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OctetString({})", xer::hex_encode(self))
+    }
+}
+
+impl DER for OctetString {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::OctetString
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Primitive
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(self)
+    }
+
+    fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+        Vec::<u8>::der_decode_content(r, length).map(OctetString::from)
+    }
+}
+
+impl XEREncodeable for OctetString {
+    fn xer_name(&self) -> String {
+        "OCTET_STRING".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        e.push_text(&xer::hex_encode(self));
+        Ok(())
+    }
+}
+
+impl XERDecodeable for OctetString {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        xer::hex_decode(&e.text()?).map(OctetString::from)
+    }
+}
+
+impl JER for OctetString {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::String(xer::hex_encode(self)))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        xer::hex_decode(v.as_str()?).map(OctetString::from)
+    }
+}
+
+impl PER for OctetString {
+    fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+        per::encode_length(w, self.len(), c, per::align_items(c, 8), |w, range| {
+            w.write_bytes(&self[range]);
+            Ok(())
+        })
+    }
+
+    fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self> {
+        Vec::<u8>::per_decode(r, c).map(OctetString::from)
+    }
+}
+
+impl OER for OctetString {
+    fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+        oer::encode_octets(w, self, c)
+    }
+
+    fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self> {
+        oer::decode_octets(r, c).map(OctetString::from)
+    }
+}
+
+impl Constrained for OctetString {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(false, true, false)?;
+        c.check_size(self.len())
+    }
+}
+
+#[test]
+fn slice_octet_string() {
+    let octets = OctetString::from(vec![1, 2, 3, 4, 5]);
+    let middle = octets.slice(1..4);
+    assert_eq!(middle.as_bytes(), &[2, 3, 4]);
+    assert_eq!(middle.slice(1..=1).as_bytes(), &[3]);
+    assert!(middle.slice(..0).is_empty());
+    assert!(Arc::ptr_eq(&octets.bytes, &middle.bytes));
+    assert_eq!(middle, OctetString::from(&[2, 3, 4][..]));
+    assert_eq!(format!("{:?}", middle), "OctetString(020304)");
+
+    let encoded = middle.der_bytes().unwrap();
+    assert_eq!(encoded, vec![0x04, 0x03, 0x02, 0x03, 0x04]);
+    assert_eq!(encoded, (&middle[..]).der_bytes().unwrap());
+    assert_eq!(middle, OctetString::der_from_bytes(encoded).unwrap());
+    assert_eq!(
+        middle,
+        OctetString::xer_from_str(&middle.xer_string().unwrap()).unwrap()
+    );
+    assert_eq!(middle.jer_string().unwrap(), "\"020304\"");
+    assert_eq!(
+        middle.uper_bytes().unwrap(),
+        vec![0x02u8, 0x03, 0x04].uper_bytes().unwrap()
+    );
+    assert_eq!(
+        middle,
+        OctetString::oer_from_bytes(&middle.oer_bytes().unwrap()).unwrap()
+    );
+}
+
+#[test]
+#[should_panic]
+fn slice_out_of_bounds() {
+    OctetString::from(vec![1, 2, 3]).slice(1..4);
+}
@@ -0,0 +1,234 @@
+use crate::constraints::{Constrained, Constraints};
+use crate::der::*;
+use crate::io::{self, Read, Write};
+use crate::jer::{Value, JER};
+use crate::oer::{self, Reader, OER};
+use crate::per::{self, BitReader, BitWriter, PER};
+use crate::prelude::*;
+use crate::xer::{xml, Element, XERDecodeable, XEREncodeable};
+use core::fmt;
+use core::str::FromStr;
+
+/// Asn1 ObjectIdentifier Type
+///
+/// # Example
+///
+/// ```
+/// # use eagre_asn1::types::ObjectIdentifier;
+/// # use eagre_asn1::der::DER;
+///
+/// let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+/// assert_eq!(oid.der_bytes().unwrap(), vec![0x06, 0x06, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D]);
+/// assert_eq!(oid.arcs(), &[1, 2, 840, 113549]);
+/// assert_eq!(oid.to_string(), "1.2.840.113549");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectIdentifier {
+    arcs: Vec<u64>,
+}
+
+impl ObjectIdentifier {
+    /// Create an ObjectIdentifier from its arcs
+    ///
+    /// Fails if there are less than two arcs, if the first arc is bigger than 2 or if the second
+    /// arc is bigger than 39 below the arcs 0 and 1.
+    pub fn new(arcs: Vec<u64>) -> io::Result<ObjectIdentifier> {
+        match arcs[..] {
+            [first, second, ..] if first < 2 && second < 40 => (),
+            [2, second, ..] if second <= u64::MAX - 80 => (),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "invalid root arcs of ObjectIdentifier",
+                ))
+            }
+        }
+        Ok(ObjectIdentifier { arcs })
+    }
+
+    /// Return the arcs
+    pub fn arcs(&self) -> &[u64] {
+        &self.arcs
+    }
+
+    /// Content octets, the first two arcs are combined into one subidentifier
+    fn content(&self) -> Vec<u8> {
+        let mut content = vec![];
+        let first = self.arcs[0] * 40 + self.arcs[1];
+        for arc in Some(first)
+            .into_iter()
+            .chain(self.arcs[2..].iter().cloned())
+        {
+            let groups = (64 - arc.leading_zeros() as usize).div_ceil(7).max(1);
+            for i in (0..groups).rev() {
+                let more = if i > 0 { 0x80 } else { 0x00 };
+                content.push(more | ((arc >> (7 * i)) & 0x7F) as u8);
+            }
+        }
+        content
+    }
+
+    fn from_content(content: &[u8]) -> io::Result<ObjectIdentifier> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidInput, msg);
+        if content.last().is_none_or(|b| b & 0x80 != 0) {
+            return Err(invalid("truncated ObjectIdentifier"));
+        }
+        let mut subidentifiers = vec![];
+        let mut arc: u64 = 0;
+        let mut start = true;
+        for b in content {
+            if start && *b == 0x80 {
+                return Err(invalid("ObjectIdentifier arc is not minimally encoded"));
+            }
+            if arc >> 57 != 0 {
+                return Err(invalid("ObjectIdentifier arc too big"));
+            }
+            arc = arc << 7 | u64::from(b & 0x7F);
+            start = b & 0x80 == 0;
+            if start {
+                subidentifiers.push(arc);
+                arc = 0;
+            }
+        }
+        let first = subidentifiers[0];
+        let mut arcs = match first {
+            0..=39 => vec![0, first],
+            40..=79 => vec![1, first - 40],
+            _ => vec![2, first - 80],
+        };
+        arcs.extend(&subidentifiers[1..]);
+        Ok(ObjectIdentifier { arcs })
+    }
+}
+
+/// Parse the dotted form, as in `1.2.840.113549`
+impl FromStr for ObjectIdentifier {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<ObjectIdentifier> {
+        let arcs = s
+            .split('.')
+            .map(|arc| match arc.bytes().all(|b| b.is_ascii_digit()) {
+                true => arc.parse().ok(),
+                false => None,
+            })
+            .collect::<Option<Vec<u64>>>()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid ObjectIdentifier \"{}\"", s),
+                )
+            })?;
+        ObjectIdentifier::new(arcs)
+    }
+}
+
+impl fmt::Display for ObjectIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let arcs: Vec<String> = self.arcs.iter().map(|arc| arc.to_string()).collect();
+        f.write_str(&arcs.join("."))
+    }
+}
+
+impl DER for ObjectIdentifier {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::ObjectIdentifier
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Primitive
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(&self.content())
+    }
+
+    fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+        let mut content = vec![0; length];
+        r.read_exact(&mut content)?;
+        ObjectIdentifier::from_content(&content)
+    }
+}
+
+/// XER and JER use the dotted form
+impl XEREncodeable for ObjectIdentifier {
+    fn xer_name(&self) -> String {
+        "OBJECT_IDENTIFIER".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        e.push_text(&self.to_string());
+        Ok(())
+    }
+}
+
+impl XERDecodeable for ObjectIdentifier {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        e.text()?.trim_matches(xml::is_whitespace).parse()
+    }
+}
+
+impl JER for ObjectIdentifier {
+    fn jer_value(&self) -> io::Result<Value> {
+        Ok(Value::String(self.to_string()))
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        v.as_str()?.parse()
+    }
+}
+
+/// PER and OER carry the content octets of the DER encoding with a length
+impl PER for ObjectIdentifier {
+    fn per_encode(&self, w: &mut BitWriter, _: &Constraints) -> io::Result<()> {
+        per::encode_octets(w, &self.content())
+    }
+
+    fn per_decode(r: &mut BitReader, _: &Constraints) -> io::Result<Self> {
+        ObjectIdentifier::from_content(&per::decode_octets(r)?)
+    }
+}
+
+impl OER for ObjectIdentifier {
+    fn oer_encode(&self, w: &mut Vec<u8>, _: &Constraints) -> io::Result<()> {
+        oer::encode_octets(w, &self.content(), &Constraints::NONE)
+    }
+
+    fn oer_decode(r: &mut Reader, _: &Constraints) -> io::Result<Self> {
+        ObjectIdentifier::from_content(&oer::decode_octets(r, &Constraints::NONE)?)
+    }
+}
+
+impl Constrained for ObjectIdentifier {}
+
+#[test]
+fn serialize_object_identifier() {
+    let oid: ObjectIdentifier = "2.999.3".parse().unwrap();
+    let encoded = oid.der_bytes().unwrap();
+    assert_eq!(encoded, vec![0x06, 0x03, 0x88, 0x37, 0x03]);
+    assert_eq!(oid, ObjectIdentifier::der_from_bytes(encoded).unwrap());
+    assert_eq!(
+        ObjectIdentifier::der_from_bytes(vec![0x06, 0x01, 0x00]).unwrap(),
+        ObjectIdentifier::new(vec![0, 0]).unwrap()
+    );
+    assert!(ObjectIdentifier::der_from_bytes(vec![0x06, 0x00]).is_err());
+    assert!(ObjectIdentifier::der_from_bytes(vec![0x06, 0x02, 0x2A, 0x86]).is_err());
+    assert!(ObjectIdentifier::der_from_bytes(vec![0x06, 0x03, 0x2A, 0x80, 0x01]).is_err());
+    assert!("1.40".parse::<ObjectIdentifier>().is_err());
+    assert!("3.1".parse::<ObjectIdentifier>().is_err());
+    assert!("1.2.+3".parse::<ObjectIdentifier>().is_err());
+
+    assert_eq!(
+        oid.xer_string().unwrap(),
+        "<OBJECT_IDENTIFIER>2.999.3</OBJECT_IDENTIFIER>"
+    );
+    assert_eq!(
+        oid,
+        ObjectIdentifier::xer_from_str(&oid.xer_string().unwrap()).unwrap()
+    );
+    assert_eq!(oid.jer_string().unwrap(), "\"2.999.3\"");
+    assert_eq!(
+        oid,
+        ObjectIdentifier::jer_from_str(&oid.jer_string().unwrap()).unwrap()
+    );
+}
@@ -0,0 +1,101 @@
+use crate::der::DER;
+use crate::io;
+use crate::types::{Any, ObjectIdentifier};
+use core::any;
+use core::fmt::Debug;
+
+/// Open type, whose actual type is chosen by an OBJECT IDENTIFIER
+///
+/// This is what `ANY DEFINED BY` and information object classes describe: AlgorithmIdentifier
+/// parameters, X.509 extensions and CMS contents all depend on a preceding OID. In `der_sequence!`
+/// a field of an open type is annotated with `DEFINED BY <FIELD>;`, it is decoded as raw `Any`
+/// first and resolved as soon as the whole sequence is decoded.
+///
+/// Implemented by `Any` itself, by enums using `der_open_type!` and, with the `std` feature, by
+/// `Box<dyn OpenValue>`, which looks up the type in the global `Registry`.
+pub trait OpenType: Sized {
+    /// Decode the value identified by `id` from its raw encoding
+    fn open_decode(id: &ObjectIdentifier, any: Any) -> io::Result<Self>;
+
+    /// Keep a raw encoding whose identifier is not known yet
+    fn open_raw(any: Any) -> Self;
+
+    /// Encode the value into its raw encoding
+    fn open_encode(&self) -> io::Result<Any>;
+
+    /// Resolve a value that was decoded before its identifier was known
+    fn open_resolve(self, id: &ObjectIdentifier) -> io::Result<Self> {
+        Self::open_decode(id, self.open_encode()?)
+    }
+}
+
+impl OpenType for Any {
+    fn open_decode(_: &ObjectIdentifier, any: Any) -> io::Result<Self> {
+        Ok(any)
+    }
+
+    fn open_raw(any: Any) -> Self {
+        any
+    }
+
+    fn open_encode(&self) -> io::Result<Any> {
+        Ok(self.clone())
+    }
+
+    fn open_resolve(self, _: &ObjectIdentifier) -> io::Result<Self> {
+        Ok(self)
+    }
+}
+
+/// An absent value stays absent
+impl<T: OpenType> OpenType for Option<T> {
+    fn open_decode(id: &ObjectIdentifier, any: Any) -> io::Result<Self> {
+        T::open_decode(id, any).map(Some)
+    }
+
+    fn open_raw(any: Any) -> Self {
+        Some(T::open_raw(any))
+    }
+
+    fn open_encode(&self) -> io::Result<Any> {
+        match self {
+            Some(value) => value.open_encode(),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "absent open type can not be encoded",
+            )),
+        }
+    }
+
+    fn open_resolve(self, id: &ObjectIdentifier) -> io::Result<Self> {
+        self.map(|value| value.open_resolve(id)).transpose()
+    }
+}
+
+/// Value of an open type resolved through a `Registry`
+///
+/// Implemented for every DER type, use `downcast_ref` to get at the actual value.
+pub trait OpenValue: Debug {
+    /// Encode the value into its raw encoding
+    fn to_any(&self) -> io::Result<Any>;
+
+    /// The value as `core::any::Any`, for downcasting
+    fn as_dyn(&self) -> &dyn any::Any;
+}
+
+impl<T: DER + Debug + 'static> OpenValue for T {
+    fn to_any(&self) -> io::Result<Any> {
+        Any::der_from_intermediate(self.der_intermediate()?)
+    }
+
+    fn as_dyn(&self) -> &dyn any::Any {
+        self
+    }
+}
+
+impl dyn OpenValue {
+    /// Return the value if it is a `T`
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.as_dyn().downcast_ref()
+    }
+}
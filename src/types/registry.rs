@@ -0,0 +1,92 @@
+use crate::der::DER;
+use crate::io;
+use crate::types::{Any, ObjectIdentifier, OpenType, OpenValue};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::RwLock;
+
+type Decoder = fn(Any) -> io::Result<Box<dyn OpenValue>>;
+
+fn decode_boxed<T: DER + Debug + 'static>(any: Any) -> io::Result<Box<dyn OpenValue>> {
+    Ok(Box::new(any.resolve::<T>()?))
+}
+
+/// Registry mapping OBJECT IDENTIFIERs to the types of open type values
+///
+/// Values with an unregistered OID are kept as raw `Any`.
+///
+/// # Example
+///
+/// ```
+/// # use eagre_asn1::types::*;
+/// let registry = Registry::new();
+/// let id: ObjectIdentifier = "1.2.3".parse().unwrap();
+/// registry.register::<i32>(id.clone());
+///
+/// let value = registry.decode(&id, Any::new(42).unwrap()).unwrap();
+/// assert_eq!(value.downcast_ref::<i32>(), Some(&42));
+/// let other = "1.2.4".parse().unwrap();
+/// let value = registry.decode(&other, Any::new(42).unwrap()).unwrap();
+/// assert!(value.downcast_ref::<Any>().is_some());
+/// ```
+#[derive(Debug)]
+pub struct Registry {
+    decoders: RwLock<BTreeMap<ObjectIdentifier, Decoder>>,
+}
+
+static GLOBAL: Registry = Registry::new();
+
+impl Registry {
+    /// Create an empty Registry
+    pub const fn new() -> Registry {
+        Registry {
+            decoders: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// The Registry used to decode `Box<dyn OpenValue>`
+    pub fn global() -> &'static Registry {
+        &GLOBAL
+    }
+
+    /// Decode values identified by `id` as `T`, replacing any type registered before
+    pub fn register<T: DER + Debug + 'static>(&self, id: ObjectIdentifier) {
+        let mut decoders = self.decoders.write().unwrap_or_else(|e| e.into_inner());
+        decoders.insert(id, decode_boxed::<T>);
+    }
+
+    /// Decode the value identified by `id`, or keep it as `Any` if `id` is not registered
+    pub fn decode(&self, id: &ObjectIdentifier, any: Any) -> io::Result<Box<dyn OpenValue>> {
+        let decoder = {
+            let decoders = self.decoders.read().unwrap_or_else(|e| e.into_inner());
+            decoders.get(id).cloned()
+        };
+        match decoder {
+            Some(decoder) => decoder(any),
+            None => Ok(Box::new(any)),
+        }
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        Registry::new()
+    }
+}
+
+impl OpenType for Box<dyn OpenValue> {
+    fn open_decode(id: &ObjectIdentifier, any: Any) -> io::Result<Self> {
+        Registry::global().decode(id, any)
+    }
+
+    fn open_raw(any: Any) -> Self {
+        Box::new(any)
+    }
+
+    fn open_encode(&self) -> io::Result<Any> {
+        // The box is an OpenValue itself, encode the boxed value
+        (**self).to_any()
+    }
+}
+
+crate::der_open_codecs!(Box<dyn OpenValue>);
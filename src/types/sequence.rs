@@ -1,5 +1,393 @@
-use der::*;
+use crate::der::*;
+use crate::io::{self, Read, Write};
+use crate::prelude::*;
+use crate::types::Any;
 
+/// Dynamic Asn1 Sequence Type
+///
+/// A sequence of named values of any type, for messages whose structure is only known at
+/// runtime. Values are kept in their DER encoding and decoded again by `get()`.
+///
+/// Encoding it on its own writes the values in order without tags, decoding it on its own names
+/// the values by their position. Use a `Schema` to apply tags, leave out optional values and
+/// find the names while decoding.
+///
+/// # Example
+///
+/// ```
+/// # use eagre_asn1::types::*;
+/// # use eagre_asn1::der::*;
+///
+/// let schema = Schema::new()
+///     .field(SchemaField::new("id"))
+///     .field(SchemaField::new("comment").implicit(Class::ContextSpecific, 0).optional())
+///     .field(SchemaField::new("admin").explicit(Class::ContextSpecific, 1));
+///
+/// let mut user = Sequence::new();
+/// user.push("id", 7).unwrap();
+/// user.push("admin", true).unwrap();
+/// let encoded = user.der_bytes_with(&schema).unwrap();
+/// assert_eq!(encoded, vec![0x30, 0x08, 0x02, 0x01, 0x07, 0xA1, 0x03, 0x01, 0x01, 0xFF]);
+///
+/// let decoded = Sequence::der_from_bytes_with(&schema, encoded).unwrap();
+/// assert_eq!(decoded.get::<i32>("id").unwrap(), 7);
+/// assert!(decoded.get::<bool>("admin").unwrap());
+/// assert!(decoded.get_any("comment").is_none());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Sequence {
-    inner: Vec<(String, Box<DER>)>,
+    fields: Vec<(String, Any)>,
+}
+
+impl Sequence {
+    /// Create an empty Sequence
+    pub fn new() -> Sequence {
+        Sequence { fields: vec![] }
+    }
+
+    /// Append `value` as field `name`
+    ///
+    /// Fails if there already is a field `name` or `value` can not be encoded.
+    pub fn push<T: DER>(&mut self, name: &str, value: T) -> io::Result<()> {
+        if self.get_any(name).is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("duplicate field {}", name),
+            ));
+        }
+        self.fields.push((name.to_string(), Any::new(value)?));
+        Ok(())
+    }
+
+    /// Decode the value of field `name`
+    pub fn get<T: DER>(&self, name: &str) -> io::Result<T> {
+        match self.get_any(name) {
+            Some(any) => any.resolve(),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no field {}", name),
+            )),
+        }
+    }
+
+    /// Return the raw value of field `name`
+    pub fn get_any(&self, name: &str) -> Option<&Any> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, any)| any)
+    }
+
+    /// Remove field `name` and return its raw value
+    pub fn remove(&mut self, name: &str) -> Option<Any> {
+        let index = self.fields.iter().position(|(field, _)| field == name)?;
+        Some(self.fields.remove(index).1)
+    }
+
+    /// Number of fields
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Return whether this Sequence has no fields
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Iterate over the names and raw values of all fields, in order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Any)> {
+        self.fields.iter().map(|(name, any)| (name.as_str(), any))
+    }
+
+    /// Create Intermediate using `schema`
+    ///
+    /// Fields are encoded in the order of the schema. Fails if a field that is not optional is
+    /// missing or if there is a field the schema does not know.
+    pub fn der_intermediate_with(&self, schema: &Schema) -> io::Result<Intermediate> {
+        if let Some((name, _)) = self
+            .fields
+            .iter()
+            .find(|(name, _)| !schema.fields.iter().any(|field| &field.name == name))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("field {} is not in the schema", name),
+            ));
+        }
+        let mut content = vec![];
+        for field in schema.fields.iter() {
+            let any = match self.get_any(&field.name) {
+                Some(any) => any,
+                None if field.optional => continue,
+                None => return Err(missing_field(&field.name)),
+            };
+            let i = any.der_intermediate()?;
+            let i = match field.tagging {
+                Tagging::None => i,
+                Tagging::Explicit(class, tag) => i.into_explicit(tag, class)?,
+                Tagging::Implicit(class, tag) => i.into_implicit(tag, class),
+            };
+            i.encode(&mut content)?;
+        }
+        Ok(Intermediate::new(
+            Class::Universal,
+            ContentType::Constructed,
+            Self::der_universal_tag() as u32,
+        )
+        .with_content(content))
+    }
+
+    /// Fully encode into stream using `schema`
+    pub fn der_encode_with(&self, schema: &Schema, w: &mut dyn Write) -> io::Result<()> {
+        self.der_intermediate_with(schema)?.encode(w)
+    }
+
+    /// Return fully encoded bytes using `schema`
+    pub fn der_bytes_with(&self, schema: &Schema) -> io::Result<Vec<u8>> {
+        let mut stream = Vec::new();
+        self.der_encode_with(schema, &mut stream)?;
+        Ok(stream)
+    }
+
+    /// Create object from Intermediate using `schema`
+    ///
+    /// Untagged optional fields take the next element, unless a later field has its tag.
+    /// Implicitly tagged values keep their context tag, which `get()` does not care about.
+    pub fn der_from_intermediate_with(schema: &Schema, i: Intermediate) -> io::Result<Sequence> {
+        let children = i.children()?;
+        let mut elements = children.into_iter().peekable();
+        let mut sequence = Sequence::new();
+        for (index, field) in schema.fields.iter().enumerate() {
+            let claimed = |element: &Intermediate| {
+                schema.fields[index + 1..]
+                    .iter()
+                    .any(|later| later.tagging.matches(element))
+            };
+            let matching = |element: &Intermediate| match field.tagging {
+                Tagging::None => !field.optional || !claimed(element),
+                _ => field.tagging.matches(element),
+            };
+            let element = match elements.next_if(matching) {
+                Some(element) => element,
+                None if field.optional => continue,
+                None => return Err(missing_field(&field.name)),
+            };
+            let element = match field.tagging {
+                Tagging::Explicit(..) => {
+                    let mut inner = element.children()?;
+                    if inner.len() != 1 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!(
+                                "explicit tag of field {} does not wrap one element",
+                                field.name
+                            ),
+                        ));
+                    }
+                    inner.remove(0)
+                }
+                _ => element,
+            };
+            sequence
+                .fields
+                .push((field.name.clone(), Any::der_from_intermediate(element)?));
+        }
+        if elements.next().is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "element after the last field of the schema",
+            ));
+        }
+        Ok(sequence)
+    }
+
+    /// Create object from stream using `schema`
+    pub fn der_decode_with(schema: &Schema, r: &mut dyn Read) -> io::Result<Sequence> {
+        Sequence::der_from_intermediate_with(schema, Intermediate::decode(r)?)
+    }
+
+    /// Create object from bytes using `schema`
+    pub fn der_from_bytes_with(schema: &Schema, bytes: Vec<u8>) -> io::Result<Sequence> {
+        Sequence::der_decode_with(schema, &mut io::Cursor::new(bytes))
+    }
+}
+
+fn missing_field(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("missing field {}", name),
+    )
+}
+
+/// Values are encoded in order without tags and named by their position when decoding
+impl DER for Sequence {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::Sequence
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Constructed
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        for (_, any) in self.fields.iter() {
+            any.der_encode(w)?;
+        }
+        Ok(())
+    }
+
+    fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+        let mut encoded = r.take(length as u64);
+        let mut sequence = Sequence::new();
+        while encoded.limit() > 0 {
+            let name = sequence.len().to_string();
+            sequence.fields.push((name, Any::der_decode(&mut encoded)?));
+        }
+        Ok(sequence)
+    }
+}
+
+/// Runtime description of the fields of a `Sequence`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    fields: Vec<SchemaField>,
+}
+
+impl Schema {
+    /// Create a Schema without fields
+    pub fn new() -> Schema {
+        Schema { fields: vec![] }
+    }
+
+    /// Add a field after all fields added before
+    pub fn field(mut self, field: SchemaField) -> Schema {
+        self.fields.push(field);
+        self
+    }
+
+    /// Return the fields in order
+    pub fn fields(&self) -> &[SchemaField] {
+        &self.fields
+    }
+}
+
+/// Field of a `Schema`, untagged and not optional unless configured otherwise
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaField {
+    name: String,
+    tagging: Tagging,
+    optional: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Tagging {
+    None,
+    Explicit(Class, u32),
+    Implicit(Class, u32),
+}
+
+impl Tagging {
+    fn matches(&self, i: &Intermediate) -> bool {
+        match *self {
+            Tagging::None => false,
+            Tagging::Explicit(class, tag) | Tagging::Implicit(class, tag) => {
+                i.class == class && i.tag == tag
+            }
+        }
+    }
+}
+
+impl SchemaField {
+    /// Create an untagged field
+    pub fn new(name: &str) -> SchemaField {
+        SchemaField {
+            name: name.to_string(),
+            tagging: Tagging::None,
+            optional: false,
+        }
+    }
+
+    /// Tag the field with an `EXPLICIT` tag
+    pub fn explicit(mut self, class: Class, tag: u32) -> SchemaField {
+        self.tagging = Tagging::Explicit(class, tag);
+        self
+    }
+
+    /// Tag the field with an `IMPLICIT` tag
+    pub fn implicit(mut self, class: Class, tag: u32) -> SchemaField {
+        self.tagging = Tagging::Implicit(class, tag);
+        self
+    }
+
+    /// Mark the field `OPTIONAL`
+    pub fn optional(mut self) -> SchemaField {
+        self.optional = true;
+        self
+    }
+
+    /// Return the name of the field
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Return whether the field is optional
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+}
+
+#[test]
+fn dynamic_sequence() {
+    let schema = Schema::new()
+        .field(SchemaField::new("name"))
+        .field(SchemaField::new("nick").optional())
+        .field(
+            SchemaField::new("age")
+                .implicit(Class::ContextSpecific, 0)
+                .optional(),
+        )
+        .field(SchemaField::new("admin").implicit(Class::ContextSpecific, 1));
+
+    let mut user = Sequence::new();
+    user.push("admin", false).unwrap();
+    user.push("name", "Rahix".to_string()).unwrap();
+    assert!(user.push("name", 1).is_err());
+    assert_eq!(user.len(), 2);
+
+    // Fields are reordered by the schema
+    let encoded = user.der_bytes_with(&schema).unwrap();
+    assert_eq!(
+        encoded,
+        vec![0x30, 0x0A, 0x0C, 0x05, 0x52, 0x61, 0x68, 0x69, 0x78, 0x81, 0x01, 0x00]
+    );
+    let decoded = Sequence::der_from_bytes_with(&schema, encoded.clone()).unwrap();
+    assert_eq!(decoded.get::<String>("name").unwrap(), "Rahix");
+    assert!(!decoded.get::<bool>("admin").unwrap());
+    assert!(decoded.get_any("nick").is_none());
+    assert_eq!(decoded.der_bytes_with(&schema).unwrap(), encoded);
+
+    // An untagged optional field takes elements no later field claims
+    user.push("nick", "R".to_string()).unwrap();
+    user.push("age", 30).unwrap();
+    let decoded =
+        Sequence::der_from_bytes_with(&schema, user.der_bytes_with(&schema).unwrap()).unwrap();
+    assert_eq!(decoded.get::<String>("nick").unwrap(), "R");
+    assert_eq!(decoded.get::<i32>("age").unwrap(), 30);
+    let names: Vec<&str> = decoded.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["name", "nick", "age", "admin"]);
+
+    // Missing, unknown and extra fields are errors
+    user.remove("admin");
+    assert!(user.der_bytes_with(&schema).is_err());
+    user.push("admin", true).unwrap();
+    user.push("other", 1).unwrap();
+    assert!(user.der_bytes_with(&schema).is_err());
+    let short = Schema::new().field(SchemaField::new("name"));
+    assert!(Sequence::der_from_bytes_with(&short, encoded.clone()).is_err());
+    assert!(user.get::<i32>("missing").is_err());
+
+    // Without a schema fields are named by their position
+    let plain = Sequence::der_from_bytes(encoded).unwrap();
+    assert_eq!(plain.get::<String>("0").unwrap(), "Rahix");
+    assert_eq!(plain.get_any("1").unwrap().tag(), 1);
 }
@@ -0,0 +1,264 @@
+use crate::constraints::{Constrained, Constraints};
+use crate::der::*;
+use crate::io::{self, Read, Write};
+use crate::jer::{Value, JER};
+use crate::oer::{Reader, OER};
+use crate::per::{BitReader, BitWriter, PER};
+use crate::prelude::*;
+use crate::xer::{Element, XERDecodeable, XEREncodeable};
+use core::convert::TryFrom;
+use core::iter::FromIterator;
+use core::ops::{Deref, DerefMut};
+
+/// Asn1 SequenceOf Type
+///
+/// Unlike `Vec<T>`, this type can only ever be a `SEQUENCE OF`. In particular `SequenceOf<u8>` is
+/// a `SEQUENCE OF INTEGER`, while `Vec<u8>` is an `OCTET STRING`.
+///
+/// # Example
+///
+/// ```
+/// # use eagre_asn1::types::SequenceOf;
+/// # use eagre_asn1::der::DER;
+///
+/// let numbers = SequenceOf(vec![1u8, 2]);
+/// assert_eq!(numbers.der_bytes().unwrap(), vec![0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02]);
+/// assert_eq!(vec![1u8, 2].der_bytes().unwrap(), vec![0x04, 0x02, 0x01, 0x02]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SequenceOf<T>(pub Vec<T>);
+
+impl<T> From<Vec<T>> for SequenceOf<T> {
+    fn from(items: Vec<T>) -> SequenceOf<T> {
+        SequenceOf(items)
+    }
+}
+
+impl<T> From<SequenceOf<T>> for Vec<T> {
+    fn from(sequence: SequenceOf<T>) -> Vec<T> {
+        sequence.0
+    }
+}
+
+impl<T> FromIterator<T> for SequenceOf<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> SequenceOf<T> {
+        SequenceOf(iter.into_iter().collect())
+    }
+}
+
+impl<T> Deref for SequenceOf<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for SequenceOf<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<T: DER> DER for SequenceOf<T> {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::Sequence
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Constructed
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        self.0.der_encode_content(w)
+    }
+
+    fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+        Vec::<T>::der_decode_content(r, length).map(SequenceOf)
+    }
+}
+
+impl<T: XEREncodeable> XEREncodeable for SequenceOf<T> {
+    fn xer_name(&self) -> String {
+        self.0.xer_name()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        self.0.xer_encode_content(e)
+    }
+}
+
+impl<T: XERDecodeable> XERDecodeable for SequenceOf<T> {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        Vec::<T>::xer_decode_content(e).map(SequenceOf)
+    }
+}
+
+impl<T: JER> JER for SequenceOf<T> {
+    fn jer_value(&self) -> io::Result<Value> {
+        self.0.jer_value()
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        Vec::<T>::jer_from_value(v).map(SequenceOf)
+    }
+}
+
+impl<T: PER> PER for SequenceOf<T> {
+    fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+        self.0.per_encode(w, c)
+    }
+
+    fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self> {
+        Vec::<T>::per_decode(r, c).map(SequenceOf)
+    }
+}
+
+impl<T: OER> OER for SequenceOf<T> {
+    fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+        self.0.oer_encode(w, c)
+    }
+
+    fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self> {
+        Vec::<T>::oer_decode(r, c).map(SequenceOf)
+    }
+}
+
+impl<T: Constrained> Constrained for SequenceOf<T> {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(false, true, false)?;
+        c.check_size(self.len())
+    }
+}
+
+impl SequenceOf<u8> {
+    fn integers(&self) -> Vec<i32> {
+        self.iter().map(|b| i32::from(*b)).collect()
+    }
+
+    fn from_integers(integers: Vec<i32>) -> io::Result<SequenceOf<u8>> {
+        integers
+            .into_iter()
+            .map(|i| {
+                u8::try_from(i).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("integer {} does not fit into u8", i),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// `SEQUENCE OF INTEGER`, decoding fails for integers outside of `0..255`
+impl DER for SequenceOf<u8> {
+    fn der_universal_tag() -> UniversalTag {
+        UniversalTag::Sequence
+    }
+
+    fn der_content() -> ContentType {
+        ContentType::Constructed
+    }
+
+    fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
+        self.integers().der_encode_content(w)
+    }
+
+    fn der_decode_content(r: &mut dyn Read, length: usize) -> io::Result<Self> {
+        SequenceOf::from_integers(Vec::<i32>::der_decode_content(r, length)?)
+    }
+}
+
+impl XEREncodeable for SequenceOf<u8> {
+    fn xer_name(&self) -> String {
+        "SEQUENCE_OF".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        self.integers().xer_encode_content(e)
+    }
+}
+
+impl XERDecodeable for SequenceOf<u8> {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        SequenceOf::from_integers(Vec::<i32>::xer_decode_content(e)?)
+    }
+}
+
+impl JER for SequenceOf<u8> {
+    fn jer_value(&self) -> io::Result<Value> {
+        self.integers().jer_value()
+    }
+
+    fn jer_from_value(v: &Value) -> io::Result<Self> {
+        SequenceOf::from_integers(Vec::<i32>::jer_from_value(v)?)
+    }
+}
+
+impl PER for SequenceOf<u8> {
+    fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+        self.integers().per_encode(w, c)
+    }
+
+    fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<Self> {
+        SequenceOf::from_integers(Vec::<i32>::per_decode(r, c)?)
+    }
+}
+
+impl OER for SequenceOf<u8> {
+    fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+        self.integers().oer_encode(w, c)
+    }
+
+    fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<Self> {
+        SequenceOf::from_integers(Vec::<i32>::oer_decode(r, c)?)
+    }
+}
+
+impl Constrained for SequenceOf<u8> {
+    fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+        c.check_applicable::<Self>(false, true, false)?;
+        c.check_size(self.len())
+    }
+}
+
+#[test]
+fn serialize_sequence_of() {
+    let bytes = SequenceOf(vec![0u8, 255]);
+    let encoded = bytes.der_bytes().unwrap();
+    assert_eq!(
+        encoded,
+        vec![0x30, 0x07, 0x02, 0x01, 0x00, 0x02, 0x02, 0x00, 0xFF]
+    );
+    assert_eq!(bytes, SequenceOf::der_from_bytes(encoded).unwrap());
+    assert_eq!(
+        bytes.xer_string().unwrap(),
+        "<SEQUENCE_OF>\n  <INTEGER>0</INTEGER>\n  <INTEGER>255</INTEGER>\n</SEQUENCE_OF>"
+    );
+    assert_eq!(
+        bytes,
+        SequenceOf::xer_from_str(&bytes.xer_string().unwrap()).unwrap()
+    );
+    assert_eq!(bytes.jer_string().unwrap(), "[0,255]");
+    assert_eq!(
+        bytes,
+        SequenceOf::uper_from_bytes(&bytes.uper_bytes().unwrap()).unwrap()
+    );
+    assert_eq!(
+        bytes,
+        SequenceOf::oer_from_bytes(&bytes.oer_bytes().unwrap()).unwrap()
+    );
+    assert!(SequenceOf::<u8>::jer_from_str("[256]").is_err());
+    assert!(SequenceOf::<u8>::der_from_bytes(vec![0x30, 0x03, 0x02, 0x01, 0xFF]).is_err());
+
+    let strings: SequenceOf<String> = vec!["a".to_string()].into();
+    assert_eq!(
+        strings.der_bytes().unwrap(),
+        vec!["a".to_string()].der_bytes().unwrap()
+    );
+    assert_eq!(
+        strings,
+        SequenceOf::der_from_bytes(strings.der_bytes().unwrap()).unwrap()
+    );
+}
@@ -1,9 +1,18 @@
+use crate::constraints::{Constrained, Constraints};
 use crate::der::{self, DER};
-use std::io::{self, Read, Write};
+use crate::io::{self, Read, Write};
+use crate::jer::{Value, JER};
+use crate::oer::{self, Reader, OER};
+use crate::per::{self, Alphabet, BitReader, BitWriter, PER};
+use crate::prelude::*;
+use crate::xer::{Element, XERDecodeable, XEREncodeable};
 
 // Macro for lazy people like me
+//
+// The alphabet is the character set of known-multiplier types for PER and OER, other types are
+// encoded as length prefixed octets
 macro_rules! string_type {
-    ($name:ident) => {
+    ($name:ident, $alphabet:expr) => {
         /// Asn1 String Type
         ///
         /// Currently restricted character sets are not enforced, so it is
@@ -34,7 +43,7 @@ macro_rules! string_type {
             }
 
             fn der_encode_content(&self, w: &mut dyn Write) -> io::Result<()> {
-                w.write(self.0.as_bytes())?;
+                w.write_all(self.0.as_bytes())?;
                 Ok(())
             }
 
@@ -45,19 +54,125 @@ macro_rules! string_type {
                 Ok($name(buffer))
             }
         }
+
+        impl XEREncodeable for $name {
+            fn xer_name(&self) -> String {
+                stringify!($name).to_string()
+            }
+
+            fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+                e.push_text(&self.0);
+                Ok(())
+            }
+        }
+
+        impl XERDecodeable for $name {
+            fn xer_decode_content(e: &Element) -> io::Result<$name> {
+                Ok($name(e.text()?))
+            }
+        }
+
+        impl JER for $name {
+            fn jer_value(&self) -> io::Result<Value> {
+                Ok(Value::String(self.0.clone()))
+            }
+
+            fn jer_from_value(v: &Value) -> io::Result<$name> {
+                Ok($name(v.as_str()?.to_string()))
+            }
+        }
+
+        impl PER for $name {
+            fn per_encode(&self, w: &mut BitWriter, c: &Constraints) -> io::Result<()> {
+                let alphabet: Option<Alphabet> = $alphabet;
+                match alphabet {
+                    Some(alphabet) => {
+                        per::encode_chars(w, &self.0, c, &alphabet, stringify!($name))
+                    }
+                    None => {
+                        c.check_size(self.0.chars().count())?;
+                        per::encode_octets(w, self.0.as_bytes())
+                    }
+                }
+            }
+
+            fn per_decode(r: &mut BitReader, c: &Constraints) -> io::Result<$name> {
+                let alphabet: Option<Alphabet> = $alphabet;
+                let s = match alphabet {
+                    Some(alphabet) => per::decode_chars(r, c, &alphabet, stringify!($name))?,
+                    None => {
+                        let s = String::from_utf8(per::decode_octets(r)?).map_err(|_| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                concat!("invalid ", stringify!($name)),
+                            )
+                        })?;
+                        c.check_size(s.chars().count())?;
+                        s
+                    }
+                };
+                Ok($name(s))
+            }
+        }
+
+        impl Constrained for $name {
+            fn check_constraints(&self, c: &Constraints) -> io::Result<()> {
+                c.check_applicable::<Self>(false, true, true)?;
+                c.check_str(&self.0)
+            }
+        }
+
+        impl OER for $name {
+            fn oer_encode(&self, w: &mut Vec<u8>, c: &Constraints) -> io::Result<()> {
+                let alphabet: Option<Alphabet> = $alphabet;
+                match alphabet {
+                    Some(alphabet) => {
+                        oer::encode_chars(w, &self.0, c, &alphabet, stringify!($name))
+                    }
+                    None => {
+                        c.check_size(self.0.chars().count())?;
+                        oer::encode_octets(w, self.0.as_bytes(), &Constraints::NONE)
+                    }
+                }
+            }
+
+            fn oer_decode(r: &mut Reader, c: &Constraints) -> io::Result<$name> {
+                let alphabet: Option<Alphabet> = $alphabet;
+                let s = match alphabet {
+                    Some(alphabet) => oer::decode_chars(r, c, &alphabet, stringify!($name))?,
+                    None => {
+                        let s = String::from_utf8(oer::decode_octets(r, &Constraints::NONE)?)
+                            .map_err(|_| {
+                                io::Error::new(
+                                    io::ErrorKind::InvalidInput,
+                                    concat!("invalid ", stringify!($name)),
+                                )
+                            })?;
+                        c.check_size(s.chars().count())?;
+                        s
+                    }
+                };
+                Ok($name(s))
+            }
+        }
     };
 }
 
-string_type!(NumericString);
-string_type!(PrintableString);
-string_type!(T61String);
-string_type!(VideotexString);
-string_type!(IA5String);
-string_type!(GraphicString);
-string_type!(VisibleString);
-string_type!(GeneralString);
-string_type!(UniversalString);
-string_type!(CharacterString);
+string_type!(NumericString, Some(Alphabet::chars(" 0123456789")));
+string_type!(
+    PrintableString,
+    Some(Alphabet::chars(
+        "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789 '()+,-./:=?"
+    ))
+);
+string_type!(T61String, None);
+string_type!(VideotexString, None);
+string_type!(IA5String, Some(Alphabet::Range(0, 0x7F)));
+string_type!(GraphicString, None);
+string_type!(VisibleString, Some(Alphabet::Range(0x20, 0x7E)));
+string_type!(GeneralString, None);
+string_type!(UniversalString, Some(Alphabet::Range(0, 0xFFFF_FFFF)));
+string_type!(CharacterString, None);
 
 #[cfg(test)]
 mod tests {
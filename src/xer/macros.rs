@@ -0,0 +1,153 @@
+/// XER implementation for sequences, used by `der_sequence!` and `implement_xer!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! xer_sequence {
+    ($struct_name:ident $(<$lt:lifetime>)? [$extensible:tt $(, $unknown:ident)?] : $($field_name:ident $(BY $id:ident)? $(= [$($c:tt)*])?),+) => {
+        impl $crate::xer::XEREncodeable for $struct_name $(<$lt>)? {
+            fn xer_name(&self) -> $crate::prelude::String {
+                $crate::prelude::String::from(stringify!($struct_name))
+            }
+
+            fn xer_encode_content(&self, e: &mut $crate::xer::Element) -> $crate::io::Result<()> {
+                $(
+                    if $crate::xer::XEREncodeable::xer_present(&self.$field_name) {
+                        $($crate::der_constraints!(@check &self.$field_name, stringify!($field_name), stringify!($struct_name); $($c)*);)?
+                        let mut field = $crate::xer::Element::new(stringify!($field_name));
+                        $crate::xer::XEREncodeable::xer_encode_content(&self.$field_name, &mut field)?;
+                        e.push(field);
+                    }
+                )+
+                Ok(())
+            }
+        }
+
+        impl $crate::xer::XERDecodeable for $struct_name $(<$lt>)? {
+            fn xer_decode_content(e: &$crate::xer::Element) -> $crate::io::Result<Self> {
+                let mut elements = e.elements()?.into_iter().peekable();
+                $(
+                    let $field_name = match (elements.peek(), $crate::xer::XERDecodeable::xer_absent()) {
+                        (Some(field), _) if field.name == stringify!($field_name) => {
+                            $crate::xer::XERDecodeable::xer_decode_content(elements.next().unwrap())?
+                        },
+                        (_, Some(absent)) => absent,
+                        (Some(field), None) => return Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                            $crate::prelude::format!("unknown element <{}> in <{}>, expected <{}>", field.name, e.name, stringify!($field_name)))),
+                        (None, None) => return Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                            $crate::prelude::format!("missing element <{}> in <{}>", stringify!($field_name), e.name))),
+                    };
+                    $($crate::der_constraints!(@check &$field_name, stringify!($field_name), stringify!($struct_name); $($c)*);)?
+                    $(let $field_name = $crate::types::OpenType::open_resolve($field_name, &$id)?;)?
+                )+
+                let extensible: bool = $extensible;
+                match elements.next() {
+                    Some(field) if !extensible => return Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                        $crate::prelude::format!("unknown element <{}> in <{}>", field.name, e.name))),
+                    // Extension additions of a newer version are skipped
+                    _ => (),
+                }
+                Ok($struct_name {
+                    $(
+                        $field_name: $field_name,
+                    )+
+                    $(
+                        $unknown: $crate::prelude::Vec::new(),
+                    )?
+                })
+            }
+        }
+    };
+}
+
+/// XER implementation for choices, used by `der_choice!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! xer_choice {
+    ($choice_name:ident [$extensible:tt $(, $unknown:ident)?] : $($variant_name:ident [$($shape:tt)*] = [$($c:tt)*]),+) => {
+        impl $crate::xer::XEREncodeable for $choice_name {
+            fn xer_name(&self) -> $crate::prelude::String {
+                $crate::prelude::String::from(stringify!($choice_name))
+            }
+
+            fn xer_encode_content(&self, e: &mut $crate::xer::Element) -> $crate::io::Result<()> {
+                match self {
+                    $($crate::der_choice!(@pattern $choice_name $variant_name [$($shape)*] payload) => {
+                        let val = $crate::der_choice!(@payload $variant_name [$($shape)*] payload);
+                        $crate::der_constraints!(@check val, stringify!($variant_name), stringify!($choice_name); $($c)*);
+                        let mut variant = $crate::xer::Element::new(stringify!($variant_name));
+                        $crate::xer::XEREncodeable::xer_encode_content(val, &mut variant)?;
+                        e.push(variant);
+                    },)+
+                    $(&$choice_name::$unknown(_) => return Err($crate::der::unknown_alternative(stringify!($choice_name))),)?
+                }
+                Ok(())
+            }
+
+            fn xer_value_list() -> bool {
+                true
+            }
+        }
+
+        impl $crate::xer::XERDecodeable for $choice_name {
+            fn xer_decode_content(e: &$crate::xer::Element) -> $crate::io::Result<Self> {
+                let variant = e.single_element()?;
+                $(
+                    if variant.name == stringify!($variant_name) {
+                        let value = $crate::xer::XERDecodeable::xer_decode_content(variant)?;
+                        $crate::der_constraints!(@check &value, stringify!($variant_name), stringify!($choice_name); $($c)*);
+                        return Ok($crate::der_choice!(@construct $choice_name $variant_name [$($shape)*] value));
+                    }
+                )+
+                Err($crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                    $crate::prelude::format!("unknown alternative <{}> in <{}>", variant.name, e.name)))
+            }
+
+            fn xer_value_list() -> bool {
+                true
+            }
+        }
+    };
+}
+
+/// XER implementation for enumerations, used by `der_enumerated!`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! xer_enumerated {
+    ($enum_name:ident [$extensible:tt $(, $unknown:ident)? $(; $other:ident)?], $($enum_variant:ident = $value:expr),+) => {
+        impl $crate::xer::XEREncodeable for $enum_name {
+            fn xer_name(&self) -> $crate::prelude::String {
+                $crate::prelude::String::from(stringify!($enum_name))
+            }
+
+            fn xer_encode_content(&self, e: &mut $crate::xer::Element) -> $crate::io::Result<()> {
+                e.push($crate::xer::Element::new(match self {
+                    $(&$enum_name::$enum_variant => stringify!($enum_variant),)+
+                    $(&$enum_name::$unknown => return Err($crate::der::unknown_value(stringify!($enum_name))),)?
+                    $(&$enum_name::$other(_) => return Err($crate::der::unknown_value(stringify!($enum_name))),)?
+                }));
+                Ok(())
+            }
+
+            fn xer_value_list() -> bool {
+                true
+            }
+        }
+
+        impl $crate::xer::XERDecodeable for $enum_name {
+            fn xer_decode_content(e: &$crate::xer::Element) -> $crate::io::Result<Self> {
+                let name = e.identifier()?;
+                $(
+                    if name == stringify!($enum_variant) {
+                        return Ok($enum_name::$enum_variant);
+                    }
+                )+
+                let unknown: Option<Self> = None $(.or(Some($enum_name::$unknown)))?;
+                unknown.ok_or_else(|| $crate::io::Error::new($crate::io::ErrorKind::InvalidInput,
+                    $crate::prelude::format!("unknown enumeration value \"{}\" in <{}>", name, e.name)))
+            }
+
+            fn xer_value_list() -> bool {
+                true
+            }
+        }
+    };
+}
@@ -0,0 +1,513 @@
+use crate::der::{into_array, tuple_mismatch, Component};
+use crate::io::{self, Read, Write};
+use crate::prelude::*;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+
+/// XML Element tree
+pub mod xml;
+#[doc(hidden)]
+#[macro_use]
+pub mod macros;
+
+#[cfg(test)]
+mod test;
+
+pub use self::xml::{Content, Element};
+
+/// XER Encoding Variant
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Mode {
+    /// BASIC-XER, human readable with indentation
+    Basic,
+    /// CANONICAL-XER, without any whitespace between elements
+    Canonical,
+}
+
+/// The encoding trait for XER
+///
+/// Like DER, encoding is split into encoding the content of a value and wrapping it into an
+/// element. The element name is only used for top level values and items of a `SEQUENCE OF`,
+/// components of sequences and choices are named after the field or variant instead.
+///
+/// Types implementing `DER` through `der_sequence!`, `der_choice!` or `der_enumerated!` implement
+/// this trait when `XER` is listed in `WITH(...)` after the type name.
+///
+/// # Example
+/// ```
+/// # #[macro_use]
+/// # extern crate eagre_asn1;
+/// use eagre_asn1::xer::{XERDecodeable, XEREncodeable};
+///
+/// # #[derive(Debug, PartialEq)]
+/// struct User {
+///     pub name: String,
+///     pub admin: bool,
+/// }
+///
+/// der_sequence! {
+///     User WITH(XER):
+///         name:  NOTAG TYPE String,
+///         admin: NOTAG TYPE bool,
+/// }
+///
+/// # fn main() {
+/// let user = User { name: "Rahix".to_string(), admin: true };
+/// let encoded = user.xer_string().unwrap();
+/// assert_eq!(encoded, "<User>\n  <name>Rahix</name>\n  <admin>\n    <true/>\n  </admin>\n</User>");
+/// assert_eq!(user, User::xer_from_str(&encoded).unwrap());
+/// # }
+/// ```
+pub trait XEREncodeable {
+    /// Element name, for builtin types this is the ASN.1 type name like `INTEGER`
+    fn xer_name(&self) -> String;
+    /// Encode the content of this value into `e`
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()>;
+    /// Return whether values are encoded as a single empty element, like `<true/>`
+    ///
+    /// Items of such types are not wrapped into an extra element in a `SEQUENCE OF`.
+    fn xer_value_list() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+    /// Return whether this value is encoded at all, `false` only for absent optional values
+    fn xer_present(&self) -> bool {
+        true
+    }
+    /// Create Element from this value
+    fn xer_element(&self) -> io::Result<Element> {
+        let mut e = Element::new(&self.xer_name());
+        self.xer_encode_content(&mut e)?;
+        Ok(e)
+    }
+    /// Encode full element using BASIC-XER
+    fn xer_encode<W: Write>(&self, stream: &mut W) -> io::Result<()>
+    where
+        Self: Sized,
+    {
+        self.xer_element()?.write(stream, Mode::Basic)
+    }
+    /// Encode full element using CANONICAL-XER
+    fn xer_encode_canonical<W: Write>(&self, stream: &mut W) -> io::Result<()>
+    where
+        Self: Sized,
+    {
+        self.xer_element()?.write(stream, Mode::Canonical)
+    }
+    /// Return the BASIC-XER encoding as a string
+    fn xer_string(&self) -> io::Result<String>
+    where
+        Self: Sized,
+    {
+        let mut stream = Vec::new();
+        self.xer_encode(&mut stream)?;
+        // The writer only ever writes valid utf8
+        Ok(String::from_utf8(stream).unwrap())
+    }
+}
+
+/// The decoding trait for XER
+///
+/// Decoding accepts both BASIC-XER and CANONICAL-XER.
+pub trait XERDecodeable: Sized {
+    /// Decode the content of element `e`
+    fn xer_decode_content(e: &Element) -> io::Result<Self>;
+    /// See `XEREncodeable::xer_value_list()`
+    fn xer_value_list() -> bool {
+        false
+    }
+    /// Value to use if the element is missing from a sequence, `Some` only for optional types
+    fn xer_absent() -> Option<Self> {
+        None
+    }
+    /// Decode a full XML document
+    fn xer_decode<R: Read>(stream: &mut R) -> io::Result<Self> {
+        let mut text = String::new();
+        stream.read_to_string(&mut text)?;
+        Self::xer_from_str(&text)
+    }
+    /// Decode from a string
+    fn xer_from_str(text: &str) -> io::Result<Self> {
+        Self::xer_decode_content(&Element::parse(text)?)
+    }
+}
+
+impl XEREncodeable for bool {
+    fn xer_name(&self) -> String {
+        "BOOLEAN".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        e.push(Element::new(if *self { "true" } else { "false" }));
+        Ok(())
+    }
+
+    fn xer_value_list() -> bool {
+        true
+    }
+}
+
+impl XERDecodeable for bool {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        match e.identifier()?.as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid boolean in <{}>", e.name),
+            )),
+        }
+    }
+
+    fn xer_value_list() -> bool {
+        true
+    }
+}
+
+impl XEREncodeable for i32 {
+    fn xer_name(&self) -> String {
+        "INTEGER".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        e.push_text(&self.to_string());
+        Ok(())
+    }
+}
+
+impl XERDecodeable for i32 {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        let text = e.text()?;
+        let digits = xml::trim(&text);
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid integer in <{}>", e.name),
+            )
+        };
+        // X.680 only allows a leading "-", `parse()` also accepts "+"
+        if digits.starts_with('+') {
+            return Err(invalid());
+        }
+        digits.parse().map_err(|_| invalid())
+    }
+}
+
+impl XEREncodeable for String {
+    fn xer_name(&self) -> String {
+        "UTF8String".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        e.push_text(self);
+        Ok(())
+    }
+}
+
+impl XERDecodeable for String {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        e.text()
+    }
+}
+
+impl XEREncodeable for &str {
+    fn xer_name(&self) -> String {
+        "UTF8String".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        e.push_text(self);
+        Ok(())
+    }
+}
+
+impl XEREncodeable for &[u8] {
+    fn xer_name(&self) -> String {
+        "OCTET_STRING".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        e.push_text(&hex_encode(self));
+        Ok(())
+    }
+}
+
+impl XEREncodeable for Vec<u8> {
+    fn xer_name(&self) -> String {
+        "OCTET_STRING".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        e.push_text(&hex_encode(self));
+        Ok(())
+    }
+}
+
+impl XERDecodeable for Vec<u8> {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        hex_decode(&e.text()?)
+    }
+}
+
+impl<T: XEREncodeable> XEREncodeable for Vec<T> {
+    fn xer_name(&self) -> String {
+        "SEQUENCE_OF".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        for item in self.iter() {
+            encode_item(item, e)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: XERDecodeable> XERDecodeable for Vec<T> {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        e.elements()?
+            .into_iter()
+            .map(|item| decode_item(item, e))
+            .collect()
+    }
+}
+
+impl<const N: usize> XEREncodeable for [u8; N] {
+    fn xer_name(&self) -> String {
+        "OCTET_STRING".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        e.push_text(&hex_encode(self));
+        Ok(())
+    }
+}
+
+impl<const N: usize> XERDecodeable for [u8; N] {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        into_array(hex_decode(&e.text()?)?)
+    }
+}
+
+impl<T: XEREncodeable, const N: usize> XEREncodeable for [T; N] {
+    fn xer_name(&self) -> String {
+        "SEQUENCE_OF".to_string()
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        for item in self.iter() {
+            encode_item(item, e)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: XERDecodeable, const N: usize> XERDecodeable for [T; N] {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        into_array(Vec::<T>::xer_decode_content(e)?)
+    }
+}
+
+/// Implement XER for tuples, whose components are encoded like the items of a `SEQUENCE OF`
+macro_rules! xer_tuple {
+    () => {};
+    (($first:ident $first_var:ident) $(($ty:ident $var:ident))*) => {
+        impl<$first: XEREncodeable, $($ty: XEREncodeable),*> XEREncodeable for ($first, $($ty,)*) {
+            fn xer_name(&self) -> String {
+                "SEQUENCE".to_string()
+            }
+
+            fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+                let ($first_var, $($var,)*) = self;
+                encode_item($first_var, e)?;
+                $(encode_item($var, e)?;)*
+                Ok(())
+            }
+        }
+
+        impl<$first: XERDecodeable, $($ty: XERDecodeable),*> XERDecodeable for ($first, $($ty,)*) {
+            fn xer_decode_content(e: &Element) -> io::Result<Self> {
+                let mut items = e.elements()?.into_iter();
+                let $first_var = decode_item(items.next().ok_or_else(tuple_mismatch)?, e)?;
+                $(let $var = decode_item(items.next().ok_or_else(tuple_mismatch)?, e)?;)*
+                if items.next().is_some() {
+                    return Err(tuple_mismatch());
+                }
+                Ok(($first_var, $($var,)*))
+            }
+        }
+
+        xer_tuple!($(($ty $var))*);
+    };
+}
+
+xer_tuple!((A a) (B b) (C c) (D d) (E e) (F f) (G g) (H h) (I i) (J j) (K k) (L l));
+
+/// Implement XER for smart pointers, which are encoded like the value they point to
+macro_rules! xer_pointer {
+    ($($pointer:ident $(<$lt:lifetime>)?),+) => {$(
+        impl<T: XEREncodeable> XEREncodeable for $pointer<$($lt,)? T> {
+            fn xer_name(&self) -> String {
+                (**self).xer_name()
+            }
+
+            fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+                (**self).xer_encode_content(e)
+            }
+
+            fn xer_value_list() -> bool {
+                T::xer_value_list()
+            }
+
+            fn xer_present(&self) -> bool {
+                (**self).xer_present()
+            }
+
+            fn xer_element(&self) -> io::Result<Element> {
+                (**self).xer_element()
+            }
+        }
+
+        impl<T: XERDecodeable> XERDecodeable for $pointer<$($lt,)? T> {
+            fn xer_decode_content(e: &Element) -> io::Result<Self> {
+                T::xer_decode_content(e).map($pointer::new)
+            }
+
+            fn xer_value_list() -> bool {
+                T::xer_value_list()
+            }
+
+            fn xer_absent() -> Option<Self> {
+                T::xer_absent().map($pointer::new)
+            }
+        }
+    )+};
+}
+
+xer_pointer!(Box, Rc, Arc, Component<'_>);
+
+/// Absent values are left out of the enclosing sequence
+impl<T: XEREncodeable> XEREncodeable for Option<T> {
+    fn xer_name(&self) -> String {
+        match self {
+            Some(value) => value.xer_name(),
+            None => String::new(),
+        }
+    }
+
+    fn xer_encode_content(&self, e: &mut Element) -> io::Result<()> {
+        match self {
+            Some(value) => value.xer_encode_content(e),
+            None => Ok(()),
+        }
+    }
+
+    fn xer_value_list() -> bool {
+        T::xer_value_list()
+    }
+
+    fn xer_present(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T: XERDecodeable> XERDecodeable for Option<T> {
+    fn xer_decode_content(e: &Element) -> io::Result<Self> {
+        T::xer_decode_content(e).map(Some)
+    }
+
+    fn xer_value_list() -> bool {
+        T::xer_value_list()
+    }
+
+    fn xer_absent() -> Option<Self> {
+        Some(None)
+    }
+}
+
+/// Encode an item of a `SEQUENCE OF` into `e`, values of value list types are not wrapped
+fn encode_item<T: XEREncodeable>(item: &T, e: &mut Element) -> io::Result<()> {
+    if T::xer_value_list() {
+        item.xer_encode_content(e)
+    } else {
+        e.push(item.xer_element()?);
+        Ok(())
+    }
+}
+
+/// Decode an item of a `SEQUENCE OF` from its element in `parent`
+fn decode_item<T: XERDecodeable>(item: &Element, parent: &Element) -> io::Result<T> {
+    if T::xer_value_list() {
+        let mut wrapper = Element::new(&parent.name);
+        wrapper.push(item.clone());
+        T::xer_decode_content(&wrapper)
+    } else {
+        T::xer_decode_content(item)
+    }
+}
+
+/// Encode bytes as uppercase hex, as used for `OCTET STRING`
+pub fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// Decode hex, ignoring whitespace
+pub fn hex_decode(text: &str) -> io::Result<Vec<u8>> {
+    let digits = text
+        .chars()
+        .filter(|c| !xml::is_whitespace(*c))
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid hex digit"))?;
+    if digits.len() % 2 == 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "odd number of hex digits",
+        ));
+    }
+    Ok(digits.chunks(2).map(|d| d[0] << 4 | d[1]).collect())
+}
+
+/// Macro to implement `XEREncodeable` and `XERDecodeable` for a struct
+///
+/// The struct is encoded like a sequence with one element per field, in the given order.
+/// Decoding fails with an error naming the offending element if an unknown element is found or a
+/// field is missing. Types using `der_sequence!` with `WITH(XER)` implement XER and do not need this.
+///
+/// # Example
+/// ```
+/// # #[macro_use]
+/// # extern crate eagre_asn1;
+/// use eagre_asn1::xer::XERDecodeable;
+///
+/// # #[derive(Debug, PartialEq)]
+/// struct User {
+///     pub name: String,
+///     pub id: i32,
+/// }
+///
+/// implement_xer!(User, name, id);
+///
+/// # fn main() {
+/// let fixture = "<?xml version=\"1.0\"?>
+/// <User>
+///     <name>Rahix</name>
+///     <id> 12 </id>
+/// </User>";
+/// let user = User::xer_from_str(fixture).unwrap();
+/// assert_eq!(user, User { name: "Rahix".to_string(), id: 12 });
+///
+/// let error = User::xer_from_str("<User><nick>Rahix</nick></User>").unwrap_err();
+/// assert_eq!(error.to_string(), "unknown element <nick> in <User>, expected <name>");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! implement_xer {
+    ($struct_name:ident, $($field_name:ident),+) => {
+        $crate::xer_sequence!($struct_name [false]: $($field_name),+);
+    };
+    ($struct_name:ident, $($field_name:ident),+,) => {
+        $crate::xer_sequence!($struct_name [false]: $($field_name),+);
+    };
+}
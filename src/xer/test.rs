@@ -0,0 +1,209 @@
+use super::*;
+use crate::types::{IA5String, Null};
+
+#[derive(Debug, PartialEq)]
+struct TestStruct {
+    pub alpha: i32,
+    pub beta: Vec<bool>,
+    pub gamma: String,
+    pub delta: TestChoice,
+    pub epsilon: Vec<TestEnum>,
+}
+
+der_sequence! {TestStruct WITH(XER):
+    alpha: NOTAG TYPE i32,
+    beta: EXPLICIT TAG CONTEXT 42; TYPE Vec<bool>,
+    gamma: IMPLICIT TAG APPLICATION 397; TYPE String,
+    delta: EXPLICIT TAG CONTEXT 1; TYPE TestChoice,
+    epsilon: NOTAG TYPE Vec<TestEnum>,
+}
+
+#[derive(Debug, PartialEq)]
+enum TestChoice {
+    Alpha(Vec<u8>),
+    Beta(Null),
+}
+
+der_choice! {TestChoice WITH(XER):
+    Alpha: IMPLICIT TAG CONTEXT 0; TYPE Vec<u8>,
+    Beta: IMPLICIT TAG CONTEXT 1; TYPE Null,
+}
+
+#[derive(Debug, PartialEq)]
+enum TestEnum {
+    Alpha = 1,
+    Beta = 2,
+}
+
+der_enumerated!(TestEnum WITH(XER), Alpha, Beta);
+
+fn test_struct() -> TestStruct {
+    TestStruct {
+        alpha: -42,
+        beta: vec![true, false],
+        gamma: "<Hello> & \u{0}World\n".to_string(),
+        delta: TestChoice::Alpha(vec![0x0A, 0xFF]),
+        epsilon: vec![TestEnum::Beta],
+    }
+}
+
+#[test]
+fn encode_basic() {
+    assert_eq!(
+        test_struct().xer_string().unwrap(),
+        "<TestStruct>
+  <alpha>-42</alpha>
+  <beta>
+    <true/>
+    <false/>
+  </beta>
+  <gamma>&lt;Hello&gt; &amp; <nul/>World
+</gamma>
+  <delta>
+    <Alpha>0AFF</Alpha>
+  </delta>
+  <epsilon>
+    <Beta/>
+  </epsilon>
+</TestStruct>"
+    );
+}
+
+#[test]
+fn encode_canonical() {
+    let mut stream = vec![];
+    test_struct().xer_encode_canonical(&mut stream).unwrap();
+    assert_eq!(
+        String::from_utf8(stream).unwrap(),
+        "<TestStruct><alpha>-42</alpha><beta><true/><false/></beta>\
+         <gamma>&lt;Hello&gt; &amp; <nul/>World<lf/></gamma><delta><Alpha>0AFF</Alpha></delta>\
+         <epsilon><Beta/></epsilon></TestStruct>"
+    );
+}
+
+#[test]
+fn roundtrip() {
+    let data = test_struct();
+    assert_eq!(
+        data,
+        TestStruct::xer_from_str(&data.xer_string().unwrap()).unwrap()
+    );
+    let mut stream = vec![];
+    data.xer_encode_canonical(&mut stream).unwrap();
+    assert_eq!(data, TestStruct::xer_decode(&mut &stream[..]).unwrap());
+
+    let strings = vec![IA5String::from("foo".to_string())];
+    let decoded = Vec::<IA5String>::xer_from_str(&strings.xer_string().unwrap()).unwrap();
+    assert_eq!("foo", String::from(decoded.into_iter().next().unwrap()));
+}
+
+#[test]
+fn decode_alternative_forms() {
+    assert!(bool::xer_from_str("<BOOLEAN> true </BOOLEAN>").unwrap());
+    assert_eq!(
+        i32::xer_from_str("<INTEGER>\n  12\n</INTEGER>").unwrap(),
+        12
+    );
+    assert_eq!(
+        TestEnum::xer_from_str("<TestEnum>Alpha</TestEnum>").unwrap(),
+        TestEnum::Alpha
+    );
+    assert_eq!(
+        Vec::<u8>::xer_from_str("<a>0a ff</a>").unwrap(),
+        vec![0x0A, 0xFF]
+    );
+}
+
+#[test]
+fn decode_normalizes_whitespace() {
+    let text = "<SEQUENCE_OF>\r\n\t<UTF8String>a\r\nb\rc</UTF8String>\r\n</SEQUENCE_OF>";
+    let decoded = Vec::<String>::xer_from_str(text).unwrap();
+    assert_eq!(decoded, vec!["a\nb\nc".to_string()]);
+    // Only space, tab, line feed and carriage return are white space in XER
+    assert!(i32::xer_from_str("<INTEGER>\u{a0}1</INTEGER>").is_err());
+}
+
+#[test]
+fn decode_errors() {
+    let unknown = "<TestChoice><Gamma>1</Gamma></TestChoice>";
+    let error = TestChoice::xer_from_str(unknown).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unknown alternative <Gamma> in <TestChoice>"
+    );
+    let missing = "<TestStruct><alpha>1</alpha></TestStruct>";
+    assert!(TestStruct::xer_from_str(missing).is_err());
+    assert!(TestEnum::xer_from_str("<TestEnum><Gamma/></TestEnum>").is_err());
+    assert!(i32::xer_from_str("<INTEGER>1<a/></INTEGER>").is_err());
+    assert!(i32::xer_from_str("<INTEGER>+5</INTEGER>").is_err());
+    assert_eq!(i32::xer_from_str("<INTEGER> -5 </INTEGER>").unwrap(), -5);
+}
+
+#[derive(Debug, PartialEq)]
+struct OptionalStruct {
+    pub alpha: Option<i32>,
+    pub beta: Option<bool>,
+}
+
+der_sequence! {OptionalStruct WITH(XER):
+    alpha: IMPLICIT TAG CONTEXT 0; TYPE Option<i32>,
+    beta: IMPLICIT TAG CONTEXT 1; TYPE Option<bool>,
+}
+
+#[test]
+fn optional_elements() {
+    let data = OptionalStruct {
+        alpha: None,
+        beta: Some(false),
+    };
+    let encoded = data.xer_string().unwrap();
+    assert_eq!(
+        encoded,
+        "<OptionalStruct>\n  <beta>\n    <false/>\n  </beta>\n</OptionalStruct>"
+    );
+    assert_eq!(data, OptionalStruct::xer_from_str(&encoded).unwrap());
+    assert_eq!(
+        OptionalStruct::xer_from_str("<OptionalStruct/>").unwrap(),
+        OptionalStruct {
+            alpha: None,
+            beta: None,
+        }
+    );
+    assert!(OptionalStruct::xer_from_str("<OptionalStruct><gamma/></OptionalStruct>").is_err());
+}
+
+#[derive(Debug, PartialEq)]
+struct Extensible {
+    pub alpha: i32,
+    pub shade: Shade,
+}
+
+der_sequence! {Extensible WITH(XER):
+    alpha: NOTAG TYPE i32,
+    shade: NOTAG TYPE Shade,
+    ...
+}
+
+#[derive(Debug, PartialEq)]
+enum Shade {
+    Red = 0,
+    Unknown,
+}
+
+der_enumerated!(Shade WITH(XER), Red, ... Unknown);
+
+#[test]
+fn extension_markers() {
+    let decoded = Extensible::xer_from_str(
+        "<Extensible><alpha>1</alpha><shade><Blue/></shade><eta>2</eta></Extensible>",
+    )
+    .unwrap();
+    assert_eq!(
+        decoded,
+        Extensible {
+            alpha: 1,
+            shade: Shade::Unknown,
+        }
+    );
+    assert!(decoded.xer_string().is_err());
+}
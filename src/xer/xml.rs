@@ -0,0 +1,443 @@
+use crate::der::MAX_NESTING_DEPTH;
+use crate::io::{self, Write};
+use crate::prelude::*;
+
+use super::Mode;
+
+/// Names of the X.680 escape elements for the control characters 0x00 to 0x1F
+const CONTROL_NAMES: [&str; 32] = [
+    "nul", "soh", "stx", "etx", "eot", "enq", "ack", "bel", "bs", "tab", "lf", "vt", "ff", "cr",
+    "so", "si", "dle", "dc1", "dc2", "dc3", "dc4", "nak", "syn", "etb", "can", "em", "sub", "esc",
+    "is4", "is3", "is2", "is1",
+];
+
+/// Return whether `c` is XML white space, i.e. space, tab, line feed or carriage return
+///
+/// Only these characters are white space in XER, other unicode white space is significant.
+pub fn is_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\n' || c == '\r'
+}
+
+/// Remove leading and trailing XML white space
+pub fn trim(text: &str) -> &str {
+    text.trim_matches(is_whitespace)
+}
+
+/// XML Element
+///
+/// Intermediate representation of XER encoded values, comparable to `der::Intermediate`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Element {
+    /// Element name
+    pub name: String,
+    /// Child elements and text
+    pub children: Vec<Content>,
+}
+
+/// Content of an XML Element
+#[derive(Clone, Debug, PartialEq)]
+pub enum Content {
+    /// Child element
+    Element(Element),
+    /// Unescaped character data
+    Text(String),
+}
+
+impl Element {
+    /// Create new Element without content
+    pub fn new(name: &str) -> Element {
+        Element {
+            name: name.to_string(),
+            children: vec![],
+        }
+    }
+
+    /// Append a child element
+    pub fn push(&mut self, e: Element) {
+        self.children.push(Content::Element(e));
+    }
+
+    /// Append text
+    pub fn push_text(&mut self, text: &str) {
+        if let Some(Content::Text(ref mut t)) = self.children.last_mut() {
+            t.push_str(text);
+            return;
+        }
+        self.children.push(Content::Text(text.to_string()));
+    }
+
+    /// Return all child elements
+    ///
+    /// Whitespace between the children is ignored, any other text is an error.
+    pub fn elements(&self) -> io::Result<Vec<&Element>> {
+        let mut elements = vec![];
+        for c in self.children.iter() {
+            match c {
+                Content::Element(e) => elements.push(e),
+                Content::Text(t) if trim(t).is_empty() => (),
+                Content::Text(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("unexpected text in <{}>", self.name),
+                    ))
+                }
+            }
+        }
+        Ok(elements)
+    }
+
+    /// Return the only child element
+    pub fn single_element(&self) -> io::Result<&Element> {
+        let elements = self.elements()?;
+        if elements.len() != 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("expected exactly one element in <{}>", self.name),
+            ));
+        }
+        Ok(elements[0])
+    }
+
+    /// Return the identifier of an empty child element or the text content
+    ///
+    /// This is used for values which may either be written as `<true/>` or as `true`.
+    pub fn identifier(&self) -> io::Result<String> {
+        if self.children.iter().all(|c| match c {
+            Content::Text(_) => true,
+            Content::Element(_) => false,
+        }) {
+            return Ok(trim(&self.text()?).to_string());
+        }
+        let e = self.single_element()?;
+        if !e.children.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unexpected content in <{}>", e.name),
+            ));
+        }
+        Ok(e.name.clone())
+    }
+
+    /// Return the text content
+    ///
+    /// Control character escapes like `<nul/>` are replaced by the characters they stand for.
+    pub fn text(&self) -> io::Result<String> {
+        let mut text = String::new();
+        for c in self.children.iter() {
+            match c {
+                Content::Text(t) => text.push_str(t),
+                Content::Element(e) => match CONTROL_NAMES.iter().position(|n| *n == e.name) {
+                    Some(i) if e.children.is_empty() => text.push(i as u8 as char),
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("unexpected element <{}> in <{}>", e.name, self.name),
+                        ))
+                    }
+                },
+            }
+        }
+        Ok(text)
+    }
+
+    /// Write this Element
+    ///
+    /// `Mode::Basic` puts every child element on its own, indented line, `Mode::Canonical` does
+    /// not add any whitespace.
+    pub fn write(&self, w: &mut dyn Write, mode: Mode) -> io::Result<()> {
+        self.write_indented(w, mode, 0)
+    }
+
+    fn write_indented(&self, w: &mut dyn Write, mode: Mode, depth: usize) -> io::Result<()> {
+        if self.children.is_empty() {
+            return write!(w, "<{}/>", self.name);
+        }
+        write!(w, "<{}>", self.name)?;
+        let pretty = mode == Mode::Basic
+            && self.children.iter().all(|c| match c {
+                Content::Element(_) => true,
+                Content::Text(_) => false,
+            });
+        for c in self.children.iter() {
+            match c {
+                Content::Element(e) => {
+                    if pretty {
+                        write!(w, "\n{:indent$}", "", indent = (depth + 1) * 2)?;
+                    }
+                    e.write_indented(w, mode, depth + 1)?;
+                }
+                Content::Text(t) => write_text(w, t, mode)?,
+            }
+        }
+        if pretty {
+            write!(w, "\n{:indent$}", "", indent = depth * 2)?;
+        }
+        write!(w, "</{}>", self.name)
+    }
+
+    /// Parse an XML document into its root Element
+    ///
+    /// This is a minimal parser which supports everything XER encoders produce: elements,
+    /// character data, entity and character references and CDATA sections. Attributes, comments,
+    /// processing instructions and the document type declaration are skipped.
+    pub fn parse(text: &str) -> io::Result<Element> {
+        let mut parser = Parser {
+            text,
+            pos: 0,
+            depth: 0,
+        };
+        parser.skip_misc()?;
+        let root = parser.element()?;
+        parser.skip_misc()?;
+        if parser.pos != text.len() {
+            return Err(parser.error("content after root element"));
+        }
+        Ok(root)
+    }
+}
+
+fn write_text(w: &mut dyn Write, text: &str, mode: Mode) -> io::Result<()> {
+    for c in text.chars() {
+        match c {
+            '&' => write!(w, "&amp;")?,
+            '<' => write!(w, "&lt;")?,
+            '>' => write!(w, "&gt;")?,
+            '\t' | '\n' if mode == Mode::Basic => write!(w, "{}", c)?,
+            '\u{0}'..='\u{1f}' => write!(w, "<{}/>", CONTROL_NAMES[c as usize])?,
+            _ => write!(w, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Replace CR LF and single CR by LF, as any XML processor does before parsing
+fn normalize_line_endings(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    /// Number of currently open elements
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("XML syntax error at byte {}: {}", self.pos, msg),
+        )
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches(is_whitespace).len();
+    }
+
+    fn skip_until(&mut self, end: &str) -> io::Result<&'a str> {
+        match self.rest().find(end) {
+            Some(i) => {
+                let skipped = &self.rest()[..i];
+                self.pos += i + end.len();
+                Ok(skipped)
+            }
+            None => Err(self.error(&format!("missing \"{}\"", end))),
+        }
+    }
+
+    /// Skip whitespace, comments, processing instructions and the doctype
+    fn skip_misc(&mut self) -> io::Result<()> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                self.skip_until(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> io::Result<&'a str> {
+        let rest = self.rest();
+        let length = rest
+            .find(|c: char| is_whitespace(c) || c == '>' || c == '/' || c == '=')
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error("expected name"));
+        }
+        self.pos += length;
+        Ok(&rest[..length])
+    }
+
+    fn expect(&mut self, s: &str) -> io::Result<()> {
+        if !self.rest().starts_with(s) {
+            return Err(self.error(&format!("expected \"{}\"", s)));
+        }
+        self.pos += s.len();
+        Ok(())
+    }
+
+    fn element(&mut self) -> io::Result<Element> {
+        self.expect("<")?;
+        let mut element = Element::new(self.name()?);
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            // Attributes are not used by XER, skip them
+            self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = if self.rest().starts_with('\'') {
+                "'"
+            } else {
+                "\""
+            };
+            self.expect(quote)?;
+            self.skip_until(quote)?;
+        }
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error(&format!("unclosed element <{}>", element.name)));
+            } else if rest.starts_with("</") {
+                self.pos += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(self.error(&format!(
+                        "closing tag </{}> does not match <{}>",
+                        name, element.name
+                    )));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += 9;
+                let text = self.skip_until("]]>")?;
+                element.push_text(&normalize_line_endings(text));
+            } else if rest.starts_with("<!--") {
+                // Only the comment itself, white space around it may be part of the text
+                self.skip_until("-->")?;
+            } else if rest.starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if rest.starts_with('<') {
+                if self.depth >= MAX_NESTING_DEPTH {
+                    return Err(self.error("elements are nested too deeply"));
+                }
+                self.depth += 1;
+                let child = self.element()?;
+                self.depth -= 1;
+                element.push(child);
+            } else {
+                let length = rest.find('<').unwrap_or(rest.len());
+                let text = self.unescape(&normalize_line_endings(&rest[..length]))?;
+                element.push_text(&text);
+                self.pos += length;
+            }
+        }
+    }
+
+    fn unescape(&self, text: &str) -> io::Result<String> {
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(i) = rest.find('&') {
+            result.push_str(&rest[..i]);
+            let end = rest[i..]
+                .find(';')
+                .ok_or_else(|| self.error("unterminated reference"))?;
+            let reference = &rest[i + 1..i + end];
+            let c = match reference {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                // Only digits, `from_str_radix` and `parse` also accept a sign
+                _ => match reference.strip_prefix("#x") {
+                    Some(hex) if hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+                        u32::from_str_radix(hex, 16).ok()
+                    }
+                    Some(_) => None,
+                    None => reference
+                        .strip_prefix('#')
+                        .filter(|d| d.chars().all(|c| c.is_ascii_digit()))
+                        .and_then(|d| d.parse().ok()),
+                }
+                .and_then(::core::char::from_u32),
+            };
+            match c {
+                Some(c) => result.push(c),
+                None => return Err(self.error(&format!("unknown reference &{};", reference))),
+            }
+            rest = &rest[i + end + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+}
+
+#[test]
+fn parse_and_write() {
+    let text = "<?xml version=\"1.0\"?>
+<!-- comment -->
+<Outer a='1'>
+  <Inner>a &lt;&amp;&gt; &#x41;<![CDATA[<&>]]></Inner>
+  <Empty />
+  <Escaped>x<nul/>y</Escaped>
+</Outer>";
+    let root = Element::parse(text).unwrap();
+    let children = root.elements().unwrap();
+    assert_eq!(children.len(), 3);
+    assert_eq!(children[0].text().unwrap(), "a <&> A<&>");
+    assert_eq!(children[2].text().unwrap(), "x\u{0}y");
+
+    let mut canonical = vec![];
+    for child in children {
+        child.write(&mut canonical, Mode::Canonical).unwrap();
+    }
+    assert_eq!(
+        String::from_utf8(canonical).unwrap(),
+        "<Inner>a &lt;&amp;&gt; A&lt;&amp;&gt;</Inner><Empty/><Escaped>x<nul/>y</Escaped>"
+    );
+
+    // White space next to a comment or processing instruction is part of the text
+    let root = Element::parse("<UTF8String>a<!--x--> b <?pi?>  c</UTF8String>").unwrap();
+    assert_eq!(root.text().unwrap(), "a b   c");
+}
+
+#[test]
+fn parse_rejects_malformed() {
+    for text in [
+        "<a>",
+        "<a></b>",
+        "<a>&foo;</a>",
+        "<a/><b/>",
+        "text",
+        "<a>&#x+41;</a>",
+        "<a>&#+65;</a>",
+    ] {
+        assert!(Element::parse(text).is_err(), "{}", text);
+    }
+    let nested = |depth: usize| format!("{}{}", "<a>".repeat(depth), "</a>".repeat(depth));
+    assert!(Element::parse(&nested(MAX_NESTING_DEPTH + 1)).is_ok());
+    let e = Element::parse(&nested(MAX_NESTING_DEPTH + 2)).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+    assert!(Element::parse(&"<a>".repeat(1_000_000)).is_err());
+}